@@ -1,30 +1,81 @@
 use std::env;
-use std::path::PathBuf;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
 use clap::{Parser, Subcommand};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use codecrafters_git::objects::object_base::GitObject;
-use codecrafters_git::repository;
+use codecrafters_git::{clone, ls_tree, refs, repository, rev_parse};
+use codecrafters_git::ls_tree::LsTreeOptions;
+use codecrafters_git::log::log_manager::{self, LogOptions};
+use codecrafters_git::log::pretty::LogFormat;
+use codecrafters_git::dircache::index_manager;
+use codecrafters_git::ignore::ignore_manager::{CheckIgnoreOptions, IgnoreChecker};
+use codecrafters_git::ignore::wildmatch::wildmatch;
 use codecrafters_git::objects::object_manager;
+use codecrafters_git::repository::InitOptions;
+use codecrafters_git::status::changes::UntrackedMode;
+use codecrafters_git::status::status_manager::{self, StatusFormat, StatusOptions};
+use codecrafters_git::config::config_manager::{self, ConfigAction, ConfigLocation, ConfigOptions, ValueType};
+use codecrafters_git::diff::algorithm::DiffAlgorithm;
+use codecrafters_git::diff::diff_manager::{self, DiffOptions};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
+    /// Run as if git was started in this directory (repeat to chain relative paths)
+    #[arg(short = 'C', value_name = "path")]
+    directories: Vec<PathBuf>,
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Subcommand, Debug)]
 enum Commands {
-    /// Initialize a new git repository in the working directory.
-    Init,
+    /// Create an empty git repository or reinitialize an existing one.
+    Init {
+        /// Create a bare repository
+        #[arg(long)]
+        bare: bool,
+        /// Name of the branch HEAD points to in the new repository
+        #[arg(short = 'b', long = "initial-branch", value_name = "branch-name")]
+        initial_branch: Option<String>,
+        /// Directory from which templates will be copied (empty to copy none)
+        #[arg(long, value_name = "template-directory")]
+        template: Option<PathBuf>,
+        /// Only print error and warning messages
+        #[arg(short, long)]
+        quiet: bool,
+        /// Directory of the repository, created if needed
+        directory: Option<PathBuf>,
+    },
 
     /// Display contents of a repository object.
     CatFile {
-        /// Pretty-print the contents of the object
-        #[arg(short, default_value_t = false)]
+        /// Pretty-print the contents of the object based on its type
+        #[arg(short = 'p', group = "mode")]
         pretty_print: bool,
+        /// Show the object type
+        #[arg(short = 't', group = "mode")]
+        show_type: bool,
+        /// Show the object size
+        #[arg(short = 's', group = "mode")]
+        show_size: bool,
+        /// Exit with zero status if the object exists
+        #[arg(short = 'e', group = "mode")]
+        check_exists: bool,
+        /// Print information and contents of each object named on stdin, with an optional format
+        #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "", group = "mode")]
+        batch: Option<String>,
+        /// Print information about each object named on stdin, with an optional format
+        #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "", group = "mode")]
+        batch_check: Option<String>,
+        /// With --batch or --batch-check, process every object in the repository instead of stdin
+        #[arg(long)]
+        batch_all_objects: bool,
+        /// The expected object type, or the object when no type is given
+        type_or_object: Option<String>,
         /// The name of the object to show
-        object: String,
+        object: Option<String>,
     },
 
     /// Compute and optionally write the hash of a file.
@@ -35,84 +86,931 @@ enum Commands {
         file_path: PathBuf,
     },
 
+    /// List the contents of a tree object.
     LsTree {
-        #[arg(long)]
+        /// Only show tree entries
+        #[arg(short = 'd')]
+        trees_only: bool,
+        /// Recurse into sub-trees
+        #[arg(short = 'r')]
+        recursive: bool,
+        /// Show tree entries even when going to recurse them
+        #[arg(short = 't')]
+        show_trees: bool,
+        /// Show the object size of blob entries
+        #[arg(short = 'l', long = "long")]
+        long: bool,
+        /// Terminate entries with NUL instead of newline
+        #[arg(short = 'z')]
+        null_terminated: bool,
+        /// List only file names
+        #[arg(long, alias = "name-status")]
         name_only: bool,
+        /// Show paths relative to the repository root instead of the current directory
+        #[arg(long)]
+        full_name: bool,
+        /// List the whole tree regardless of the current directory (implies --full-name)
+        #[arg(long)]
+        full_tree: bool,
+        /// The tree-ish to list
         object: String,
+        /// Only show these paths
+        paths: Vec<String>,
+    },
+
+    /// Create a tree object from the index.
+    WriteTree,
+
+    /// Add file contents to the index.
+    Add {
+        /// Allow adding otherwise ignored files
+        #[arg(short, long)]
+        force: bool,
+        /// Files or directories to add
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
     },
 
-    WriteTree {
-        #[arg(default_value_os_t = get_working_dir_path())]
-        path: PathBuf,
+    /// Remove files from the index and the working tree.
+    Rm {
+        /// Only remove from the index, keeping the working tree files
+        #[arg(long)]
+        cached: bool,
+        /// Allow recursive removal of directories
+        #[arg(short)]
+        recursive: bool,
+        /// Remove files even if their content would be lost
+        #[arg(short, long)]
+        force: bool,
+        /// Files or directories to remove
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
     },
 
     CommitTree {
         object: String,
-        #[arg(short, long)]
-        parent: Option<String>,
+        /// Parent commits, in order (repeat for merge commits)
+        #[arg(short, long = "parent")]
+        parents: Vec<String>,
         #[arg(short, long)]
         message: String,
     },
+
+    /// Clone a repository over the smart HTTP protocol into a new directory.
+    Clone {
+        /// URL of the remote repository
+        url: String,
+        /// Directory to clone into
+        directory: PathBuf,
+    },
+
+    /// Pick out and massage parameters: resolve revisions to object names.
+    RevParse {
+        /// Verify that exactly one parameter is given and that it names an object
+        #[arg(long)]
+        verify: bool,
+        /// Abbreviate the object names to a unique prefix (7 characters by default)
+        #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "7")]
+        short: Option<usize>,
+        /// Revisions to resolve
+        revisions: Vec<String>,
+    },
+
+    /// Show commit logs.
+    Log {
+        /// Shorthand for --format=oneline
+        #[arg(long)]
+        oneline: bool,
+        /// Limit the number of commits to output
+        #[arg(short = 'n', long = "max-count")]
+        max_count: Option<usize>,
+        /// Pretty-print format: oneline, short, medium, full, fuller, format:<string> or tformat:<string>
+        #[arg(long, alias = "pretty")]
+        format: Option<String>,
+        /// Draw a text-based graph of the commit history
+        #[arg(long)]
+        graph: bool,
+        /// Revisions to start from, HEAD by default
+        revisions: Vec<String>,
+        /// Only show commits modifying these paths
+        #[arg(last = true)]
+        paths: Vec<String>,
+    },
+
+    /// Create, list or delete tags.
+    Tag {
+        /// Make an annotated tag object
+        #[arg(short, long)]
+        annotate: bool,
+        /// Use the given tag message (implies -a)
+        #[arg(short, long)]
+        message: Option<String>,
+        /// List tags, optionally matching the given pattern
+        #[arg(short, long)]
+        list: bool,
+        /// Delete the tag
+        #[arg(short, long)]
+        delete: bool,
+        /// Replace an existing tag
+        #[arg(short, long)]
+        force: bool,
+        /// The tag name (or a pattern with -l)
+        name: Option<String>,
+        /// The object to tag, HEAD by default
+        object: Option<String>,
+    },
+
+    /// Update the object name stored in a ref safely.
+    UpdateRef {
+        /// Delete the ref (after verifying it still contains <new_value> if given)
+        #[arg(short)]
+        delete: bool,
+        /// Update the ref itself rather than the ref it points to
+        #[arg(long)]
+        no_deref: bool,
+        /// The ref to update
+        reference: String,
+        /// The new object name (or the expected value with -d)
+        new_value: Option<String>,
+        /// The value the ref must have before the update
+        old_value: Option<String>,
+    },
+
+    /// Read, modify and delete symbolic refs.
+    SymbolicRef {
+        /// Delete the symbolic ref
+        #[arg(short)]
+        delete: bool,
+        /// Shorten the ref name when reading (e.g. refs/heads/main -> main)
+        #[arg(long)]
+        short: bool,
+        /// The symbolic ref (e.g. HEAD)
+        name: String,
+        /// The ref it should point to
+        target: Option<String>,
+    },
+
+    /// List references in the local repository.
+    ShowRef {
+        /// Only show branches
+        #[arg(long)]
+        heads: bool,
+        /// Only show tags
+        #[arg(long)]
+        tags: bool,
+        /// Show the HEAD reference
+        #[arg(long)]
+        head: bool,
+        /// Only show the object names
+        #[arg(short = 's', long = "hash")]
+        hash_only: bool,
+        /// Require an exact ref name match
+        #[arg(long)]
+        verify: bool,
+        /// Only show refs matching one of these patterns
+        patterns: Vec<String>,
+    },
+
+    /// Record the changes staged in the index as a new commit on the current branch.
+    Commit {
+        /// The commit message; several -m options are joined as separate paragraphs
+        #[arg(short, long = "message", required = true)]
+        messages: Vec<String>,
+        /// Allow a commit with the same tree as its parent
+        #[arg(long)]
+        allow_empty: bool,
+    },
+
+    /// Pack reachable objects into a single packfile and prune packed loose objects.
+    Gc,
+
+    /// Debug gitignore and exclude rules: show which paths are ignored and why.
+    CheckIgnore {
+        /// Show the matching pattern, its file and line for each path
+        #[arg(short, long)]
+        verbose: bool,
+        /// Also show paths that match no pattern (requires --verbose)
+        #[arg(short, long, requires = "verbose")]
+        non_matching: bool,
+        /// Read paths from standard input, one per line
+        #[arg(long)]
+        stdin: bool,
+        /// Separate input and output records with NUL instead of newlines
+        #[arg(short = 'z')]
+        null_terminated: bool,
+        /// Print nothing, only set the exit status
+        #[arg(short, long, conflicts_with = "verbose")]
+        quiet: bool,
+        /// Also check paths tracked in the index
+        #[arg(long)]
+        no_index: bool,
+        /// The paths to check
+        #[arg(conflicts_with = "stdin")]
+        paths: Vec<String>,
+    },
+
+    /// Get and set repository or global options.
+    Config {
+        /// Use the global config file (~/.gitconfig)
+        #[arg(long, group = "location")]
+        global: bool,
+        /// Use the system-wide config file
+        #[arg(long, group = "location")]
+        system: bool,
+        /// Use the repository config file
+        #[arg(long, group = "location")]
+        local: bool,
+        /// Use the given config file
+        #[arg(short = 'f', long = "file", value_name = "file", group = "location")]
+        file: Option<PathBuf>,
+        /// Get the last value of a key
+        #[arg(long, group = "action")]
+        get: bool,
+        /// Get all values of a multi-valued key
+        #[arg(long, group = "action")]
+        get_all: bool,
+        /// Set a key, which must not have several values
+        #[arg(long, group = "action")]
+        set: bool,
+        /// Add a new value to a key without altering existing ones
+        #[arg(long, group = "action")]
+        add: bool,
+        /// Remove a key, which must not have several values
+        #[arg(long, group = "action")]
+        unset: bool,
+        /// Remove all values of a key
+        #[arg(long, group = "action")]
+        unset_all: bool,
+        /// List all variables set in the config files
+        #[arg(short, long, group = "action")]
+        list: bool,
+        /// Check values against a type and show them in canonical form
+        #[arg(long = "type", value_parser = ["bool", "int", "path"])]
+        value_type: Option<String>,
+        /// The key, as section[.subsection].name
+        name: Option<String>,
+        /// The value to set
+        value: Option<String>,
+    },
+
+    /// Show the working tree status: staged, unstaged and untracked changes.
+    Status {
+        /// Give the output in the short format
+        #[arg(short, long)]
+        short: bool,
+        /// Give the output in a stable, easy-to-parse format for scripts (v1 or v2)
+        #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "v1", value_parser = ["v1", "v2"])]
+        porcelain: Option<String>,
+        /// Terminate entries with NUL instead of LF, implying --porcelain=v1 if no format is given
+        #[arg(short = 'z')]
+        null_terminated: bool,
+        /// Show untracked files: no, normal (directories collapsed) or all
+        #[arg(short = 'u', long = "untracked-files", value_name = "mode", num_args = 0..=1,
+              default_missing_value = "all", value_parser = ["no", "normal", "all"])]
+        untracked_files: Option<String>,
+    },
+
+    /// Show changes between commits, the index and the working tree.
+    Diff {
+        /// Compare the index to a commit (HEAD by default) instead of the working tree
+        #[arg(long, alias = "staged")]
+        cached: bool,
+        /// Generate a patch (the default)
+        #[arg(short = 'p', short_alias = 'u', long)]
+        patch: bool,
+        /// Show a diffstat: an histogram of the changes of each file
+        #[arg(long)]
+        stat: bool,
+        /// Show the number of added and deleted lines of each file
+        #[arg(long)]
+        numstat: bool,
+        /// Show only the names and the status of changed files
+        #[arg(long)]
+        name_status: bool,
+        /// Number of context lines around each change
+        #[arg(short = 'U', long = "unified", value_name = "n", default_value_t = 3)]
+        unified: usize,
+        /// Diff algorithm: myers, minimal, patience or histogram
+        #[arg(long, value_name = "algorithm")]
+        diff_algorithm: Option<String>,
+        /// Commits, trees or blobs to compare, optionally followed by paths when -- is omitted
+        revisions: Vec<String>,
+        /// Only show changes of these paths
+        #[arg(last = true)]
+        paths: Vec<String>,
+    },
 }
 
 fn main() -> Result<()> {
     let args = Cli::parse();
 
+    for directory in &args.directories {
+        env::set_current_dir(directory)
+            .map_err(|e| anyhow!("cannot change to '{}': {}", directory.display(), e))?;
+    }
+    // Ces commandes créent le dépôt, ou n'en ont pas besoin : les autres se placent à sa racine
+    let needs_repository = !matches!(args.command,
+        Commands::Init { .. } | Commands::Clone { .. } | Commands::Config { .. } | Commands::HashObject { write_mode: false, .. });
+    if needs_repository {
+        repository::setup()?;
+    }
+
     match args.command {
-        Commands::Init => repository::init_repository(),
-        Commands::CatFile { pretty_print, object } => {
-            cat_file(&object, pretty_print)
+        Commands::Init { bare, initial_branch, template, quiet, directory } => {
+            let options = InitOptions { bare, initial_branch, template, quiet };
+            repository::init_repository(directory.as_deref(), &options)
+        }
+        Commands::CatFile { pretty_print, show_type, show_size, check_exists, batch, batch_check, batch_all_objects, type_or_object, object } => {
+            match (batch, batch_check, type_or_object, object) {
+                (Some(format), _, _, _) => cat_file_batch(&format, true, batch_all_objects),
+                (_, Some(format), _, _) => cat_file_batch(&format, false, batch_all_objects),
+                (_, _, Some(kind), Some(object)) => cat_file_typed(&kind, &object),
+                (_, _, Some(object), None) => cat_file(&object, pretty_print, show_type, show_size, check_exists),
+                _ => Err(anyhow!("usage: cat-file (-t | -s | -e | -p | <type>) <object> | (--batch | --batch-check) [--batch-all-objects]")),
+            }
         }
         Commands::HashObject { write_mode, file_path } => {
             hash_object(&file_path, write_mode)
         }
-        Commands::LsTree { name_only, object } => {
-            ls_tree(&object, name_only)
+        Commands::LsTree { trees_only, recursive, show_trees, long, null_terminated, name_only, full_name, full_tree, object, paths } => {
+            ls_tree(&object, &paths, full_name, full_tree, LsTreeOptions { recursive, show_trees, trees_only, long, name_only, null_terminated, ..LsTreeOptions::default() })
+        }
+        Commands::WriteTree => write_tree(),
+        Commands::Add { force, paths } => index_manager::add_paths(&work_tree_paths(&paths)?, force),
+        Commands::Rm { cached, recursive, force, paths } => rm(&paths, cached, recursive, force),
+        Commands::CommitTree { object, parents, message } => {
+            commit_tree(&object, &parents, &message)
+        }
+        Commands::Gc => gc(),
+        Commands::Commit { messages, allow_empty } => commit(&messages, allow_empty),
+        Commands::UpdateRef { delete, no_deref, reference, new_value, old_value } => {
+            update_ref(&reference, new_value.as_deref(), old_value.as_deref(), delete, no_deref)
+        }
+        Commands::SymbolicRef { delete, short, name, target } => {
+            symbolic_ref(&name, target.as_deref(), delete, short)
+        }
+        Commands::ShowRef { heads, tags, head, hash_only, verify, patterns } => {
+            show_ref(&patterns, heads, tags, head, hash_only, verify)
+        }
+        Commands::Clone { url, directory } => clone::clone_repository(&url, &directory),
+        Commands::RevParse { verify, short, revisions } => rev_parse(&revisions, verify, short),
+        Commands::Log { oneline, max_count, format, graph, revisions, paths } => {
+            log(&revisions, oneline, max_count, format.as_deref(), graph, paths)
+        }
+        Commands::Tag { annotate, message, list, delete, force, name, object } => {
+            tag(name.as_deref(), object.as_deref(), message.as_deref(), annotate, list, delete, force)
+        }
+        Commands::CheckIgnore { verbose, non_matching, stdin, null_terminated, quiet, no_index, paths } => {
+            let options = CheckIgnoreOptions { verbose, non_matching, null_terminated, quiet, no_index };
+            check_ignore(&paths, stdin, options)
+        }
+        Commands::Config { global, system, local, file, get, get_all, set, add, unset, unset_all, list, value_type, name, value } => {
+            let location = match file {
+                Some(file) => Some(ConfigLocation::File(file)),
+                None if global => Some(ConfigLocation::Global),
+                None if system => Some(ConfigLocation::System),
+                None if local => Some(ConfigLocation::Local),
+                None => None,
+            };
+            let value_type = value_type.map(|value_type| match value_type.as_str() {
+                "bool" => ValueType::Bool,
+                "int" => ValueType::Int,
+                _ => ValueType::Path,
+            });
+            // Sans option d'action, une clé seule est lue et une clé suivie d'une valeur est écrite
+            let action = match (name, value) {
+                (None, None) if list => ConfigAction::List,
+                (Some(name), None) if get_all => ConfigAction::GetAll(name),
+                (Some(name), None) if unset => ConfigAction::Unset(name),
+                (Some(name), None) if unset_all => ConfigAction::UnsetAll(name),
+                (Some(name), None) if !(set || add || list) => ConfigAction::Get(name),
+                (Some(name), Some(value)) if add => ConfigAction::Add(name, value),
+                (Some(name), Some(value)) if !(get || get_all || unset || unset_all || list) => ConfigAction::Set(name, value),
+                _ => return Err(anyhow!("wrong number of arguments")),
+            };
+            config(&action, &ConfigOptions { location, value_type })
+        }
+        Commands::Status { short, porcelain, null_terminated, untracked_files } => {
+            let format = match porcelain.as_deref() {
+                Some("v2") => StatusFormat::PorcelainV2,
+                Some(_) => StatusFormat::PorcelainV1,
+                None if short => StatusFormat::Short,
+                None => StatusFormat::Long,
+            };
+            let untracked = untracked_files.map(|mode| match mode.as_str() {
+                "no" => UntrackedMode::No,
+                "normal" => UntrackedMode::Normal,
+                _ => UntrackedMode::All,
+            });
+            let mut stdout = io::stdout().lock();
+            status_manager::status(&StatusOptions { format, null_terminated, untracked }, &mut stdout)?;
+            stdout.flush()?;
+            Ok(())
         }
-        Commands::WriteTree { path } => {
-            write_tree(&path)
-        },
-        Commands::CommitTree { object, parent, message } => {
-            commit_tree(&object, &parent, &message)
+        Commands::Diff { cached, patch, stat, numstat, name_status, unified, diff_algorithm, revisions, paths } => {
+            let (revisions, paths) = if paths.is_empty() {
+                split_revisions_and_paths(revisions)?
+            } else {
+                (revisions, paths)
+            };
+            let options = DiffOptions {
+                cached,
+                // Le diff unifié n'est affiché par défaut qu'en l'absence d'autre format
+                patch: patch || !(stat || numstat || name_status),
+                stat,
+                numstat,
+                name_status,
+                context_lines: unified,
+                algorithm: diff_algorithm.as_deref().map(DiffAlgorithm::parse).transpose()?,
+                paths: root_relative_paths(&paths)?,
+            };
+            let mut stdout = io::stdout().lock();
+            diff_manager::diff(&revisions, &options, &mut stdout)?;
+            stdout.flush()?;
+            Ok(())
         }
     }
 }
 
-fn commit_tree(object_name: &String, parent_name: &Option<String>, message: &String) -> Result<()> {
-    let object = object_manager::create_commit(object_name, parent_name, message)?;
+fn config(action: &ConfigAction, options: &ConfigOptions) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    let found = config_manager::config(action, options, &mut stdout)?;
+    stdout.flush()?;
+    // Codes de retour de git : 1 pour une clé lue absente, 5 pour une clé supprimée absente
+    match action {
+        ConfigAction::Get(_) | ConfigAction::GetAll(_) if !found => std::process::exit(1),
+        ConfigAction::Unset(_) | ConfigAction::UnsetAll(_) if !found => std::process::exit(5),
+        _ => Ok(()),
+    }
+}
+
+fn commit_tree(object_name: &str, parents: &[String], message: &str) -> Result<()> {
+    let tree_hash = rev_parse::resolve_revision_as(object_name, "tree")?;
+    let parents = parents.iter()
+        .map(|parent| rev_parse::resolve_revision_as(parent, "commit"))
+        .collect::<Result<Vec<_>>>()?;
+    let object = object_manager::create_commit(&tree_hash, &parents, message)?;
     println!("{}", object.get_hash());
     Ok(())
 }
 
-fn ls_tree(object_name: &String, name_only: bool) -> Result<()> {
-    let object = object_manager::read_object(object_name)?;
-    println!("{}", object.get_data()?);
+fn commit(messages: &[String], allow_empty: bool) -> Result<()> {
+    let tree = index_manager::write_tree()?;
+    let parent = refs::resolve_ref(refs::HEAD)?;
+
+    if !allow_empty {
+        match &parent {
+            Some(parent) if object_manager::read_commit_tree(parent)? == tree.get_hash() => {
+                return Err(anyhow!("nothing to commit, working tree clean"));
+            }
+            None if object_manager::read_tree(tree.get_hash())?.entries.is_empty() => {
+                return Err(anyhow!("nothing to commit (create/copy files and use \"git add\" to track)"));
+            }
+            _ => {}
+        }
+    }
+
+    let message = messages.join("\n\n");
+    let parents: Vec<String> = parent.iter().cloned().collect();
+    let commit = object_manager::create_commit(tree.get_hash(), &parents, &message)?;
+
+    // La vérification de l'ancienne valeur protège contre une mise à jour concurrente de la branche
+    let expected = parent.clone().unwrap_or_else(|| refs::NULL_HASH.to_string());
+    let subject = message.lines().next().unwrap_or_default();
+    let reflog_kind = if parent.is_none() { "commit (initial)" } else { "commit" };
+    refs::update_ref(refs::HEAD, commit.get_hash(), Some(&expected), &format!("{}: {}", reflog_kind, subject))?;
+
+    let branch = refs::resolve_ref_name(refs::HEAD)?;
+    let branch = match branch.strip_prefix("refs/heads/") {
+        Some(name) => name.to_string(),
+        None => "detached HEAD".to_string(),
+    };
+    let root = if parent.is_none() { " (root-commit)" } else { "" };
+    println!("[{}{} {}] {}", branch, root, &commit.get_hash()[..7], subject);
     Ok(())
 }
 
-fn write_tree(path: &PathBuf) -> Result<()> {
-    let object = object_manager::create_object(path)?;
+fn update_ref(reference: &str, new_value: Option<&str>, old_value: Option<&str>, delete: bool, no_deref: bool) -> Result<()> {
+    if delete {
+        // Avec -d, la seconde valeur éventuelle est la valeur attendue
+        if old_value.is_some() {
+            return Err(anyhow!("usage: update-ref -d <ref> [<old-value>]"));
+        }
+        let expected = new_value.map(rev_parse::resolve_revision).transpose()?;
+        let name = if no_deref { reference.to_string() } else { refs::resolve_ref_name(reference)? };
+        return refs::delete_ref(&name, expected.as_deref());
+    }
+
+    let new_value = new_value.ok_or_else(|| anyhow!("usage: update-ref <ref> <new-value> [<old-value>]"))?;
+    let new_hash = rev_parse::resolve_existing_revision(new_value)?;
+    let old_hash = old_value
+        .map(|value| match value {
+            "" | refs::NULL_HASH => Ok(refs::NULL_HASH.to_string()),
+            _ => rev_parse::resolve_revision(value),
+        })
+        .transpose()?;
+
+    if no_deref {
+        refs::update_ref_no_deref(reference, &new_hash, old_hash.as_deref(), "")
+    } else {
+        refs::update_ref(reference, &new_hash, old_hash.as_deref(), "")
+    }
+}
+
+fn symbolic_ref(name: &str, target: Option<&str>, delete: bool, short: bool) -> Result<()> {
+    if delete {
+        if refs::read_symbolic_ref(name)?.is_none() {
+            return Err(anyhow!("Cannot delete {}, not a symbolic ref", name));
+        }
+        return refs::delete_ref(name, None);
+    }
+
+    if let Some(target) = target {
+        return refs::set_symbolic_ref(name, target);
+    }
+
+    let target = refs::read_symbolic_ref(name)?.ok_or_else(|| anyhow!("ref {} is not a symbolic ref", name))?;
+    if short {
+        let short_name = ["refs/heads/", "refs/tags/", "refs/remotes/", "refs/"].iter()
+            .find_map(|prefix| target.strip_prefix(prefix))
+            .unwrap_or(&target);
+        println!("{}", short_name);
+    } else {
+        println!("{}", target);
+    }
+    Ok(())
+}
+
+fn show_ref(patterns: &[String], heads: bool, tags: bool, head: bool, hash_only: bool, verify: bool) -> Result<()> {
+    let mut matches = vec![];
+
+    if verify {
+        for pattern in patterns {
+            let hash = match refs::check_ref_format(pattern) {
+                Ok(()) => refs::resolve_ref(pattern)?,
+                Err(_) => None,
+            };
+            match hash {
+                Some(hash) if pattern == refs::HEAD || pattern.starts_with("refs/") => matches.push((pattern.clone(), hash)),
+                _ => return Err(anyhow!("'{}' - not a valid ref", pattern)),
+            }
+        }
+    } else {
+        if head {
+            if let Some(hash) = refs::resolve_ref(refs::HEAD)? {
+                matches.push((refs::HEAD.to_string(), hash));
+            }
+        }
+        for (name, hash) in refs::list_refs("refs/")? {
+            let kind_matches = (!heads && !tags)
+                || (heads && name.starts_with("refs/heads/"))
+                || (tags && name.starts_with("refs/tags/"));
+            // Un motif correspond à la fin du nom, sur des composants entiers
+            let pattern_matches = patterns.is_empty()
+                || patterns.iter().any(|pattern| name == *pattern || name.ends_with(&format!("/{}", pattern)));
+            if kind_matches && pattern_matches {
+                matches.push((name, hash));
+            }
+        }
+    }
+
+    if matches.is_empty() {
+        std::process::exit(1);
+    }
+    for (name, hash) in matches {
+        if hash_only {
+            println!("{}", hash);
+        } else {
+            println!("{} {}", hash, name);
+        }
+    }
+    Ok(())
+}
+
+fn rev_parse(revisions: &[String], verify: bool, short: Option<usize>) -> Result<()> {
+    if verify && revisions.len() != 1 {
+        return Err(anyhow!("Needed a single revision"));
+    }
+
+    for revision in revisions {
+        let hash = rev_parse::resolve_revision(revision)?;
+        if verify && !object_manager::has_object(&hash)? {
+            return Err(anyhow!("Needed a single revision"));
+        }
+        match short {
+            Some(length) => println!("{}", rev_parse::abbreviate(&hash, length)?),
+            None => println!("{}", hash),
+        }
+    }
+    Ok(())
+}
+
+fn log(revisions: &[String], oneline: bool, max_count: Option<usize>, format: Option<&str>, graph: bool, paths: Vec<String>) -> Result<()> {
+    let format = match format {
+        Some(format) => LogFormat::parse(format)?,
+        None if oneline => LogFormat::Oneline,
+        None => LogFormat::Medium,
+    };
+
+    let starts = if revisions.is_empty() {
+        // Une branche sans commit n'a pas d'historique
+        let head = refs::resolve_ref(refs::HEAD)?;
+        let branch = refs::resolve_ref_name(refs::HEAD)?;
+        vec![head.ok_or_else(|| anyhow!("your current branch '{}' does not have any commits yet",
+            branch.trim_start_matches("refs/heads/")))?]
+    } else {
+        revisions.iter().map(|revision| rev_parse::resolve_revision_as(revision, "commit")).collect::<Result<_>>()?
+    };
+
+    let paths = root_relative_paths(&paths)?;
+    log_manager::log(&starts, &LogOptions { format, max_count, graph, paths })
+}
+
+fn tag(name: Option<&str>, object: Option<&str>, message: Option<&str>, annotate: bool, list: bool, delete: bool, force: bool) -> Result<()> {
+    let name = match name {
+        Some(name) if !list => name,
+        // Sans nom, ou avec -l, les tags sont listés
+        pattern => {
+            for (reference, _) in refs::list_refs("refs/tags/")? {
+                let tag_name = &reference["refs/tags/".len()..];
+                if pattern.map_or(true, |pattern| wildmatch(pattern.as_bytes(), tag_name.as_bytes(), false)) {
+                    println!("{}", tag_name);
+                }
+            }
+            return Ok(());
+        }
+    };
+
+    let reference = format!("refs/tags/{}", name);
+    refs::check_ref_format(&reference).map_err(|_| anyhow!("'{}' is not a valid tag name.", name))?;
+    let existing = refs::resolve_ref(&reference)?;
+
+    if delete {
+        let hash = existing.ok_or_else(|| anyhow!("tag '{}' not found.", name))?;
+        refs::delete_ref(&reference, Some(&hash))?;
+        println!("Deleted tag '{}' (was {})", name, &hash[..7]);
+        return Ok(());
+    }
+
+    if existing.is_some() && !force {
+        return Err(anyhow!("tag '{}' already exists", name));
+    }
+
+    let target = rev_parse::resolve_existing_revision(object.unwrap_or(refs::HEAD))?;
+    let hash = if annotate || message.is_some() {
+        let message = message.ok_or_else(|| anyhow!("no tag message given, use -m <message>"))?;
+        object_manager::create_tag(&target, name, message)?.get_hash().to_string()
+    } else {
+        target
+    };
+
+    let expected = existing.unwrap_or_else(|| refs::NULL_HASH.to_string());
+    refs::update_ref(&reference, &hash, Some(&expected), "")?;
+    if force && expected != refs::NULL_HASH && expected != hash {
+        println!("Updated tag '{}' (was {})", name, &expected[..7]);
+    }
+    Ok(())
+}
+
+fn check_ignore(paths: &[String], from_stdin: bool, options: CheckIgnoreOptions) -> Result<()> {
+    if !from_stdin && paths.is_empty() {
+        return Err(anyhow!("no path specified"));
+    }
+    if options.quiet && (from_stdin || paths.len() > 1) {
+        return Err(anyhow!("--quiet is only valid with a single pathname"));
+    }
+
+    let separator = if options.null_terminated { b'\0' } else { b'\n' };
+    let mut checker = IgnoreChecker::new(options)?;
+    let mut stdout = io::stdout().lock();
+    let mut ignored = false;
+
+    if from_stdin {
+        // Chaque résultat est écrit aussitôt, pour un usage interactif à travers un tube
+        for line in io::stdin().lock().split(separator) {
+            let path = String::from_utf8(line?)?;
+            ignored |= checker.check(&path, &mut stdout)?;
+            stdout.flush()?;
+        }
+    } else {
+        for path in paths {
+            ignored |= checker.check(path, &mut stdout)?;
+        }
+    }
+
+    stdout.flush()?;
+    if !ignored {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn gc() -> Result<()> {
+    let summary = object_manager::gc()?;
+    match summary.pack_name {
+        Some(pack_name) => println!(
+            "Packed {} objects into {}.pack, pruned {} loose objects",
+            summary.object_count, pack_name, summary.pruned_count
+        ),
+        None => println!("Nothing to pack"),
+    }
+    Ok(())
+}
+
+fn ls_tree(object_name: &str, paths: &[String], full_name: bool, full_tree: bool, mut options: LsTreeOptions) -> Result<()> {
+    let tree_hash = rev_parse::resolve_revision_as(object_name, "tree")?;
+
+    // Comme git, les chemins sont relatifs au répertoire courant, qui est listé par défaut
+    let prefix = repository::current()?.prefix().to_string_lossy().into_owned();
+    options.paths = if full_tree {
+        paths.to_vec()
+    } else if paths.is_empty() {
+        if prefix.is_empty() { vec![] } else { vec![format!("{}/", prefix)] }
+    } else {
+        paths.iter()
+            .map(|path| {
+                let root_path = work_tree_paths(&[PathBuf::from(path)])?.remove(0);
+                let root_path = root_path.to_string_lossy();
+                // Un `/` final, ou `.`, désigne le contenu du dossier plutôt que le dossier
+                let contents = path.ends_with('/') || path == "." || path.ends_with("/.") || path.ends_with("..");
+                Ok(if contents && root_path != "." { format!("{}/", root_path) } else { root_path.into_owned() })
+            })
+            .collect::<Result<_>>()?
+    };
+    if !(full_name || full_tree || prefix.is_empty()) {
+        options.prefix = Some(prefix.into_bytes());
+    }
+    ls_tree::ls_tree(&tree_hash, &options, &mut io::stdout().lock())
+}
+
+/// Convertit des chemins relatifs au répertoire de l'utilisateur en chemins relatifs à la racine du dépôt.
+fn work_tree_paths(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let repository = repository::current()?;
+    paths.iter().map(|path| repository.work_tree_path(path)).collect()
+}
+
+/// Convertit les chemins donnés à une commande en chemins relatifs à la racine, `.` désignant la racine.
+fn root_relative_paths(paths: &[String]) -> Result<Vec<String>> {
+    Ok(work_tree_paths(&paths.iter().map(PathBuf::from).collect::<Vec<_>>())?
+        .iter()
+        .map(|path| if path == Path::new(".") { String::new() } else { path.to_string_lossy().into_owned() })
+        .collect())
+}
+
+/// Sépare les arguments donnés sans `--` entre révisions et chemins, comme git : les premiers
+/// arguments qui se résolvent en révision (ou en intervalle `A..B`) sont des révisions, tous
+/// les suivants doivent être des chemins existants du répertoire de travail.
+fn split_revisions_and_paths(mut revisions: Vec<String>) -> Result<(Vec<String>, Vec<String>)> {
+    let first_path = revisions.iter().position(|argument| !is_revision(argument)).unwrap_or(revisions.len());
+    let paths = revisions.split_off(first_path);
+
+    let repository = repository::current()?;
+    for path in &paths {
+        // Le répertoire courant est la racine du dépôt depuis `repository::setup`
+        let exists = repository.work_tree_path(Path::new(path)).is_ok_and(|path| path.symlink_metadata().is_ok());
+        if !exists {
+            return Err(anyhow!(
+                "ambiguous argument '{}': unknown revision or path not in the working tree.\n\
+                Use '--' to separate paths from revisions, like this:\n\
+                'git <command> [<revision>...] -- [<file>...]'",
+                path
+            ));
+        }
+    }
+    Ok((revisions, paths))
+}
+
+/// Indique si un argument désigne une révision ou un intervalle `A..B` de révisions.
+fn is_revision(argument: &str) -> bool {
+    match argument.split_once("..") {
+        Some(("", "")) => false,
+        Some((old, new)) => [old, new].iter().all(|side| side.is_empty() || rev_parse::resolve_revision(side).is_ok()),
+        None => rev_parse::resolve_revision(argument).is_ok(),
+    }
+}
+
+fn write_tree() -> Result<()> {
+    let object = index_manager::write_tree()?;
     println!("{}", object.get_hash());
     Ok(())
 }
 
-fn cat_file(object_name: &str, pretty_print: bool) -> Result<()> {
-    let object = object_manager::read_object(object_name)?;
+fn rm(paths: &[PathBuf], cached: bool, recursive: bool, force: bool) -> Result<()> {
+    for path in index_manager::remove_paths(&work_tree_paths(paths)?, cached, recursive, force)? {
+        println!("rm '{}'", path);
+    }
+    Ok(())
+}
+
+fn cat_file(object_name: &str, pretty_print: bool, show_type: bool, show_size: bool, check_exists: bool) -> Result<()> {
+    let hash = rev_parse::resolve_revision(object_name)?;
+
+    if check_exists {
+        std::process::exit(if object_manager::has_object(&hash)? { 0 } else { 1 });
+    }
 
-    if pretty_print {
-        print!("{}", object.get_data()?)
+    let (kind, size) = object_manager::read_object_header(&hash)?;
+    let mut stdout = io::stdout().lock();
+    if show_type {
+        writeln!(stdout, "{}", kind)?;
+    } else if show_size {
+        writeln!(stdout, "{}", size)?;
+    } else if pretty_print {
+        // Les arbres sont affichés comme par ls-tree, les autres objets tels quels
+        if kind == "tree" {
+            ls_tree::ls_tree(&hash, &LsTreeOptions::default(), &mut stdout)?;
+        } else {
+            object_manager::copy_object_content(&hash, &mut stdout)?;
+        }
     } else {
-        // print!("{:?}", object)
+        return Err(anyhow!("usage: cat-file (-t | -s | -e | -p | <type>) <object>"));
     }
     Ok(())
 }
 
-fn hash_object(path: &PathBuf, write_mode: bool) -> Result<()> {
-    let object = object_manager::create_object(path)?;
-    print!("{}", object.get_hash());
+/// Affiche le contenu brut d'un objet du type demandé, en déréférençant tags et commits.
+fn cat_file_typed(kind: &str, object_name: &str) -> Result<()> {
+    let hash = rev_parse::resolve_revision_as(object_name, kind)?;
+    object_manager::copy_object_content(&hash, &mut io::stdout().lock())?;
+    Ok(())
+}
+
+/// Traite les objets nommés sur l'entrée standard, un par ligne, ou tous les objets du dépôt.
+///
+/// # Paramètres
+/// - `format` : format de la ligne d'information (`%(objectname)`, `%(objecttype)`,
+///   `%(objectsize)`, `%(rest)`), vide pour le format par défaut.
+/// - `with_contents` : ajoute le contenu de chaque objet après sa ligne d'information.
+/// - `all_objects` : parcourt tous les objets au lieu de lire l'entrée standard.
+fn cat_file_batch(format: &str, with_contents: bool, all_objects: bool) -> Result<()> {
+    let format = if format.is_empty() { "%(objectname) %(objecttype) %(objectsize)" } else { format };
+    let mut stdout = io::stdout().lock();
+
+    if all_objects {
+        for hash in object_manager::list_objects()? {
+            cat_file_batch_object(&hash, "", format, with_contents, &mut stdout)?;
+        }
+        return Ok(());
+    }
+
+    // Avec %(rest), seul le premier mot de la ligne désigne l'objet
+    let split_rest = format.contains("%(rest)");
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let (name, rest) = match line.split_once(char::is_whitespace) {
+            Some((name, rest)) if split_rest => (name, rest.trim_start()),
+            _ => (line.as_str(), ""),
+        };
+        cat_file_batch_object(name, rest, format, with_contents, &mut stdout)?;
+        // Permet de dialoguer avec le processus ligne par ligne
+        stdout.flush()?;
+    }
     Ok(())
 }
 
-fn get_working_dir_path() -> PathBuf {
-    env::current_dir().unwrap()
-}
\ No newline at end of file
+fn cat_file_batch_object<W: Write>(name: &str, rest: &str, format: &str, with_contents: bool, out: &mut W) -> Result<()> {
+    let is_short_hash = name.len() < 40 && !name.is_empty() && name.chars().all(|c| c.is_ascii_hexdigit());
+    if is_short_hash && object_manager::find_objects_by_prefix(&name.to_lowercase())?.len() > 1 {
+        writeln!(out, "{} ambiguous", name)?;
+        return Ok(());
+    }
+
+    let object = rev_parse::resolve_revision(name)
+        .and_then(|hash| Ok((object_manager::read_object_header(&hash)?, hash)));
+    let Ok(((kind, size), hash)) = object else {
+        writeln!(out, "{} missing", name)?;
+        return Ok(());
+    };
+
+    let mut info = String::new();
+    let mut remaining = format;
+    while let Some(start) = remaining.find("%(") {
+        info.push_str(&remaining[..start]);
+        let end = remaining[start..].find(')')
+            .ok_or_else(|| anyhow!("unterminated format element in '{}'", format))? + start;
+        match &remaining[start + 2..end] {
+            "objectname" => info.push_str(&hash),
+            "objecttype" => info.push_str(&kind),
+            "objectsize" => info.push_str(&size.to_string()),
+            "rest" => info.push_str(rest),
+            atom => return Err(anyhow!("unknown format element: {}", atom)),
+        }
+        remaining = &remaining[end + 1..];
+    }
+    info.push_str(remaining);
+
+    writeln!(out, "{}", info)?;
+    if with_contents {
+        object_manager::copy_object_content(&hash, out)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn hash_object(path: &Path, write_mode: bool) -> Result<()> {
+    // Avec -w, le processus s'est placé à la racine du dépôt
+    let path = if write_mode { repository::current()?.prefix().join(path) } else { path.to_path_buf() };
+    let hash = object_manager::hash_file(&path, write_mode)?;
+    print!("{}", hash);
+    Ok(())
+}
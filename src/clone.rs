@@ -0,0 +1,113 @@
+use crate::config::config_file::ConfigFile;
+use crate::dircache::index_manager;
+use crate::objects::object_manager;
+use crate::refs;
+use crate::repository::{self, InitOptions, Repository};
+use crate::transport::http::{RemoteRef, SmartHttpClient};
+use anyhow::{anyhow, Result};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const REMOTE_NAME: &str = "origin";
+const BRANCH_PREFIX: &str = "refs/heads/";
+const TAG_PREFIX: &str = "refs/tags/";
+
+/// Clone un dépôt distant via le protocole smart HTTP dans `directory`.
+///
+/// Le répertoire de travail du processus devient `directory`.
+pub fn clone_repository(url: &str, directory: &Path) -> Result<()> {
+    if directory.exists() && fs::read_dir(directory)?.next().is_some() {
+        return Err(anyhow!("destination path {:?} already exists and is not an empty directory", directory));
+    }
+    fs::create_dir_all(directory)?;
+    std::env::set_current_dir(directory)?;
+    repository::create_git_directory(Path::new(repository::DOT_GIT), &InitOptions::default())?;
+    let repository = repository::set_current(Repository::open(Path::new(repository::DOT_GIT), Some(Path::new(".")))?);
+
+    let client = SmartHttpClient::connect(url)?;
+    let refs = client.list_refs()?;
+    write_remote_config(&repository, url)?;
+
+    let mut wants: Vec<String> = refs.iter()
+        .filter(|reference| reference.name == "HEAD" || reference.name.starts_with(BRANCH_PREFIX) || reference.name.starts_with(TAG_PREFIX))
+        .map(|reference| reference.hash.clone())
+        .collect();
+    wants.sort();
+    wants.dedup();
+
+    if wants.is_empty() {
+        eprintln!("warning: You appear to have cloned an empty repository.");
+        return Ok(());
+    }
+
+    // Le pack est d'abord reçu dans un fichier temporaire, puis indexé et renommé
+    let pack_dir = repository.objects_dir().join("pack");
+    fs::create_dir_all(&pack_dir)?;
+    let tmp_pack_path = pack_dir.join("tmp_pack_incoming");
+    {
+        let mut writer = BufWriter::new(File::create(&tmp_pack_path)?);
+        client.fetch_pack(&wants, &mut writer)?;
+        writer.flush()?;
+    }
+    let (_, object_count) = object_manager::index_pack(&tmp_pack_path)?;
+    eprintln!("Received {} objects (protocol v{})", object_count, client.protocol_version());
+
+    let reflog_message = format!("clone: from {}", url);
+    for reference in &refs {
+        if let Some(branch) = reference.name.strip_prefix(BRANCH_PREFIX) {
+            refs::update_ref(&format!("refs/remotes/{}/{}", REMOTE_NAME, branch), &reference.hash, None, &reflog_message)?;
+        } else if reference.name.starts_with(TAG_PREFIX) {
+            refs::update_ref(&reference.name, &reference.hash, None, &reflog_message)?;
+        }
+    }
+
+    let Some(head) = refs.iter().find(|reference| reference.name == "HEAD") else {
+        eprintln!("warning: remote HEAD refers to nonexistent ref, unable to checkout");
+        return Ok(());
+    };
+
+    match default_branch(head, &refs) {
+        Some(branch) => {
+            // HEAD pointe d'abord sur la branche pour que sa création figure aussi dans son journal
+            refs::set_symbolic_ref(refs::HEAD, &format!("{}{}", BRANCH_PREFIX, branch))?;
+            refs::update_ref(refs::HEAD, &head.hash, None, &reflog_message)?;
+            refs::set_symbolic_ref(
+                &format!("refs/remotes/{}/HEAD", REMOTE_NAME),
+                &format!("refs/remotes/{}/{}", REMOTE_NAME, branch),
+            )?;
+            let mut config = ConfigFile::open(&repository.path("config"))?;
+            config.set(&format!("branch.{}.remote", branch), REMOTE_NAME)?;
+            config.set(&format!("branch.{}.merge", branch), &format!("{}{}", BRANCH_PREFIX, branch))?;
+            config.save()?;
+        }
+        // HEAD détachée côté serveur
+        None => refs::update_ref_no_deref(refs::HEAD, &head.hash, None, &reflog_message)?,
+    }
+
+    object_manager::checkout_commit(&head.hash, Path::new("."))?;
+    index_manager::reset_index(&object_manager::read_commit_tree(&head.hash)?)
+}
+
+/// Détermine la branche pointée par le HEAD distant.
+fn default_branch<'a>(head: &'a RemoteRef, refs: &'a [RemoteRef]) -> Option<&'a str> {
+    if let Some(target) = &head.symref_target {
+        return target.strip_prefix(BRANCH_PREFIX);
+    }
+
+    // Sans information symbolique, on devine à partir du hash (en privilégiant main/master)
+    let mut candidates: Vec<&str> = refs.iter()
+        .filter(|reference| reference.hash == head.hash)
+        .filter_map(|reference| reference.name.strip_prefix(BRANCH_PREFIX))
+        .collect();
+    candidates.sort_by_key(|branch| !matches!(*branch, "main" | "master"));
+    candidates.first().copied()
+}
+
+fn write_remote_config(repository: &Repository, url: &str) -> Result<()> {
+    let mut config = ConfigFile::open(&repository.path("config"))?;
+    config.set(&format!("remote.{}.url", REMOTE_NAME), url)?;
+    config.set(&format!("remote.{}.fetch", REMOTE_NAME), &format!("+refs/heads/*:refs/remotes/{}/*", REMOTE_NAME))?;
+    config.save()
+}
+
@@ -0,0 +1,235 @@
+use crate::config::parser::{self, ConfigItem};
+use anyhow::{anyhow, Result};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+const LOCK_EXTENSION: &str = ".lock";
+
+/// Un fichier de configuration, modifiable sans perdre ses commentaires ni sa mise en forme.
+///
+/// Les modifications ne touchent que les lignes concernées ; le reste du texte est conservé tel quel.
+#[derive(Debug)]
+pub struct ConfigFile {
+    path: PathBuf,
+    content: String,
+    items: Vec<ConfigItem>,
+}
+
+impl ConfigFile {
+    /// Lit un fichier de configuration ; un fichier absent est vide.
+    pub fn open(path: &Path) -> Result<ConfigFile> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(anyhow!("unable to read config file '{}': {}", path.display(), e)),
+        };
+        let items = parser::parse(&content, &path.to_string_lossy())?;
+        Ok(ConfigFile { path: path.to_path_buf(), content, items })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Entrées du fichier dans l'ordre : clé canonique et valeur (`None` pour une clé sans `=`).
+    pub fn entries(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.items.iter().filter_map(|item| match item {
+            ConfigItem::Entry { key, value, .. } => Some((key.as_str(), value.as_deref())),
+            ConfigItem::Section { .. } => None,
+        })
+    }
+
+    /// Dernière valeur de la clé dans ce fichier.
+    ///
+    /// # Renvoie
+    /// - `None` si la clé est absente, `Some(None)` pour une clé sans `=`.
+    pub fn get(&self, key: &str) -> Result<Option<Option<&str>>> {
+        let key = canonical_key(key)?;
+        Ok(self.entries().filter(|(name, _)| *name == key).last().map(|(_, value)| value))
+    }
+
+    /// Donne une valeur à une clé : remplace son unique occurrence ou l'ajoute à sa section.
+    ///
+    /// # Erreurs
+    /// - Retourne une erreur si la clé a plusieurs valeurs.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let canonical = canonical_key(key)?;
+        let spans = self.entry_spans(&canonical);
+        match spans.as_slice() {
+            [] => self.add(key, value),
+            [span] => {
+                let line = format_entry(key, value);
+                self.replace(span.clone(), &line)
+            }
+            _ => Err(anyhow!("cannot overwrite multiple values with a single value\n       Use --add or --unset-all to change {}.", canonical)),
+        }
+    }
+
+    /// Ajoute une valeur à une clé, en plus des éventuelles valeurs existantes.
+    ///
+    /// La ligne est placée après la dernière entrée de la dernière section correspondante,
+    /// ou dans une nouvelle section en fin de fichier. Les noms écrits gardent la casse de `key`.
+    pub fn add(&mut self, key: &str, value: &str) -> Result<()> {
+        let canonical = canonical_key(key)?;
+        let (section, subsection, _) = split_key(&canonical);
+
+        let mut insert_at = None;
+        let mut in_section = false;
+        for item in &self.items {
+            match item {
+                ConfigItem::Section { name, subsection: item_subsection, span } => {
+                    in_section = name == section && item_subsection.as_deref() == subsection;
+                    if in_section {
+                        insert_at = Some(self.line_end(span.end));
+                    }
+                }
+                ConfigItem::Entry { span, .. } if in_section => insert_at = Some(span.end),
+                ConfigItem::Entry { .. } => {}
+            }
+        }
+
+        let line = format_entry(key, value);
+        match insert_at {
+            Some(position) => self.replace(position..position, &line),
+            None => {
+                let end = self.content.len();
+                let (section, subsection, _) = split_key(key);
+                let header = format_section_header(section, subsection);
+                self.replace(end..end, &format!("{}\n{}", header, line))
+            }
+        }
+    }
+
+    /// Supprime une clé.
+    ///
+    /// # Paramètres
+    /// - `all` : supprime toutes ses valeurs ; sinon, la clé ne doit en avoir qu'une.
+    ///
+    /// # Renvoie
+    /// - Le nombre de valeurs supprimées.
+    pub fn unset(&mut self, key: &str, all: bool) -> Result<usize> {
+        let key = canonical_key(key)?;
+        let spans = self.entry_spans(&key);
+        if spans.len() > 1 && !all {
+            return Err(anyhow!("{} has multiple values", key));
+        }
+
+        // De la fin vers le début, pour que les positions restent valables
+        for span in spans.iter().rev() {
+            self.content.replace_range(span.clone(), "");
+        }
+        self.items = parser::parse(&self.content, &self.path.to_string_lossy())?;
+        Ok(spans.len())
+    }
+
+    /// Écrit le fichier de façon atomique, via un fichier `<nom>.lock` renommé.
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut lock_name = self.path.as_os_str().to_owned();
+        lock_name.push(LOCK_EXTENSION);
+        let lock_path = PathBuf::from(lock_name);
+        let mut lock = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+            .map_err(|e| anyhow!("could not lock config file {}: {}", self.path.display(), e))?;
+
+        let result = lock.write_all(self.content.as_bytes())
+            .and_then(|_| lock.sync_all())
+            .and_then(|_| fs::rename(&lock_path, &self.path));
+        if result.is_err() {
+            let _ = fs::remove_file(&lock_path);
+        }
+        Ok(result?)
+    }
+
+    fn entry_spans(&self, key: &str) -> Vec<Range<usize>> {
+        self.items.iter().filter_map(|item| match item {
+            ConfigItem::Entry { key: name, span, .. } if name == key => Some(span.clone()),
+            _ => None,
+        }).collect()
+    }
+
+    /// Position juste après la fin de la ligne qui contient `position`.
+    fn line_end(&self, position: usize) -> usize {
+        self.content[position..].find('\n').map_or(self.content.len(), |offset| position + offset + 1)
+    }
+
+    fn replace(&mut self, range: Range<usize>, text: &str) -> Result<()> {
+        // Un texte inséré commence toujours sur une nouvelle ligne
+        let needs_newline = range.start > 0 && !self.content[..range.start].ends_with('\n');
+        let text = if needs_newline { format!("\n{}", text) } else { text.to_string() };
+        self.content.replace_range(range, &text);
+        self.items = parser::parse(&self.content, &self.path.to_string_lossy())?;
+        Ok(())
+    }
+}
+
+/// Met une clé sous sa forme canonique : section et nom en minuscules, sous-section inchangée.
+///
+/// # Erreurs
+/// - Retourne une erreur si la clé n'a pas de section ou de nom valide.
+pub fn canonical_key(key: &str) -> Result<String> {
+    let (Some(first_dot), Some(last_dot)) = (key.find('.'), key.rfind('.')) else {
+        return Err(anyhow!("key does not contain a section: {}", key));
+    };
+    let section = &key[..first_dot];
+    let name = &key[last_dot + 1..];
+
+    let valid_section = !section.is_empty() && section.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    let valid_name = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !valid_section || !valid_name {
+        return Err(anyhow!("invalid key: {}", key));
+    }
+
+    let subsection = &key[first_dot..last_dot];
+    Ok(format!("{}{}.{}", section.to_ascii_lowercase(), subsection, name.to_ascii_lowercase()))
+}
+
+/// Découpe une clé canonique en section, sous-section éventuelle et nom.
+pub(crate) fn split_key(key: &str) -> (&str, Option<&str>, &str) {
+    let first_dot = key.find('.').unwrap_or(0);
+    let last_dot = key.rfind('.').unwrap_or(0);
+    let subsection = (first_dot < last_dot).then(|| &key[first_dot + 1..last_dot]);
+    (&key[..first_dot], subsection, &key[last_dot + 1..])
+}
+
+fn format_section_header(section: &str, subsection: Option<&str>) -> String {
+    match subsection {
+        Some(subsection) => {
+            let escaped = subsection.replace('\\', "\\\\").replace('"', "\\\"");
+            format!("[{} \"{}\"]", section, escaped)
+        }
+        None => format!("[{}]", section),
+    }
+}
+
+/// Formate une ligne `\tnom = valeur`, avec guillemets et échappements comme git.
+fn format_entry(key: &str, value: &str) -> String {
+    let (_, _, name) = split_key(key);
+    // Les blancs aux extrémités et les débuts de commentaire n'ont de sens qu'entre guillemets
+    let needs_quotes = value.starts_with(' ') || value.ends_with(' ') || value.contains(['#', ';']);
+
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c => escaped.push(c),
+        }
+    }
+
+    if needs_quotes {
+        format!("\t{} = \"{}\"\n", name, escaped)
+    } else {
+        format!("\t{} = {}\n", name, escaped)
+    }
+}
@@ -0,0 +1,149 @@
+use crate::config::config_file::ConfigFile;
+use crate::config::config_set::{self, ConfigScope, ConfigSet};
+use crate::repository;
+use anyhow::{anyhow, Result};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Fichier lu ou modifié par la commande `config`.
+#[derive(Debug, Clone)]
+pub enum ConfigLocation {
+    System,
+    Global,
+    Local,
+    File(PathBuf),
+}
+
+/// Type attendu d'une valeur : elle est vérifiée et affichée (ou écrite) sous forme canonique.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Bool,
+    Int,
+    Path,
+}
+
+/// Opération de la commande `config`.
+#[derive(Debug)]
+pub enum ConfigAction {
+    /// Affiche la dernière valeur d'une clé.
+    Get(String),
+    /// Affiche toutes les valeurs d'une clé.
+    GetAll(String),
+    /// Remplace l'unique valeur d'une clé, ou l'ajoute.
+    Set(String, String),
+    /// Ajoute une valeur à une clé, sans toucher aux autres.
+    Add(String, String),
+    /// Supprime l'unique valeur d'une clé.
+    Unset(String),
+    /// Supprime toutes les valeurs d'une clé.
+    UnsetAll(String),
+    /// Affiche toutes les valeurs, `clé=valeur`.
+    List,
+}
+
+/// Options de la commande `config`.
+#[derive(Debug, Default)]
+pub struct ConfigOptions {
+    /// Fichier à utiliser ; par défaut, lecture de toute la configuration et écriture locale.
+    pub location: Option<ConfigLocation>,
+    pub value_type: Option<ValueType>,
+}
+
+/// Exécute une opération `config`.
+///
+/// # Renvoie
+/// - `false` si la clé lue ou supprimée n'existe pas, pour le code de retour.
+pub fn config<W: Write>(action: &ConfigAction, options: &ConfigOptions, out: &mut W) -> Result<bool> {
+    match action {
+        ConfigAction::Get(key) | ConfigAction::GetAll(key) => {
+            let config = read_config(options)?;
+            let mut entries = config.get_all(key)?;
+            if matches!(action, ConfigAction::Get(_)) {
+                entries = entries.split_off(entries.len().saturating_sub(1));
+            }
+            for entry in &entries {
+                writeln!(out, "{}", format_value(&entry.key, entry.value.as_deref(), options.value_type)?)?;
+            }
+            Ok(!entries.is_empty())
+        }
+        ConfigAction::List => {
+            for entry in read_config(options)?.entries() {
+                match &entry.value {
+                    Some(value) => writeln!(out, "{}={}", entry.key, value)?,
+                    None => writeln!(out, "{}", entry.key)?,
+                }
+            }
+            Ok(true)
+        }
+        ConfigAction::Set(key, value) | ConfigAction::Add(key, value) => {
+            let value = normalize_value(key, value, options.value_type)?;
+            let mut file = ConfigFile::open(&writable_file(options)?)?;
+            if matches!(action, ConfigAction::Set(..)) {
+                file.set(key, &value)?;
+            } else {
+                file.add(key, &value)?;
+            }
+            file.save()?;
+            Ok(true)
+        }
+        ConfigAction::Unset(key) | ConfigAction::UnsetAll(key) => {
+            let mut file = ConfigFile::open(&writable_file(options)?)?;
+            let removed = file.unset(key, matches!(action, ConfigAction::UnsetAll(_)))?;
+            if removed > 0 {
+                file.save()?;
+            }
+            Ok(removed > 0)
+        }
+    }
+}
+
+/// Configuration lue : le fichier demandé seul, ou toutes les portées avec leurs inclusions.
+fn read_config(options: &ConfigOptions) -> Result<ConfigSet> {
+    let Some(location) = &options.location else {
+        return ConfigSet::load();
+    };
+
+    let (scope, files) = match location {
+        ConfigLocation::System => (ConfigScope::System, config_set::system_config_file().into_iter().collect()),
+        ConfigLocation::Global => (ConfigScope::Global, config_set::global_config_files()),
+        ConfigLocation::Local => (ConfigScope::Local, vec![repository::current()?.path("config")]),
+        ConfigLocation::File(path) => (ConfigScope::Command, vec![path.clone()]),
+    };
+    let mut config = ConfigSet::default();
+    for file in files {
+        config.extend(ConfigSet::load_file(&file, scope, false)?);
+    }
+    Ok(config)
+}
+
+fn writable_file(options: &ConfigOptions) -> Result<PathBuf> {
+    match options.location.as_ref().unwrap_or(&ConfigLocation::Local) {
+        ConfigLocation::System => config_set::system_config_file()
+            .ok_or_else(|| anyhow!("system config is disabled by GIT_CONFIG_NOSYSTEM")),
+        ConfigLocation::Global => config_set::global_config_file_for_writing(),
+        ConfigLocation::Local => Ok(repository::current()?.path("config")),
+        ConfigLocation::File(path) => Ok(path.clone()),
+    }
+}
+
+/// Met une valeur lue sous la forme canonique du type demandé.
+fn format_value(key: &str, value: Option<&str>, value_type: Option<ValueType>) -> Result<String> {
+    Ok(match value_type {
+        Some(ValueType::Bool) => config_set::parse_bool(key, value)?.to_string(),
+        Some(ValueType::Int) => config_set::parse_int(key, value)?.to_string(),
+        Some(ValueType::Path) => match value {
+            Some(value) => config_set::expand_path(value)?.to_string_lossy().into_owned(),
+            None => return Err(anyhow!("missing value for '{}'", key)),
+        },
+        None => value.unwrap_or_default().to_string(),
+    })
+}
+
+/// Vérifie une valeur à écrire et la met sous forme canonique (`yes` devient `true`, `1k` devient `1024`).
+fn normalize_value(key: &str, value: &str, value_type: Option<ValueType>) -> Result<String> {
+    match value_type {
+        // Un chemin est écrit tel quel : `~/` n'est développé qu'à la lecture
+        Some(ValueType::Path) | None => Ok(value.to_string()),
+        Some(_) => format_value(key, Some(value), value_type),
+    }
+}
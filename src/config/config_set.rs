@@ -0,0 +1,304 @@
+use crate::config::config_file::{self, ConfigFile};
+use crate::ignore::wildmatch::wildmatch;
+use crate::repository;
+use anyhow::{anyhow, Result};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Profondeur maximale des inclusions, pour détecter les cycles.
+const MAX_INCLUDE_DEPTH: usize = 10;
+const SYSTEM_CONFIG_FILE: &str = "/etc/gitconfig";
+
+/// Origine d'un fichier de configuration, par priorité croissante.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigScope {
+    System,
+    Global,
+    Local,
+    /// Fichier désigné explicitement (`config --file`).
+    Command,
+}
+
+impl fmt::Display for ConfigScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConfigScope::System => "system",
+            ConfigScope::Global => "global",
+            ConfigScope::Local => "local",
+            ConfigScope::Command => "command",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Une valeur lue, avec le fichier d'où elle vient.
+#[derive(Debug, Clone)]
+pub struct ConfigEntry {
+    /// Clé canonique (`section.sous-section.nom`).
+    pub key: String,
+    /// `None` pour une clé sans `=`, qui vaut vrai en booléen.
+    pub value: Option<String>,
+    pub scope: ConfigScope,
+    pub origin: PathBuf,
+}
+
+/// Configuration fusionnée de plusieurs fichiers : pour une clé, la dernière valeur lue l'emporte.
+#[derive(Debug, Default)]
+pub struct ConfigSet {
+    entries: Vec<ConfigEntry>,
+}
+
+impl ConfigSet {
+    /// Charge les configurations système, globale puis locale, inclusions comprises.
+    ///
+    /// Hors d'un dépôt, seules les deux premières s'appliquent.
+    pub fn load() -> Result<ConfigSet> {
+        let mut config = ConfigSet::load_global()?;
+        if let Ok(repository) = repository::current() {
+            config.read_file(&repository.path("config"), ConfigScope::Local, true, 0)?;
+        }
+        Ok(config)
+    }
+
+    /// Charge les seules configurations système et globale, par exemple pour `init`
+    /// qui ne doit pas consulter un dépôt englobant.
+    pub fn load_global() -> Result<ConfigSet> {
+        let mut config = ConfigSet::default();
+        if let Some(system) = system_config_file() {
+            config.read_file(&system, ConfigScope::System, true, 0)?;
+        }
+        for global in global_config_files() {
+            config.read_file(&global, ConfigScope::Global, true, 0)?;
+        }
+        Ok(config)
+    }
+
+    /// Charge un seul fichier.
+    ///
+    /// # Paramètres
+    /// - `includes` : suit les directives `include` et `includeIf`.
+    pub fn load_file(path: &Path, scope: ConfigScope, includes: bool) -> Result<ConfigSet> {
+        let mut config = ConfigSet::default();
+        config.read_file(path, scope, includes, 0)?;
+        Ok(config)
+    }
+
+    /// Ajoute les valeurs d'une autre configuration, qui l'emportent sur les précédentes.
+    pub fn extend(&mut self, other: ConfigSet) {
+        self.entries.extend(other.entries);
+    }
+
+    /// Toutes les valeurs, dans l'ordre de lecture.
+    pub fn entries(&self) -> &[ConfigEntry] {
+        &self.entries
+    }
+
+    /// Toutes les valeurs d'une clé à plusieurs valeurs, dans l'ordre de lecture.
+    pub fn get_all(&self, key: &str) -> Result<Vec<&ConfigEntry>> {
+        let key = config_file::canonical_key(key)?;
+        Ok(self.entries.iter().filter(|entry| entry.key == key).collect())
+    }
+
+    /// Dernière valeur lue pour une clé.
+    pub fn get_entry(&self, key: &str) -> Result<Option<&ConfigEntry>> {
+        Ok(self.get_all(key)?.pop())
+    }
+
+    /// Valeur textuelle d'une clé.
+    ///
+    /// # Erreurs
+    /// - Retourne une erreur pour une clé sans `=`, qui n'a pas de valeur textuelle.
+    pub fn get_string(&self, key: &str) -> Result<Option<String>> {
+        match self.get_entry(key)? {
+            Some(entry) => Ok(Some(entry.value.clone()
+                .ok_or_else(|| anyhow!("missing value for '{}'", entry.key))?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Valeur booléenne d'une clé (`true`/`yes`/`on`/`1`, `false`/`no`/`off`/`0`/vide).
+    pub fn get_bool(&self, key: &str) -> Result<Option<bool>> {
+        self.get_entry(key)?
+            .map(|entry| parse_bool(&entry.key, entry.value.as_deref()))
+            .transpose()
+    }
+
+    /// Valeur entière d'une clé, avec un suffixe `k`, `m` ou `g` éventuel.
+    pub fn get_int(&self, key: &str) -> Result<Option<i64>> {
+        self.get_entry(key)?
+            .map(|entry| parse_int(&entry.key, entry.value.as_deref()))
+            .transpose()
+    }
+
+    /// Chemin d'une clé, `~/` désignant le répertoire personnel.
+    pub fn get_path(&self, key: &str) -> Result<Option<PathBuf>> {
+        match self.get_string(key)? {
+            Some(value) => Ok(Some(expand_path(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn read_file(&mut self, path: &Path, scope: ConfigScope, includes: bool, depth: usize) -> Result<()> {
+        if depth > MAX_INCLUDE_DEPTH {
+            return Err(anyhow!("exceeded maximum include depth ({}) while including {}", MAX_INCLUDE_DEPTH, path.display()));
+        }
+        let file = ConfigFile::open(path)?;
+
+        for (key, value) in file.entries() {
+            self.entries.push(ConfigEntry {
+                key: key.to_string(),
+                value: value.map(str::to_string),
+                scope,
+                origin: path.to_path_buf(),
+            });
+
+            let Some(value) = value.filter(|_| includes) else {
+                continue;
+            };
+            let included = match config_file::split_key(key) {
+                ("include", None, "path") => true,
+                ("includeif", Some(condition), "path") => include_condition_holds(condition, path)?,
+                _ => false,
+            };
+            if included {
+                let target = resolve_relative(&expand_path(value)?, path);
+                // Un fichier inclus absent est ignoré, comme dans git
+                if target.is_file() {
+                    self.read_file(&target, scope, includes, depth + 1)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Interprète une valeur booléenne comme git.
+///
+/// # Paramètres
+/// - `key` : clé de la valeur, pour le message d'erreur.
+/// - `value` : `None` pour une clé sans `=`, qui vaut vrai.
+pub fn parse_bool(key: &str, value: Option<&str>) -> Result<bool> {
+    let Some(value) = value else {
+        return Ok(true);
+    };
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" => Ok(true),
+        "false" | "no" | "off" | "" => Ok(false),
+        _ => parse_int(key, Some(value))
+            .map(|number| number != 0)
+            .map_err(|_| anyhow!("bad boolean config value '{}' for '{}'", value, key)),
+    }
+}
+
+/// Interprète une valeur entière comme git, avec les suffixes `k`, `m` et `g` (puissances de 1024).
+pub fn parse_int(key: &str, value: Option<&str>) -> Result<i64> {
+    let value = value.ok_or_else(|| anyhow!("missing value for '{}'", key))?;
+    let trimmed = value.trim();
+    let (digits, factor) = match trimmed.chars().last().map(|c| c.to_ascii_lowercase()) {
+        Some('k') => (&trimmed[..trimmed.len() - 1], 1 << 10),
+        Some('m') => (&trimmed[..trimmed.len() - 1], 1 << 20),
+        Some('g') => (&trimmed[..trimmed.len() - 1], 1 << 30),
+        _ => (trimmed, 1),
+    };
+
+    digits.parse::<i64>().ok()
+        .and_then(|number| number.checked_mul(factor))
+        .ok_or_else(|| anyhow!("bad numeric config value '{}' for '{}': invalid unit", value, key))
+}
+
+/// Développe `~/` en répertoire personnel dans un chemin de configuration.
+pub fn expand_path(value: &str) -> Result<PathBuf> {
+    match value.strip_prefix("~/") {
+        Some(rest) => {
+            let home = env::var_os("HOME").ok_or_else(|| anyhow!("failed to expand user dir in: '{}'", value))?;
+            Ok(PathBuf::from(home).join(rest))
+        }
+        None if value.starts_with('~') => Err(anyhow!("failed to expand user dir in: '{}'", value)),
+        None => Ok(PathBuf::from(value)),
+    }
+}
+
+/// Un chemin relatif d'un fichier inclus l'est au fichier qui l'inclut.
+fn resolve_relative(path: &Path, including_file: &Path) -> PathBuf {
+    match including_file.parent() {
+        Some(parent) if path.is_relative() => parent.join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Évalue la condition d'un `[includeIf "<condition>"]`.
+///
+/// Seules les conditions `gitdir:` et `gitdir/i:` sont gérées ; les autres sont fausses.
+fn include_condition_holds(condition: &str, including_file: &Path) -> Result<bool> {
+    let (pattern, case_insensitive) = if let Some(pattern) = condition.strip_prefix("gitdir:") {
+        (pattern, false)
+    } else if let Some(pattern) = condition.strip_prefix("gitdir/i:") {
+        (pattern, true)
+    } else {
+        return Ok(false);
+    };
+    let Ok(repository) = repository::current() else {
+        return Ok(false);
+    };
+
+    // Motif relatif à l'inclusion (`./`), à n'importe quelle profondeur sinon ;
+    // un `/` final désigne tout ce que contient le répertoire
+    let mut pattern = if pattern.starts_with("./") {
+        resolve_relative(Path::new(pattern), including_file).to_string_lossy().into_owned()
+    } else {
+        expand_path(pattern)?.to_string_lossy().into_owned()
+    };
+    if !Path::new(&pattern).is_absolute() && !pattern.starts_with("**/") {
+        pattern.insert_str(0, "**/");
+    }
+    if pattern.ends_with('/') {
+        pattern.push_str("**");
+    }
+
+    let git_dir = fs::canonicalize(repository.git_dir()).unwrap_or_else(|_| repository.git_dir().to_path_buf());
+    let mut git_dir = git_dir.to_string_lossy().into_owned();
+    if case_insensitive {
+        pattern = pattern.to_lowercase();
+        git_dir = git_dir.to_lowercase();
+    }
+    Ok(wildmatch(pattern.as_bytes(), git_dir.as_bytes(), true))
+}
+
+/// Fichier de configuration système : `GIT_CONFIG_SYSTEM`, sauf si `GIT_CONFIG_NOSYSTEM` est vrai.
+pub fn system_config_file() -> Option<PathBuf> {
+    let no_system = env::var("GIT_CONFIG_NOSYSTEM").ok()
+        .is_some_and(|value| parse_bool("GIT_CONFIG_NOSYSTEM", Some(&value)).unwrap_or(false));
+    if no_system {
+        return None;
+    }
+    Some(env::var_os("GIT_CONFIG_SYSTEM").map_or_else(|| PathBuf::from(SYSTEM_CONFIG_FILE), PathBuf::from))
+}
+
+/// Fichiers de configuration globale, par priorité croissante :
+/// `$XDG_CONFIG_HOME/git/config` puis `~/.gitconfig`, ou le seul `GIT_CONFIG_GLOBAL`.
+pub fn global_config_files() -> Vec<PathBuf> {
+    if let Some(global) = env::var_os("GIT_CONFIG_GLOBAL") {
+        return vec![PathBuf::from(global)];
+    }
+
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let xdg = match env::var_os("XDG_CONFIG_HOME").filter(|value| !value.is_empty()) {
+        Some(config_home) => Some(PathBuf::from(config_home).join("git").join("config")),
+        None => home.as_ref().map(|home| home.join(".config").join("git").join("config")),
+    };
+    xdg.into_iter().chain(home.map(|home| home.join(".gitconfig"))).collect()
+}
+
+/// Fichier modifié par `config --global` : `~/.gitconfig`, sauf si seul le fichier XDG existe.
+pub fn global_config_file_for_writing() -> Result<PathBuf> {
+    let files = global_config_files();
+    let preferred = files.last().ok_or_else(|| anyhow!("$HOME not set"))?;
+    if !preferred.exists() {
+        if let Some(xdg) = files.first().filter(|xdg| xdg.exists()) {
+            return Ok(xdg.clone());
+        }
+    }
+    Ok(preferred.clone())
+}
@@ -0,0 +1,4 @@
+pub mod config_file;
+pub mod config_manager;
+pub mod config_set;
+mod parser;
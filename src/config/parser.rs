@@ -0,0 +1,263 @@
+use anyhow::{anyhow, Result};
+use std::ops::Range;
+
+const UTF8_BOM: &str = "\u{feff}";
+
+/// Élément d'un fichier de configuration, avec sa position pour pouvoir le réécrire.
+#[derive(Debug, Clone)]
+pub enum ConfigItem {
+    /// En-tête `[section]` ou `[section "sous-section"]`.
+    Section {
+        /// Nom de section en minuscules.
+        name: String,
+        /// Sous-section, sensible à la casse.
+        subsection: Option<String>,
+        /// Position de l'en-tête, du `[` au `]` inclus.
+        span: Range<usize>,
+    },
+    /// Ligne `nom = valeur`, ou `nom` seul (booléen vrai).
+    Entry {
+        /// Clé complète canonique (`section.sous-section.nom`).
+        key: String,
+        value: Option<String>,
+        /// Position de l'entrée jusqu'au saut de ligne inclus, indentation comprise
+        /// quand l'entrée commence sa ligne.
+        span: Range<usize>,
+    },
+}
+
+/// Analyse un fichier de configuration dans le dialecte INI de git.
+///
+/// Gère les commentaires `#` et `;`, les sous-sections entre guillemets, l'ancienne forme
+/// `[section.sous-section]`, les valeurs entre guillemets, les échappements `\n`, `\t`,
+/// `\b`, `\"`, `\\` et les lignes prolongées par `\`.
+///
+/// # Paramètres
+/// - `content` : contenu du fichier.
+/// - `source` : nom du fichier, pour les messages d'erreur.
+pub fn parse(content: &str, source: &str) -> Result<Vec<ConfigItem>> {
+    let mut parser = Parser { bytes: content.as_bytes(), position: 0, line: 1, source };
+    if content.starts_with(UTF8_BOM) {
+        parser.position = UTF8_BOM.len();
+    }
+    parser.parse()
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    line: usize,
+    source: &'a str,
+}
+
+impl Parser<'_> {
+    fn parse(&mut self) -> Result<Vec<ConfigItem>> {
+        let mut items = vec![];
+        let mut section: Option<(String, Option<String>)> = None;
+        let mut comment = false;
+        // Début de la ligne courante, tant qu'elle ne contient que des blancs
+        let mut blank_line_start = Some(self.position);
+
+        while let Some(c) = self.next_char() {
+            if c == b'\n' {
+                comment = false;
+                blank_line_start = Some(self.position);
+                continue;
+            }
+            if comment || c.is_ascii_whitespace() {
+                continue;
+            }
+            if c == b'#' || c == b';' {
+                comment = true;
+                continue;
+            }
+
+            let start = self.position - 1;
+            if c == b'[' {
+                let (name, subsection) = self.parse_section_header()?;
+                items.push(ConfigItem::Section { name: name.clone(), subsection: subsection.clone(), span: start..self.position });
+                section = Some((name, subsection));
+                blank_line_start = None;
+                continue;
+            }
+
+            if !c.is_ascii_alphabetic() {
+                return Err(self.error());
+            }
+            let Some((name, subsection)) = &section else {
+                // Une variable doit appartenir à une section
+                return Err(self.error());
+            };
+            let variable = self.parse_variable_name(c);
+            let value = self.parse_variable_value()?;
+
+            let key = match subsection {
+                Some(subsection) => format!("{}.{}.{}", name, subsection, variable),
+                None => format!("{}.{}", name, variable),
+            };
+            // La valeur s'arrête avant son saut de ligne : l'entrée l'inclut
+            if self.peek_char() == Some(b'\n') {
+                self.next_char();
+            }
+            items.push(ConfigItem::Entry { key, value, span: blank_line_start.unwrap_or(start)..self.position });
+            blank_line_start = Some(self.position);
+        }
+
+        Ok(items)
+    }
+
+    /// Lit un en-tête après son `[`.
+    fn parse_section_header(&mut self) -> Result<(String, Option<String>)> {
+        let mut name = String::new();
+        loop {
+            match self.next_char() {
+                Some(b']') => break,
+                Some(c) if c.is_ascii_whitespace() => {
+                    let subsection = self.parse_subsection()?;
+                    return Ok((name, Some(subsection)));
+                }
+                Some(c) if c.is_ascii_alphanumeric() || c == b'-' || c == b'.' => name.push(c.to_ascii_lowercase() as char),
+                _ => return Err(self.error()),
+            }
+        }
+
+        if name.is_empty() {
+            return Err(self.error());
+        }
+        // Ancienne forme `[section.sous-section]`, dont la sous-section est en minuscules
+        match name.split_once('.') {
+            Some((section, subsection)) => Ok((section.to_string(), Some(subsection.to_string()))),
+            None => Ok((name, None)),
+        }
+    }
+
+    /// Lit la sous-section entre guillemets d'un en-tête `[section "sous-section"]`.
+    fn parse_subsection(&mut self) -> Result<String> {
+        let mut c = self.next_char();
+        while c.is_some_and(|c| c == b' ' || c == b'\t') {
+            c = self.next_char();
+        }
+        if c != Some(b'"') {
+            return Err(self.error());
+        }
+
+        let mut subsection = vec![];
+        loop {
+            match self.next_char() {
+                None | Some(b'\n') => return Err(self.error()),
+                Some(b'"') => break,
+                // Un caractère échappé est pris tel quel
+                Some(b'\\') => match self.next_char() {
+                    None | Some(b'\n') => return Err(self.error()),
+                    Some(c) => subsection.push(c),
+                },
+                Some(c) => subsection.push(c),
+            }
+        }
+
+        if self.next_char() != Some(b']') {
+            return Err(self.error());
+        }
+        Ok(String::from_utf8_lossy(&subsection).into_owned())
+    }
+
+    fn parse_variable_name(&mut self, first: u8) -> String {
+        let mut name = String::from(first.to_ascii_lowercase() as char);
+        while let Some(c) = self.peek_char().filter(|c| c.is_ascii_alphanumeric() || *c == b'-') {
+            name.push(c.to_ascii_lowercase() as char);
+            self.position += 1;
+        }
+        name
+    }
+
+    /// Lit ce qui suit le nom d'une variable, sans consommer le saut de ligne final.
+    ///
+    /// # Renvoie
+    /// - `None` pour une variable sans `=`, qui vaut vrai.
+    fn parse_variable_value(&mut self) -> Result<Option<String>> {
+        while self.peek_char().is_some_and(|c| c == b' ' || c == b'\t') {
+            self.position += 1;
+        }
+        match self.peek_char() {
+            None | Some(b'\n') => return Ok(None),
+            Some(b'=') => self.position += 1,
+            Some(_) => return Err(self.error()),
+        }
+
+        let mut value = vec![];
+        let mut quoted = false;
+        let mut comment = false;
+        // Blancs en attente : conservés (un espace chacun) seulement s'ils ne terminent pas la valeur
+        let mut spaces = 0;
+        loop {
+            let Some(c) = self.peek_char() else {
+                if quoted {
+                    return Err(self.error());
+                }
+                break;
+            };
+            if c == b'\n' {
+                if quoted {
+                    return Err(self.error());
+                }
+                break;
+            }
+            self.position += 1;
+
+            if comment {
+                continue;
+            }
+            if c.is_ascii_whitespace() && !quoted {
+                if !value.is_empty() {
+                    spaces += 1;
+                }
+                continue;
+            }
+            if !quoted && (c == b';' || c == b'#') {
+                comment = true;
+                continue;
+            }
+            value.resize(value.len() + spaces, b' ');
+            spaces = 0;
+
+            match c {
+                b'\\' => match self.next_char() {
+                    // Ligne prolongée
+                    Some(b'\n') => {}
+                    Some(b't') => value.push(b'\t'),
+                    Some(b'b') => value.push(b'\x08'),
+                    Some(b'n') => value.push(b'\n'),
+                    Some(c @ (b'\\' | b'"')) => value.push(c),
+                    _ => return Err(self.error()),
+                },
+                b'"' => quoted = !quoted,
+                _ => value.push(c),
+            }
+        }
+
+        Ok(Some(String::from_utf8_lossy(&value).into_owned()))
+    }
+
+    fn next_char(&mut self) -> Option<u8> {
+        let c = self.peek_char()?;
+        self.position += 1;
+        if c == b'\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn peek_char(&self) -> Option<u8> {
+        let c = *self.bytes.get(self.position)?;
+        // Le `\r` d'une fin de ligne Windows est ignoré comme un blanc
+        if c == b'\r' && self.bytes.get(self.position + 1) == Some(&b'\n') {
+            Some(b' ')
+        } else {
+            Some(c)
+        }
+    }
+
+    fn error(&self) -> anyhow::Error {
+        anyhow!("bad config line {} in file {}", self.line, self.source)
+    }
+}
@@ -0,0 +1,386 @@
+use crate::diff::{histogram, myers, patience};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// Au-delà de cette indentation, les lignes sont considérées comme également indentées.
+const MAX_INDENT: i32 = 200;
+/// Au-delà de ce nombre de lignes vides consécutives, les suivantes sont ignorées.
+const MAX_BLANKS: i32 = 20;
+/// Nombre maximal de positions essayées pour un bloc de lignes modifiées.
+const INDENT_HEURISTIC_MAX_SLIDING: usize = 100;
+
+// Pénalités de l'heuristique d'indentation de git, déterminées empiriquement :
+// plus elles sont élevées, moins la coupure est favorable
+const START_OF_FILE_PENALTY: i32 = 1;
+const END_OF_FILE_PENALTY: i32 = 21;
+const TOTAL_BLANK_WEIGHT: i32 = -30;
+const POST_BLANK_WEIGHT: i32 = 6;
+const RELATIVE_INDENT_PENALTY: i32 = -4;
+const RELATIVE_INDENT_WITH_BLANK_PENALTY: i32 = 10;
+const RELATIVE_OUTDENT_PENALTY: i32 = 24;
+const RELATIVE_OUTDENT_WITH_BLANK_PENALTY: i32 = 17;
+const RELATIVE_DEDENT_PENALTY: i32 = 23;
+const RELATIVE_DEDENT_WITH_BLANK_PENALTY: i32 = 17;
+const INDENT_WEIGHT: i32 = 60;
+
+/// Algorithme de comparaison ligne à ligne, choisi par `--diff-algorithm`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffAlgorithm {
+    /// Algorithme de Myers, avec les heuristiques de git pour les gros fichiers.
+    Myers,
+    /// Algorithme de Myers sans heuristique : le plus petit diff possible.
+    Minimal,
+    /// Aligne d'abord les lignes uniques des deux côtés.
+    Patience,
+    /// Variante de patience qui tolère les lignes peu fréquentes.
+    Histogram,
+}
+
+impl DiffAlgorithm {
+    /// Analyse la valeur de `--diff-algorithm` ou de `diff.algorithm`.
+    pub fn parse(value: &str) -> Result<DiffAlgorithm> {
+        match value.to_ascii_lowercase().as_str() {
+            "myers" | "default" => Ok(DiffAlgorithm::Myers),
+            "minimal" => Ok(DiffAlgorithm::Minimal),
+            "patience" => Ok(DiffAlgorithm::Patience),
+            "histogram" => Ok(DiffAlgorithm::Histogram),
+            _ => Err(anyhow!("option diff-algorithm accepts \"myers\", \"minimal\", \"patience\" and \"histogram\"")),
+        }
+    }
+}
+
+/// Lignes modifiées de chaque version : supprimées de l'ancienne, ajoutées dans la nouvelle.
+#[derive(Debug)]
+pub struct LineChanges {
+    pub old: Vec<bool>,
+    pub new: Vec<bool>,
+}
+
+impl LineChanges {
+    /// Nombre de lignes ajoutées et supprimées.
+    pub fn counts(&self) -> (usize, usize) {
+        let count = |changed: &[bool]| changed.iter().filter(|&&changed| changed).count();
+        (count(&self.new), count(&self.old))
+    }
+}
+
+/// Découpe un contenu en lignes, chacune avec son saut de ligne (sauf peut-être la dernière).
+pub fn split_lines(content: &[u8]) -> Vec<&[u8]> {
+    content.split_inclusive(|&b| b == b'\n').collect()
+}
+
+/// Compare deux suites de lignes.
+///
+/// Comme git, les blocs de lignes modifiées sont ensuite déplacés quand c'est possible :
+/// alignés sur un bloc de l'autre version, ou placés là où l'indentation les délimite le mieux.
+pub fn diff_lines(old: &[&[u8]], new: &[&[u8]], algorithm: DiffAlgorithm) -> LineChanges {
+    let (old_ids, new_ids) = classify(old, new);
+    let mut changes = LineChanges { old: vec![false; old.len()], new: vec![false; new.len()] };
+
+    match algorithm {
+        DiffAlgorithm::Myers => myers::diff(&old_ids, &new_ids, false, &mut changes.old, &mut changes.new),
+        DiffAlgorithm::Minimal => myers::diff(&old_ids, &new_ids, true, &mut changes.old, &mut changes.new),
+        DiffAlgorithm::Patience => patience::diff(&old_ids, &new_ids, &mut changes.old, &mut changes.new),
+        DiffAlgorithm::Histogram => histogram::diff(&old_ids, &new_ids, &mut changes.old, &mut changes.new),
+    }
+
+    compact(old, &old_ids, &mut changes.old, &changes.new);
+    compact(new, &new_ids, &mut changes.new, &changes.old);
+    changes
+}
+
+/// Remplace chaque ligne par un identifiant, le même pour deux lignes identiques.
+fn classify<'a>(old: &[&'a [u8]], new: &[&'a [u8]]) -> (Vec<usize>, Vec<usize>) {
+    let mut ids: HashMap<&'a [u8], usize> = HashMap::new();
+    let mut identify = |lines: &[&'a [u8]]| -> Vec<usize> {
+        lines.iter()
+            .map(|&line| {
+                let next = ids.len();
+                *ids.entry(line).or_insert(next)
+            })
+            .collect()
+    };
+    let old_ids = identify(old);
+    let new_ids = identify(new);
+    (old_ids, new_ids)
+}
+
+/// Bloc de lignes modifiées consécutives, éventuellement vide (`start == end`) : un tel bloc
+/// existe au début et à la fin du fichier et entre deux lignes inchangées.
+#[derive(Debug, Clone, Copy)]
+struct Group {
+    /// Première ligne modifiée, ou ligne inchangée au-dessus de laquelle se trouve le bloc vide.
+    start: usize,
+    /// Première ligne inchangée après le bloc.
+    end: usize,
+}
+
+impl Group {
+    fn first(changed: &[bool]) -> Group {
+        let mut group = Group { start: 0, end: 0 };
+        while group.end < changed.len() && changed[group.end] {
+            group.end += 1;
+        }
+        group
+    }
+
+    /// Passe au bloc suivant ; faux à la fin du fichier.
+    fn next(&mut self, changed: &[bool]) -> bool {
+        if self.end == changed.len() {
+            return false;
+        }
+        self.start = self.end + 1;
+        self.end = self.start;
+        while self.end < changed.len() && changed[self.end] {
+            self.end += 1;
+        }
+        true
+    }
+
+    /// Passe au bloc précédent ; faux au début du fichier.
+    fn previous(&mut self, changed: &[bool]) -> bool {
+        if self.start == 0 {
+            return false;
+        }
+        self.end = self.start - 1;
+        self.start = self.end;
+        while self.start > 0 && changed[self.start - 1] {
+            self.start -= 1;
+        }
+        true
+    }
+
+    /// Décale le bloc d'une ligne vers la fin si sa première ligne est identique à celle
+    /// qui le suit, en absorbant le bloc suivant s'il le touche.
+    fn slide_down(&mut self, ids: &[usize], changed: &mut [bool]) -> bool {
+        if self.end >= changed.len() || ids[self.start] != ids[self.end] {
+            return false;
+        }
+        changed[self.start] = false;
+        changed[self.end] = true;
+        self.start += 1;
+        self.end += 1;
+        while self.end < changed.len() && changed[self.end] {
+            self.end += 1;
+        }
+        true
+    }
+
+    /// Décale le bloc d'une ligne vers le début si sa dernière ligne est identique à celle
+    /// qui le précède, en absorbant le bloc précédent s'il le touche.
+    fn slide_up(&mut self, ids: &[usize], changed: &mut [bool]) -> bool {
+        if self.start == 0 || ids[self.start - 1] != ids[self.end - 1] {
+            return false;
+        }
+        self.start -= 1;
+        self.end -= 1;
+        changed[self.start] = true;
+        changed[self.end] = false;
+        while self.start > 0 && changed[self.start - 1] {
+            self.start -= 1;
+        }
+        true
+    }
+}
+
+/// Déplace les blocs de lignes modifiées d'une version, comme `xdl_change_compact` de git.
+///
+/// Chaque bloc est d'abord fusionné avec ses voisins quand un décalage le permet, puis placé
+/// en face d'un bloc de l'autre version s'il peut l'être, sinon à la position que
+/// l'heuristique d'indentation juge la plus lisible.
+fn compact(lines: &[&[u8]], ids: &[usize], changed: &mut [bool], other: &[bool]) {
+    let mut group = Group::first(changed);
+    let mut other_group = Group::first(other);
+
+    loop {
+        if group.end != group.start {
+            let mut group_size;
+            let mut earliest_end;
+            let mut end_matching_other;
+            loop {
+                group_size = group.end - group.start;
+                end_matching_other = None;
+
+                while group.slide_up(ids, changed) {
+                    other_group.previous(other);
+                }
+                earliest_end = group.end;
+                if other_group.end > other_group.start {
+                    end_matching_other = Some(group.end);
+                }
+
+                while group.slide_down(ids, changed) {
+                    other_group.next(other);
+                    if other_group.end > other_group.start {
+                        end_matching_other = Some(group.end);
+                    }
+                }
+                if group_size == group.end - group.start {
+                    break;
+                }
+            }
+
+            if group.end == earliest_end {
+                // Aucun décalage possible
+            } else if end_matching_other.is_some() {
+                while other_group.end == other_group.start {
+                    group.slide_up(ids, changed);
+                    other_group.previous(other);
+                }
+            } else {
+                let best_shift = best_indent_shift(lines, group.end, group_size, earliest_end);
+                while group.end > best_shift {
+                    group.slide_up(ids, changed);
+                    other_group.previous(other);
+                }
+            }
+        }
+
+        if !group.next(changed) {
+            break;
+        }
+        other_group.next(other);
+    }
+}
+
+/// Position de fin du bloc qui délimite le mieux les lignes modifiées, entre `earliest_end`
+/// et `end` : celle dont les deux coupures (avant et après le bloc) ont le meilleur score.
+fn best_indent_shift(lines: &[&[u8]], end: usize, group_size: usize, earliest_end: usize) -> usize {
+    let first_shift = earliest_end
+        .max((end + 1).saturating_sub(group_size + 2))
+        .max(end.saturating_sub(INDENT_HEURISTIC_MAX_SLIDING));
+
+    let mut best: Option<(usize, SplitScore)> = None;
+    for shift in first_shift..=end {
+        let mut score = SplitScore::default();
+        score.add(&SplitMeasure::measure(lines, shift));
+        score.add(&SplitMeasure::measure(lines, shift - group_size));
+        if best.as_ref().map_or(true, |(_, best_score)| score.compare(best_score) <= 0) {
+            best = Some((shift, score));
+        }
+    }
+    best.map_or(end, |(shift, _)| shift)
+}
+
+/// Environnement d'une coupure hypothétique entre deux lignes.
+#[derive(Debug)]
+struct SplitMeasure {
+    /// La coupure est en fin de fichier (lignes vides mises à part).
+    end_of_file: bool,
+    /// Indentation de la ligne qui suit la coupure, -1 si elle est vide.
+    indent: i32,
+    /// Lignes vides consécutives avant la coupure.
+    pre_blank: i32,
+    /// Indentation de la dernière ligne non vide avant la coupure, -1 s'il n'y en a pas.
+    pre_indent: i32,
+    /// Lignes vides après celle qui suit la coupure.
+    post_blank: i32,
+    /// Indentation de la ligne non vide suivante, -1 s'il n'y en a pas.
+    post_indent: i32,
+}
+
+impl SplitMeasure {
+    /// Mesure la coupure située juste avant la ligne `split`.
+    fn measure(lines: &[&[u8]], split: usize) -> SplitMeasure {
+        let (end_of_file, indent) = match lines.get(split) {
+            Some(line) => (false, indentation(line)),
+            None => (true, -1),
+        };
+
+        let mut pre_blank = 0;
+        let mut pre_indent = -1;
+        for line in lines[..split.min(lines.len())].iter().rev() {
+            pre_indent = indentation(line);
+            if pre_indent != -1 {
+                break;
+            }
+            pre_blank += 1;
+            if pre_blank == MAX_BLANKS {
+                pre_indent = 0;
+                break;
+            }
+        }
+
+        let mut post_blank = 0;
+        let mut post_indent = -1;
+        for line in lines.iter().skip(split + 1) {
+            post_indent = indentation(line);
+            if post_indent != -1 {
+                break;
+            }
+            post_blank += 1;
+            if post_blank == MAX_BLANKS {
+                post_indent = 0;
+                break;
+            }
+        }
+
+        SplitMeasure { end_of_file, indent, pre_blank, pre_indent, post_blank, post_indent }
+    }
+}
+
+/// Score d'une position de bloc : plus il est petit, meilleure est la position.
+#[derive(Debug, Default)]
+struct SplitScore {
+    effective_indent: i32,
+    penalty: i32,
+}
+
+impl SplitScore {
+    fn add(&mut self, measure: &SplitMeasure) {
+        if measure.pre_indent == -1 && measure.pre_blank == 0 {
+            self.penalty += START_OF_FILE_PENALTY;
+        }
+        if measure.end_of_file {
+            self.penalty += END_OF_FILE_PENALTY;
+        }
+
+        // Lignes vides après la coupure, celle qui la suit immédiatement comprise
+        let post_blank = if measure.indent == -1 { 1 + measure.post_blank } else { 0 };
+        let total_blank = measure.pre_blank + post_blank;
+        self.penalty += TOTAL_BLANK_WEIGHT * total_blank;
+        self.penalty += POST_BLANK_WEIGHT * post_blank;
+
+        let indent = if measure.indent != -1 { measure.indent } else { measure.post_indent };
+        let any_blanks = total_blank != 0;
+        self.effective_indent += indent;
+
+        if indent == -1 || measure.pre_indent == -1 || indent == measure.pre_indent {
+            // Pas d'ajustement
+        } else if indent > measure.pre_indent {
+            self.penalty += if any_blanks { RELATIVE_INDENT_WITH_BLANK_PENALTY } else { RELATIVE_INDENT_PENALTY };
+        } else if measure.post_indent != -1 && measure.post_indent > indent {
+            // Moins indentée que la précédente mais plus que la suivante : début de bloc probable
+            self.penalty += if any_blanks { RELATIVE_OUTDENT_WITH_BLANK_PENALTY } else { RELATIVE_OUTDENT_PENALTY };
+        } else {
+            // Fin de bloc probable
+            self.penalty += if any_blanks { RELATIVE_DEDENT_WITH_BLANK_PENALTY } else { RELATIVE_DEDENT_PENALTY };
+        }
+    }
+
+    fn compare(&self, other: &SplitScore) -> i32 {
+        let indents = (self.effective_indent > other.effective_indent) as i32
+            - (self.effective_indent < other.effective_indent) as i32;
+        INDENT_WEIGHT * indents + (self.penalty - other.penalty)
+    }
+}
+
+/// Indentation d'une ligne, une tabulation avançant à la colonne multiple de 8 suivante.
+///
+/// # Renvoie
+/// - -1 pour une ligne vide ou faite d'espaces, au plus [`MAX_INDENT`] sinon.
+fn indentation(line: &[u8]) -> i32 {
+    let mut indent = 0;
+    for &c in line {
+        match c {
+            b' ' => indent += 1,
+            b'\t' => indent += 8 - indent % 8,
+            b'\n' | b'\r' | 0x0b | 0x0c => {}
+            _ => return indent,
+        }
+        if indent >= MAX_INDENT {
+            return MAX_INDENT;
+        }
+    }
+    -1
+}
@@ -0,0 +1,312 @@
+use crate::config::config_set::ConfigSet;
+use crate::diff::algorithm::{self, DiffAlgorithm};
+use crate::diff::patch::{self, PatchOptions};
+use crate::diff::stat::{self, FileStat};
+use crate::dircache::index::{Index, IndexLock};
+use crate::dircache::index_entry::{MODE_FILE, MODE_GITLINK, MODE_SYMLINK};
+use crate::fs_reader::{self, FSReader};
+use crate::objects::object_kind::GitObjectKind;
+use crate::objects::object_manager;
+use crate::quote::quote_path;
+use crate::refs::{self, NULL_HASH};
+use crate::rev_parse;
+use crate::status::changes::{self, FileChange, FileVersion};
+use anyhow::{anyhow, Result};
+use std::env;
+use std::io::Write;
+
+/// Longueur minimale des hashes abrégés des lignes `index`.
+const ABBREV_LENGTH: usize = 7;
+/// Largeur de `--stat` quand `COLUMNS` n'est pas défini.
+const DEFAULT_STAT_WIDTH: usize = 80;
+const DEV_NULL: &str = "/dev/null";
+
+/// Options de la commande `diff`.
+#[derive(Debug)]
+pub struct DiffOptions {
+    /// Compare l'index à un commit (HEAD par défaut) au lieu du répertoire de travail.
+    pub cached: bool,
+    /// Affiche le diff unifié de chaque fichier.
+    pub patch: bool,
+    pub stat: bool,
+    pub numstat: bool,
+    /// Affiche seulement la nature de chaque modification et le chemin ; l'emporte sur
+    /// les autres formats.
+    pub name_status: bool,
+    /// Lignes de contexte autour des modifications.
+    pub context_lines: usize,
+    /// Algorithme de comparaison ; par défaut `diff.algorithm`, sinon Myers.
+    pub algorithm: Option<DiffAlgorithm>,
+    /// Limite la comparaison à ces chemins, relatifs à la racine (`""` pour tout le dépôt).
+    pub paths: Vec<String>,
+}
+
+/// Fichier à comparer : sa modification et la provenance de sa nouvelle version.
+#[derive(Debug)]
+struct FilePair {
+    change: FileChange,
+    /// Nom de l'ancienne version : son chemin, ou le nom donné à un blob.
+    old_name: Vec<u8>,
+    /// La nouvelle version vient du répertoire de travail et n'est pas forcément dans le dépôt.
+    worktree: bool,
+}
+
+impl FilePair {
+    fn new(change: FileChange, worktree: bool) -> FilePair {
+        let old_name = change.old_path.clone().unwrap_or_else(|| change.path.clone());
+        FilePair { change, old_name, worktree }
+    }
+}
+
+/// Affiche les différences entre deux états du dépôt.
+///
+/// # Paramètres
+/// - `revisions` : aucune pour comparer l'index au répertoire de travail (ou HEAD à l'index
+///   avec `cached`) ; une pour comparer ce commit au répertoire de travail (ou à l'index) ;
+///   deux, ou `A..B`, pour comparer deux commits, arbres ou blobs.
+///
+/// # Erreurs
+/// - Révision inconnue, ou trop de révisions.
+pub fn diff<W: Write>(revisions: &[String], options: &DiffOptions, out: &mut W) -> Result<()> {
+    let algorithm = match options.algorithm {
+        Some(algorithm) => algorithm,
+        None => match ConfigSet::load()?.get_string("diff.algorithm")? {
+            Some(name) => DiffAlgorithm::parse(&name)?,
+            None => DiffAlgorithm::Myers,
+        },
+    };
+    let pairs: Vec<FilePair> = collect_pairs(revisions, options.cached)?
+        .into_iter()
+        .filter(|pair| matches_paths(&pair.change.path, &options.paths) || matches_paths(&pair.old_name, &options.paths))
+        .collect();
+
+    if options.name_status {
+        for pair in &pairs {
+            match pair.change.status() {
+                'R' => writeln!(out, "R100\t{}\t{}", quote_path(&pair.old_name), quote_path(&pair.change.path))?,
+                status => writeln!(out, "{}\t{}", status, quote_path(&pair.change.path))?,
+            }
+        }
+        return Ok(());
+    }
+
+    if options.stat || options.numstat {
+        let stats = pairs.iter().map(|pair| file_stat(pair, algorithm)).collect::<Result<Vec<_>>>()?;
+        if options.numstat {
+            stat::write_numstat(&stats, out)?;
+        }
+        if options.stat {
+            stat::write_stat(&stats, stat_width(), out)?;
+        }
+        if options.patch && !pairs.is_empty() {
+            writeln!(out)?;
+        }
+    }
+
+    if options.patch {
+        let patch_options = PatchOptions { context_lines: options.context_lines, algorithm };
+        for pair in &pairs {
+            write_patch(pair, &patch_options, out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Fichiers modifiés entre les deux états désignés par les révisions.
+fn collect_pairs<'a>(revisions: &'a [String], cached: bool) -> Result<Vec<FilePair>> {
+    // `A..B` compare A à B, un côté vide désignant HEAD
+    if let [range] = revisions {
+        if let Some((old, new)) = range.split_once("..") {
+            let or_head = |side: &'a str| if side.is_empty() { refs::HEAD } else { side };
+            return collect_revision_pairs(or_head(old), or_head(new));
+        }
+    }
+
+    match revisions {
+        [] if !cached => {
+            let lock = IndexLock::acquire().ok();
+            let mut index = Index::load()?;
+            let (changes, refreshed) = changes::diff_index_to_worktree(&mut index)?;
+            save_refreshed_index(lock, &index, refreshed);
+            Ok(changes.into_iter().map(|change| FilePair::new(change, true)).collect())
+        }
+        [] => {
+            let tree = match refs::resolve_ref(refs::HEAD)? {
+                Some(head) => Some(object_manager::read_commit_tree(&head)?),
+                None => None,
+            };
+            let changes = changes::diff_tree_to_index(tree.as_deref(), &Index::load()?)?;
+            Ok(changes.into_iter().map(|change| FilePair::new(change, false)).collect())
+        }
+        [revision] if cached => {
+            let tree = rev_parse::resolve_revision_as(revision, "tree")?;
+            let changes = changes::diff_tree_to_index(Some(&tree), &Index::load()?)?;
+            Ok(changes.into_iter().map(|change| FilePair::new(change, false)).collect())
+        }
+        [revision] => {
+            let tree = rev_parse::resolve_revision_as(revision, "tree")?;
+            let lock = IndexLock::acquire().ok();
+            let mut index = Index::load()?;
+            let (changes, refreshed) = changes::diff_tree_to_worktree(Some(&tree), &mut index)?;
+            save_refreshed_index(lock, &index, refreshed);
+            Ok(changes.into_iter().map(|change| FilePair::new(change, true)).collect())
+        }
+        [old, new] if !cached => collect_revision_pairs(old, new),
+        _ => Err(anyhow!("usage: git diff [<options>] [<commit> [<commit>]] [--] [<path>...]")),
+    }
+}
+
+/// Fichiers modifiés entre deux arbres, ou le blob modifié si les deux révisions en désignent.
+fn collect_revision_pairs(old: &str, new: &str) -> Result<Vec<FilePair>> {
+    let old_hash = rev_parse::resolve_revision(old)?;
+    let new_hash = rev_parse::resolve_revision(new)?;
+
+    if object_manager::read_object_type(&old_hash)? == "blob" && object_manager::read_object_type(&new_hash)? == "blob" {
+        if old_hash == new_hash {
+            return Ok(vec![]);
+        }
+        // Un blob est nommé par son chemin (`HEAD:chemin`), à défaut par son hash
+        let name = |revision: &str, hash: &str| match revision.split_once(':') {
+            Some((_, path)) => path.as_bytes().to_vec(),
+            None => hash.as_bytes().to_vec(),
+        };
+        let change = FileChange {
+            path: name(new, &new_hash),
+            old_path: None,
+            old: Some(FileVersion { mode: MODE_FILE, hash: old_hash.clone() }),
+            new: Some(FileVersion { mode: MODE_FILE, hash: new_hash.clone() }),
+        };
+        return Ok(vec![FilePair { change, old_name: name(old, &old_hash), worktree: false }]);
+    }
+
+    let old_tree = rev_parse::resolve_revision_as(old, "tree")?;
+    let new_tree = rev_parse::resolve_revision_as(new, "tree")?;
+    let changes = changes::diff_trees(&old_tree, &new_tree)?;
+    Ok(changes.into_iter().map(|change| FilePair::new(change, false)).collect())
+}
+
+/// Enregistre l'index rafraîchi au passage, comme git, si son verrou a pu être pris avant
+/// de le lire : un index verrouillé ou un dépôt en lecture seule n'empêchent pas d'afficher
+/// les différences.
+fn save_refreshed_index(lock: Option<IndexLock>, index: &Index, refreshed: bool) {
+    if let Some(lock) = lock.filter(|_| refreshed) {
+        let _ = lock.commit(index);
+    }
+}
+
+fn matches_paths(path: &[u8], paths: &[String]) -> bool {
+    paths.is_empty() || paths.iter().any(|filter| {
+        let filter = filter.as_bytes();
+        filter.is_empty() || path.strip_prefix(filter).is_some_and(|rest| rest.is_empty() || rest.starts_with(b"/"))
+    })
+}
+
+/// Écrit le diff d'un fichier : l'en-tête `diff --git` et ses métadonnées, puis le diff
+/// unifié ou la mention d'un fichier binaire.
+fn write_patch<W: Write>(pair: &FilePair, options: &PatchOptions, out: &mut W) -> Result<()> {
+    let change = &pair.change;
+    if change.status() == 'T' {
+        // Comme git, un changement de type s'affiche comme une suppression suivie d'un ajout
+        let deletion = FileChange { new: None, ..change.clone() };
+        write_patch(&FilePair { change: deletion, old_name: pair.old_name.clone(), worktree: false }, options, out)?;
+        let addition = FileChange { old: None, ..change.clone() };
+        return write_patch(&FilePair { change: addition, old_name: pair.old_name.clone(), worktree: pair.worktree }, options, out);
+    }
+
+    let old_label = quote_path(&[b"a/", pair.old_name.as_slice()].concat());
+    let new_label = quote_path(&[b"b/", change.path.as_slice()].concat());
+    writeln!(out, "diff --git {} {}", old_label, new_label)?;
+    match (&change.old, &change.new) {
+        (None, Some(new)) => writeln!(out, "new file mode {:06o}", new.mode)?,
+        (Some(old), None) => writeln!(out, "deleted file mode {:06o}", old.mode)?,
+        (Some(old), Some(new)) if old.mode != new.mode => {
+            writeln!(out, "old mode {:06o}", old.mode)?;
+            writeln!(out, "new mode {:06o}", new.mode)?;
+        }
+        _ => {}
+    }
+    if change.old_path.is_some() {
+        // Seuls les renommages sans modification du contenu sont détectés
+        writeln!(out, "similarity index 100%")?;
+        writeln!(out, "rename from {}", quote_path(&pair.old_name))?;
+        writeln!(out, "rename to {}", quote_path(&change.path))?;
+    }
+
+    let old_hash = change.old.as_ref().map_or(NULL_HASH, |version| version.hash.as_str());
+    let new_hash = change.new.as_ref().map_or(NULL_HASH, |version| version.hash.as_str());
+    if old_hash == new_hash {
+        return Ok(());
+    }
+    write!(out, "index {}..{}", rev_parse::abbreviate(old_hash, ABBREV_LENGTH)?, rev_parse::abbreviate(new_hash, ABBREV_LENGTH)?)?;
+    match (&change.old, &change.new) {
+        (Some(old), Some(new)) if old.mode == new.mode => writeln!(out, " {:06o}", old.mode)?,
+        _ => writeln!(out)?,
+    }
+
+    let (old, new) = read_pair(pair)?;
+    let old_label = if change.old.is_some() { old_label } else { DEV_NULL.to_string() };
+    let new_label = if change.new.is_some() { new_label } else { DEV_NULL.to_string() };
+    if patch::is_binary(&old) || patch::is_binary(&new) {
+        writeln!(out, "Binary files {} and {} differ", old_label, new_label)?;
+        return Ok(());
+    }
+    patch::write_unified([&old_label, &new_label], &old, &new, options, out)
+}
+
+/// Lignes ajoutées et supprimées d'un fichier, ou ses tailles s'il est binaire.
+fn file_stat(pair: &FilePair, algorithm: DiffAlgorithm) -> Result<FileStat> {
+    let change = &pair.change;
+    let name = match change.old_path {
+        Some(_) => stat::rename_name(&pair.old_name, &change.path),
+        None => quote_path(&change.path),
+    };
+    let unchanged = change.old.as_ref().zip(change.new.as_ref()).is_some_and(|(old, new)| old.hash == new.hash);
+    if unchanged {
+        return Ok(FileStat { name, added: 0, deleted: 0, binary: false });
+    }
+
+    let (old, new) = read_pair(pair)?;
+    if patch::is_binary(&old) || patch::is_binary(&new) {
+        return Ok(FileStat { name, added: new.len(), deleted: old.len(), binary: true });
+    }
+    let changes = algorithm::diff_lines(&algorithm::split_lines(&old), &algorithm::split_lines(&new), algorithm);
+    let (added, deleted) = changes.counts();
+    Ok(FileStat { name, added, deleted, binary: false })
+}
+
+/// Contenus des deux versions d'un fichier, vides pour une version absente.
+fn read_pair(pair: &FilePair) -> Result<(Vec<u8>, Vec<u8>)> {
+    let old = match &pair.change.old {
+        Some(version) => read_content(&pair.old_name, version, false)?,
+        None => vec![],
+    };
+    let new = match &pair.change.new {
+        Some(version) => read_content(&pair.change.path, version, pair.worktree)?,
+        None => vec![],
+    };
+    Ok((old, new))
+}
+
+/// Contenu d'une version de fichier ; un sous-module est représenté par son commit.
+fn read_content(path: &[u8], version: &FileVersion, worktree: bool) -> Result<Vec<u8>> {
+    if version.mode == MODE_GITLINK {
+        return Ok(format!("Subproject commit {}\n", version.hash).into_bytes());
+    }
+    // Les versions modifiées du répertoire de travail ne sont pas écrites dans le dépôt
+    if worktree && !object_manager::has_object(&version.hash)? {
+        let path = fs_reader::path_from_bytes(path);
+        return if version.mode == MODE_SYMLINK { FSReader.read_link(&path) } else { FSReader.read_file(&path) };
+    }
+    match object_manager::read_object(&version.hash)? {
+        GitObjectKind::Blob(blob) => Ok(blob.content),
+        _ => Err(anyhow!("object {} is not a blob", version.hash)),
+    }
+}
+
+/// Largeur de `--stat` : `COLUMNS`, à défaut 80 colonnes.
+fn stat_width() -> usize {
+    env::var("COLUMNS").ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|&columns| columns > 0)
+        .unwrap_or(DEFAULT_STAT_WIDTH)
+}
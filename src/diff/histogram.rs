@@ -0,0 +1,200 @@
+use crate::diff::myers;
+use std::collections::HashMap;
+
+/// Nombre d'occurrences au-delà duquel une ligne ne peut plus servir à aligner les versions.
+const MAX_CHAIN_LENGTH: u32 = 64;
+
+/// Marque les lignes modifiées avec l'algorithme histogram, comme `xdl_do_histogram_diff` de git.
+///
+/// La plus longue suite de lignes communes dont les lignes sont les moins fréquentes de
+/// l'ancienne version est alignée, puis les intervalles de part et d'autre sont comparés de
+/// la même façon. Si toutes les lignes communes sont trop fréquentes, l'intervalle est
+/// confié à Myers.
+pub(crate) fn diff(old: &[usize], new: &[usize], old_changed: &mut [bool], new_changed: &mut [bool]) {
+    Histogram { old, new, old_changed, new_changed }.compare(0, old.len(), 0, new.len());
+}
+
+struct Histogram<'a> {
+    old: &'a [usize],
+    new: &'a [usize],
+    old_changed: &'a mut [bool],
+    new_changed: &'a mut [bool],
+}
+
+/// Intervalle commun aux deux versions, bornes incluses.
+#[derive(Debug, Clone, Copy)]
+struct Region {
+    old_start: usize,
+    old_end: usize,
+    new_start: usize,
+    new_end: usize,
+}
+
+/// Résultat de la recherche d'un intervalle commun.
+enum Lcs {
+    Found(Region),
+    /// Aucune ligne commune.
+    Missing,
+    /// Seulement des lignes communes trop fréquentes.
+    TooFrequent,
+}
+
+/// Occurrences d'une ligne de l'ancienne version.
+#[derive(Debug, Clone, Copy)]
+struct Record {
+    /// Première occurrence ; les suivantes sont chaînées par [`LcsSearch::next_occurrence`].
+    first: usize,
+    count: u32,
+}
+
+impl Histogram<'_> {
+    fn compare(&mut self, mut old_start: usize, mut old_count: usize, mut new_start: usize, mut new_count: usize) {
+        loop {
+            if old_count == 0 || new_count == 0 {
+                self.old_changed[old_start..old_start + old_count].fill(true);
+                self.new_changed[new_start..new_start + new_count].fill(true);
+                return;
+            }
+
+            let old_range = old_start..old_start + old_count;
+            let new_range = new_start..new_start + new_count;
+            match LcsSearch::new(self.old, old_start, old_count).find(self.new, new_start, new_count) {
+                Lcs::TooFrequent => {
+                    myers::diff(&self.old[old_range.clone()], &self.new[new_range.clone()], false,
+                                &mut self.old_changed[old_range], &mut self.new_changed[new_range]);
+                    return;
+                }
+                Lcs::Missing => {
+                    self.old_changed[old_range].fill(true);
+                    self.new_changed[new_range].fill(true);
+                    return;
+                }
+                Lcs::Found(region) => {
+                    self.compare(old_start, region.old_start - old_start, new_start, region.new_start - new_start);
+                    old_count = old_range.end - (region.old_end + 1);
+                    old_start = region.old_end + 1;
+                    new_count = new_range.end - (region.new_end + 1);
+                    new_start = region.new_end + 1;
+                }
+            }
+        }
+    }
+}
+
+/// Index des lignes d'un intervalle de l'ancienne version, pour y chercher le meilleur
+/// intervalle commun.
+struct LcsSearch<'a> {
+    old: &'a [usize],
+    old_start: usize,
+    old_end: usize,
+    records: HashMap<usize, Record>,
+    /// Occurrence suivante de la même ligne, par position relative à `old_start`.
+    next_occurrence: Vec<Option<usize>>,
+    /// Plus petit nombre d'occurrences rencontré dans un intervalle commun retenu.
+    count: u32,
+    has_common: bool,
+    best: Option<Region>,
+}
+
+impl<'a> LcsSearch<'a> {
+    fn new(old: &'a [usize], old_start: usize, old_count: usize) -> LcsSearch<'a> {
+        let old_end = old_start + old_count - 1;
+        let mut records: HashMap<usize, Record> = HashMap::new();
+        let mut next_occurrence = vec![None; old_count];
+
+        for line in (old_start..=old_end).rev() {
+            records.entry(old[line])
+                .and_modify(|record| {
+                    next_occurrence[line - old_start] = Some(record.first);
+                    record.first = line;
+                    record.count = record.count.saturating_add(1);
+                })
+                .or_insert(Record { first: line, count: 1 });
+        }
+
+        LcsSearch {
+            old,
+            old_start,
+            old_end,
+            records,
+            next_occurrence,
+            count: MAX_CHAIN_LENGTH + 1,
+            has_common: false,
+            best: None,
+        }
+    }
+
+    fn find(mut self, new: &[usize], new_start: usize, new_count: usize) -> Lcs {
+        let new_end = new_start + new_count - 1;
+        let mut line = new_start;
+        while line <= new_end {
+            line = self.try_line(new, line, new_start, new_end);
+        }
+
+        match self.best {
+            _ if self.has_common && self.count > MAX_CHAIN_LENGTH => Lcs::TooFrequent,
+            Some(region) => Lcs::Found(region),
+            None => Lcs::Missing,
+        }
+    }
+
+    /// Étend chaque occurrence de la ligne `new_line` en intervalle commun.
+    ///
+    /// # Renvoie
+    /// - La prochaine ligne de la nouvelle version à essayer.
+    fn try_line(&mut self, new: &[usize], new_line: usize, new_start: usize, new_end: usize) -> usize {
+        let mut next_line = new_line + 1;
+        let Some(&record) = self.records.get(&new[new_line]) else {
+            return next_line;
+        };
+        self.has_common = true;
+        if record.count > self.count {
+            return next_line;
+        }
+
+        let mut old_line = record.first;
+        loop {
+            let mut next = self.next_occurrence[old_line - self.old_start];
+            let (mut old_first, mut new_first) = (old_line, new_line);
+            let (mut old_last, mut new_last) = (old_line, new_line);
+            let mut count = record.count;
+
+            while self.old_start < old_first && new_start < new_first && self.old[old_first - 1] == new[new_first - 1] {
+                old_first -= 1;
+                new_first -= 1;
+                if count > 1 {
+                    count = count.min(self.occurrences(old_first));
+                }
+            }
+            while old_last < self.old_end && new_last < new_end && self.old[old_last + 1] == new[new_last + 1] {
+                old_last += 1;
+                new_last += 1;
+                if count > 1 {
+                    count = count.min(self.occurrences(old_last));
+                }
+            }
+
+            if next_line <= new_last {
+                next_line = new_last + 1;
+            }
+            let best_length = self.best.map_or(0, |best| best.old_end - best.old_start);
+            if best_length < old_last - old_first || count < self.count {
+                self.best = Some(Region { old_start: old_first, old_end: old_last, new_start: new_first, new_end: new_last });
+                self.count = count;
+            }
+
+            // Les occurrences couvertes par cet intervalle ne donneraient rien de mieux
+            while let Some(candidate) = next.filter(|&candidate| candidate <= old_last) {
+                next = self.next_occurrence[candidate - self.old_start];
+            }
+            match next {
+                Some(candidate) => old_line = candidate,
+                None => return next_line,
+            }
+        }
+    }
+
+    fn occurrences(&self, old_line: usize) -> u32 {
+        self.records[&self.old[old_line]].count
+    }
+}
@@ -0,0 +1,7 @@
+pub mod algorithm;
+pub mod diff_manager;
+mod histogram;
+mod myers;
+pub mod patch;
+mod patience;
+pub mod stat;
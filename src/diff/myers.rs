@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+use std::ops::{Index, IndexMut};
+
+/// Coût à partir duquel la recherche du chemin optimal peut être abandonnée.
+const MAX_COST_MIN: i64 = 256;
+/// Coût à partir duquel les longues diagonales communes sont exploitées.
+const HEURISTIC_MIN_COST: i64 = 256;
+/// Longueur d'une diagonale commune jugée significative.
+const SNAKE_COUNT: i64 = 20;
+const HEURISTIC_FACTOR: i64 = 4;
+/// Nombre d'occurrences au-delà duquel une ligne est trop fréquente pour aligner les versions.
+const MAX_EQUAL_LIMIT: usize = 1024;
+/// Étendue examinée autour d'une ligne fréquente avant de l'écarter.
+const SIMILAR_SCAN_WINDOW: usize = 100;
+const KEEP_DISCARD_RUN: usize = 4;
+
+/// Marque les lignes modifiées entre deux suites d'identifiants de lignes, comme
+/// `xdl_do_diff` de git.
+///
+/// Les extrémités communes sont d'abord retirées, ainsi que les lignes absentes de l'autre
+/// version et, sauf en mode `minimal`, les lignes très fréquentes entourées de telles lignes.
+pub(crate) fn diff(old: &[usize], new: &[usize], minimal: bool, old_changed: &mut [bool], new_changed: &mut [bool]) {
+    let limit = old.len().min(new.len());
+    let mut start = 0;
+    while start < limit && old[start] == new[start] {
+        start += 1;
+    }
+    let mut suffix = 0;
+    while suffix < limit - start && old[old.len() - 1 - suffix] == new[new.len() - 1 - suffix] {
+        suffix += 1;
+    }
+
+    let mut counts: HashMap<usize, (usize, usize)> = HashMap::new();
+    for &id in old {
+        counts.entry(id).or_default().0 += 1;
+    }
+    for &id in new {
+        counts.entry(id).or_default().1 += 1;
+    }
+    let old_count = |id: &usize| counts.get(id).map_or(0, |&(count, _)| count);
+    let new_count = |id: &usize| counts.get(id).map_or(0, |&(_, count)| count);
+
+    let old_kept = kept_lines(old, start, old.len() - suffix, new_count, minimal, old_changed);
+    let new_kept = kept_lines(new, start, new.len() - suffix, old_count, minimal, new_changed);
+
+    let diagonal_count = old_kept.len() + new_kept.len() + 3;
+    let mut myers = Myers {
+        old: old_kept.iter().map(|&i| old[i]).collect(),
+        new: new_kept.iter().map(|&i| new[i]).collect(),
+        forward: Diagonals::new(diagonal_count, new_kept.len() + 1),
+        backward: Diagonals::new(diagonal_count, new_kept.len() + 1),
+        max_cost: (bogo_sqrt(diagonal_count) as i64).max(MAX_COST_MIN),
+    };
+    let mut changes = vec![];
+    myers.compare(0, myers.old.len() as i64, 0, myers.new.len() as i64, minimal, &mut changes);
+
+    for change in changes {
+        match change {
+            Change::Old(i) => old_changed[old_kept[i]] = true,
+            Change::New(i) => new_changed[new_kept[i]] = true,
+        }
+    }
+}
+
+/// Lignes de `lines[start..end]` à comparer ; les autres sont directement marquées modifiées.
+///
+/// # Paramètres
+/// - `other_count` : nombre d'occurrences d'un identifiant dans l'autre version.
+fn kept_lines(lines: &[usize], start: usize, end: usize, other_count: impl Fn(&usize) -> usize,
+              minimal: bool, changed: &mut [bool]) -> Vec<usize> {
+    let limit = bogo_sqrt(lines.len()).min(MAX_EQUAL_LIMIT);
+    // 0 : absente de l'autre version, 1 : présente, 2 : trop fréquente
+    let kinds: Vec<u8> = lines.iter().enumerate()
+        .map(|(i, id)| match other_count(id) {
+            _ if i < start || i >= end => 0,
+            0 => 0,
+            count if count >= limit && !minimal => 2,
+            _ => 1,
+        })
+        .collect();
+
+    let mut kept = vec![];
+    for i in start..end {
+        if kinds[i] == 1 || (kinds[i] == 2 && !is_discardable(&kinds, i, start, end - 1)) {
+            kept.push(i);
+        } else {
+            changed[i] = true;
+        }
+    }
+    kept
+}
+
+/// Indique si une ligne trop fréquente se trouve au milieu de lignes absentes de l'autre
+/// version, auquel cas elle est écartée de la comparaison.
+fn is_discardable(kinds: &[u8], i: usize, start: usize, end: usize) -> bool {
+    let start = start.max(i.saturating_sub(SIMILAR_SCAN_WINDOW));
+    let end = end.min(i + SIMILAR_SCAN_WINDOW);
+
+    let run = |range: &mut dyn Iterator<Item = usize>| -> (usize, usize) {
+        let (mut missing, mut frequent) = (0, 1);
+        for j in range {
+            match kinds[j] {
+                0 => missing += 1,
+                2 => frequent += 1,
+                _ => break,
+            }
+        }
+        (missing, frequent)
+    };
+
+    let (missing_before, frequent_before) = run(&mut (start..i).rev());
+    if missing_before == 0 {
+        return false;
+    }
+    let (missing_after, frequent_after) = run(&mut (i + 1..=end));
+    if missing_after == 0 {
+        return false;
+    }
+    let missing = missing_before + missing_after;
+    let frequent = frequent_before + frequent_after;
+    frequent * KEEP_DISCARD_RUN < frequent + missing
+}
+
+/// Approximation grossière de la racine carrée, par puissance de deux.
+fn bogo_sqrt(mut n: usize) -> usize {
+    let mut root = 1;
+    while n > 0 {
+        root <<= 1;
+        n >>= 2;
+    }
+    root
+}
+
+/// Ligne modifiée, par position dans les lignes comparées.
+enum Change {
+    Old(usize),
+    New(usize),
+}
+
+/// Position atteinte sur chaque diagonale `k = i1 - i2`, indexée de `-décalage` à `taille - décalage`.
+struct Diagonals {
+    values: Vec<i64>,
+    offset: i64,
+}
+
+impl Diagonals {
+    fn new(size: usize, offset: usize) -> Diagonals {
+        Diagonals { values: vec![0; size], offset: offset as i64 }
+    }
+}
+
+impl Index<i64> for Diagonals {
+    type Output = i64;
+
+    fn index(&self, diagonal: i64) -> &i64 {
+        &self.values[(diagonal + self.offset) as usize]
+    }
+}
+
+impl IndexMut<i64> for Diagonals {
+    fn index_mut(&mut self, diagonal: i64) -> &mut i64 {
+        &mut self.values[(diagonal + self.offset) as usize]
+    }
+}
+
+/// Point de coupure trouvé par [`Myers::split`], et s'il faut chercher le diff minimal
+/// de chaque côté.
+struct Split {
+    old: i64,
+    new: i64,
+    minimal_before: bool,
+    minimal_after: bool,
+}
+
+/// Recherche par « diviser pour régner » du plus court chemin d'édition, en espace linéaire.
+struct Myers {
+    old: Vec<usize>,
+    new: Vec<usize>,
+    forward: Diagonals,
+    backward: Diagonals,
+    max_cost: i64,
+}
+
+impl Myers {
+    fn compare(&mut self, mut old_start: i64, mut old_end: i64, mut new_start: i64, mut new_end: i64,
+               minimal: bool, changes: &mut Vec<Change>) {
+        while old_start < old_end && new_start < new_end && self.old[old_start as usize] == self.new[new_start as usize] {
+            old_start += 1;
+            new_start += 1;
+        }
+        while old_start < old_end && new_start < new_end
+            && self.old[old_end as usize - 1] == self.new[new_end as usize - 1] {
+            old_end -= 1;
+            new_end -= 1;
+        }
+
+        if old_start == old_end {
+            changes.extend((new_start..new_end).map(|i| Change::New(i as usize)));
+        } else if new_start == new_end {
+            changes.extend((old_start..old_end).map(|i| Change::Old(i as usize)));
+        } else {
+            let split = self.split(old_start, old_end, new_start, new_end, minimal);
+            self.compare(old_start, split.old, new_start, split.new, split.minimal_before, changes);
+            self.compare(split.old, old_end, split.new, new_end, split.minimal_after, changes);
+        }
+    }
+
+    /// Cherche le milieu du chemin d'édition en avançant depuis les deux coins à la fois,
+    /// ou, si le coût devient trop élevé, un point de coupure raisonnable.
+    fn split(&mut self, off1: i64, lim1: i64, off2: i64, lim2: i64, minimal: bool) -> Split {
+        let (old, new) = (&self.old, &self.new);
+        let (forward, backward) = (&mut self.forward, &mut self.backward);
+        let same = |i1: i64, i2: i64| old[i1 as usize] == new[i2 as usize];
+
+        let dmin = off1 - lim2;
+        let dmax = lim1 - off2;
+        let fmid = off1 - off2;
+        let bmid = lim1 - lim2;
+        let odd = (fmid - bmid) & 1 != 0;
+        let (mut fmin, mut fmax) = (fmid, fmid);
+        let (mut bmin, mut bmax) = (bmid, bmid);
+
+        forward[fmid] = off1;
+        backward[bmid] = lim1;
+
+        let mut cost = 1;
+        loop {
+            let mut got_snake = false;
+
+            // Le domaine des diagonales s'étend d'un cran de chaque côté, sans sortir de la boîte
+            if fmin > dmin {
+                fmin -= 1;
+                forward[fmin - 1] = -1;
+            } else {
+                fmin += 1;
+            }
+            if fmax < dmax {
+                fmax += 1;
+                forward[fmax + 1] = -1;
+            } else {
+                fmax -= 1;
+            }
+
+            for d in (fmin..=fmax).rev().step_by(2) {
+                let mut i1 = if forward[d - 1] >= forward[d + 1] { forward[d - 1] + 1 } else { forward[d + 1] };
+                let previous = i1;
+                let mut i2 = i1 - d;
+                while i1 < lim1 && i2 < lim2 && same(i1, i2) {
+                    i1 += 1;
+                    i2 += 1;
+                }
+                if i1 - previous > SNAKE_COUNT {
+                    got_snake = true;
+                }
+                forward[d] = i1;
+                if odd && bmin <= d && d <= bmax && backward[d] <= i1 {
+                    return Split { old: i1, new: i2, minimal_before: true, minimal_after: true };
+                }
+            }
+
+            if bmin > dmin {
+                bmin -= 1;
+                backward[bmin - 1] = i64::MAX;
+            } else {
+                bmin += 1;
+            }
+            if bmax < dmax {
+                bmax += 1;
+                backward[bmax + 1] = i64::MAX;
+            } else {
+                bmax -= 1;
+            }
+
+            for d in (bmin..=bmax).rev().step_by(2) {
+                let mut i1 = if backward[d - 1] < backward[d + 1] { backward[d - 1] } else { backward[d + 1] - 1 };
+                let previous = i1;
+                let mut i2 = i1 - d;
+                while i1 > off1 && i2 > off2 && same(i1 - 1, i2 - 1) {
+                    i1 -= 1;
+                    i2 -= 1;
+                }
+                if previous - i1 > SNAKE_COUNT {
+                    got_snake = true;
+                }
+                backward[d] = i1;
+                if !odd && fmin <= d && d <= fmax && i1 <= forward[d] {
+                    return Split { old: i1, new: i2, minimal_before: true, minimal_after: true };
+                }
+            }
+
+            if minimal {
+                cost += 1;
+                continue;
+            }
+
+            // Au-delà d'un certain coût, une diagonale avancée qui finit par une longue suite
+            // de lignes communes est un point de coupure satisfaisant
+            if got_snake && cost > HEURISTIC_MIN_COST {
+                let mut best = 0;
+                let mut best_split = None;
+                for d in (fmin..=fmax).rev().step_by(2) {
+                    let distance = (d - fmid).abs();
+                    let i1 = forward[d];
+                    let i2 = i1 - d;
+                    let value = (i1 - off1) + (i2 - off2) - distance;
+                    if value > HEURISTIC_FACTOR * cost && value > best
+                        && off1 + SNAKE_COUNT <= i1 && i1 < lim1
+                        && off2 + SNAKE_COUNT <= i2 && i2 < lim2
+                        && (1..=SNAKE_COUNT).all(|k| same(i1 - k, i2 - k)) {
+                        best = value;
+                        best_split = Some((i1, i2));
+                    }
+                }
+                if let Some((i1, i2)) = best_split {
+                    return Split { old: i1, new: i2, minimal_before: true, minimal_after: false };
+                }
+
+                best = 0;
+                for d in (bmin..=bmax).rev().step_by(2) {
+                    let distance = (d - bmid).abs();
+                    let i1 = backward[d];
+                    let i2 = i1 - d;
+                    let value = (lim1 - i1) + (lim2 - i2) - distance;
+                    if value > HEURISTIC_FACTOR * cost && value > best
+                        && off1 < i1 && i1 <= lim1 - SNAKE_COUNT
+                        && off2 < i2 && i2 <= lim2 - SNAKE_COUNT
+                        && (0..SNAKE_COUNT).all(|k| same(i1 + k, i2 + k)) {
+                        best = value;
+                        best_split = Some((i1, i2));
+                    }
+                }
+                if let Some((i1, i2)) = best_split {
+                    return Split { old: i1, new: i2, minimal_before: false, minimal_after: true };
+                }
+            }
+
+            // Trop cher : la diagonale la plus avancée d'un des deux côtés fait l'affaire
+            if cost >= self.max_cost {
+                let (mut forward_best, mut forward_old) = (-1, -1);
+                for d in (fmin..=fmax).rev().step_by(2) {
+                    let mut i1 = forward[d].min(lim1);
+                    let mut i2 = i1 - d;
+                    if lim2 < i2 {
+                        i1 = lim2 + d;
+                        i2 = lim2;
+                    }
+                    if forward_best < i1 + i2 {
+                        forward_best = i1 + i2;
+                        forward_old = i1;
+                    }
+                }
+
+                let (mut backward_best, mut backward_old) = (i64::MAX, i64::MAX);
+                for d in (bmin..=bmax).rev().step_by(2) {
+                    let mut i1 = backward[d].max(off1);
+                    let mut i2 = i1 - d;
+                    if i2 < off2 {
+                        i1 = off2 + d;
+                        i2 = off2;
+                    }
+                    if i1 + i2 < backward_best {
+                        backward_best = i1 + i2;
+                        backward_old = i1;
+                    }
+                }
+
+                return if (lim1 + lim2) - backward_best < forward_best - (off1 + off2) {
+                    Split { old: forward_old, new: forward_best - forward_old, minimal_before: true, minimal_after: false }
+                } else {
+                    Split { old: backward_old, new: backward_best - backward_old, minimal_before: false, minimal_after: true }
+                };
+            }
+
+            cost += 1;
+        }
+    }
+}
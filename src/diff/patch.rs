@@ -0,0 +1,180 @@
+use crate::diff::algorithm::{self, DiffAlgorithm, LineChanges};
+use anyhow::Result;
+use std::io::Write;
+
+/// Nombre d'octets examinés pour reconnaître un contenu binaire, comme dans git.
+const BINARY_CHECK_LENGTH: usize = 8000;
+/// Longueur maximale du nom de fonction affiché dans l'en-tête d'une section.
+const FUNCTION_NAME_LENGTH: usize = 80;
+const NO_NEWLINE_MARKER: &[u8] = b"\\ No newline at end of file\n";
+
+/// Options des diffs unifiés.
+#[derive(Debug, Clone, Copy)]
+pub struct PatchOptions {
+    /// Lignes de contexte autour de chaque modification (`-U`).
+    pub context_lines: usize,
+    pub algorithm: DiffAlgorithm,
+}
+
+/// Bloc de lignes remplacées : `old_count` lignes de l'ancienne version à partir de
+/// `old_start` par `new_count` lignes de la nouvelle à partir de `new_start`.
+#[derive(Debug, Clone, Copy)]
+struct Edit {
+    old_start: usize,
+    old_count: usize,
+    new_start: usize,
+    new_count: usize,
+}
+
+/// Indique si un contenu est binaire : git le considère comme tel s'il contient un octet nul
+/// dans ses premiers octets.
+pub fn is_binary(content: &[u8]) -> bool {
+    content[..content.len().min(BINARY_CHECK_LENGTH)].contains(&0)
+}
+
+/// Écrit le diff unifié de deux contenus texte : les lignes `---` et `+++`, puis chaque
+/// section (`@@ ... @@`) avec son contexte.
+///
+/// Comme git, rien n'est écrit si les contenus sont identiques, et l'en-tête d'une section
+/// rappelle la dernière ligne de l'ancienne version qui la précède et commence comme une
+/// définition (une lettre, `_` ou `$`).
+///
+/// # Paramètres
+/// - `labels` : noms des deux versions, déjà mis entre guillemets si nécessaire
+///   (`a/chemin`, ou `/dev/null` pour une version absente).
+pub fn write_unified<W: Write>(labels: [&str; 2], old: &[u8], new: &[u8], options: &PatchOptions, out: &mut W) -> Result<()> {
+    let old_lines = algorithm::split_lines(old);
+    let new_lines = algorithm::split_lines(new);
+    let edits = edit_script(&algorithm::diff_lines(&old_lines, &new_lines, options.algorithm));
+    if edits.is_empty() {
+        return Ok(());
+    }
+
+    for (marker, label) in ["---", "+++"].iter().zip(labels) {
+        // Un nom qui contient une espace est suivi d'une tabulation, pour les outils de patch
+        let tab = if label.contains(' ') { "\t" } else { "" };
+        writeln!(out, "{} {}{}", marker, label, tab)?;
+    }
+
+    let context = options.context_lines;
+    let mut function_name: Vec<u8> = vec![];
+    let mut function_searched_until: Option<usize> = None;
+    let mut first = 0;
+    while first < edits.len() {
+        // Les modifications séparées par au plus deux fois le contexte forment une seule section
+        let mut last = first;
+        while last + 1 < edits.len() && edits[last + 1].old_start - (edits[last].old_start + edits[last].old_count) <= 2 * context {
+            last += 1;
+        }
+        let (first_edit, last_edit) = (edits[first], edits[last]);
+
+        let mut old_line = first_edit.old_start.saturating_sub(context);
+        let mut new_line = first_edit.new_start.saturating_sub(context);
+        let trailing = context
+            .min(old_lines.len() - (last_edit.old_start + last_edit.old_count))
+            .min(new_lines.len() - (last_edit.new_start + last_edit.new_count));
+        let old_end = last_edit.old_start + last_edit.old_count + trailing;
+        let new_end = last_edit.new_start + last_edit.new_count + trailing;
+
+        // La recherche reprend où la section précédente l'a laissée, et garde son résultat à défaut
+        let searched = function_searched_until.map_or(0, |line| line + 1);
+        if let Some(name) = (searched..old_line).rev().find_map(|line| function_line(old_lines[line])) {
+            function_name = name;
+        }
+        function_searched_until = old_line.checked_sub(1);
+
+        write!(out, "@@ -{} +{} @@", range(old_line, old_end - old_line), range(new_line, new_end - new_line))?;
+        if !function_name.is_empty() {
+            write!(out, " ")?;
+            out.write_all(&function_name)?;
+        }
+        writeln!(out)?;
+
+        for edit in &edits[first..=last] {
+            while old_line < edit.old_start && new_line < edit.new_start {
+                write_line(b' ', new_lines[new_line], out)?;
+                old_line += 1;
+                new_line += 1;
+            }
+            for line in &old_lines[edit.old_start..edit.old_start + edit.old_count] {
+                write_line(b'-', line, out)?;
+            }
+            for line in &new_lines[edit.new_start..edit.new_start + edit.new_count] {
+                write_line(b'+', line, out)?;
+            }
+            old_line = edit.old_start + edit.old_count;
+            new_line = edit.new_start + edit.new_count;
+        }
+        for line in &new_lines[new_line..new_end] {
+            write_line(b' ', line, out)?;
+        }
+
+        first = last + 1;
+    }
+    Ok(())
+}
+
+/// Regroupe les lignes modifiées en blocs de remplacement, dans l'ordre des fichiers.
+fn edit_script(changes: &LineChanges) -> Vec<Edit> {
+    let (old_len, new_len) = (changes.old.len(), changes.new.len());
+    let mut edits = vec![];
+    let (mut old_line, mut new_line) = (0, 0);
+
+    while old_line < old_len || new_line < new_len {
+        let old_changed = old_line < old_len && changes.old[old_line];
+        let new_changed = new_line < new_len && changes.new[new_line];
+        if !old_changed && !new_changed {
+            old_line += 1;
+            new_line += 1;
+            continue;
+        }
+
+        let mut edit = Edit { old_start: old_line, old_count: 0, new_start: new_line, new_count: 0 };
+        while old_line < old_len && changes.old[old_line] {
+            old_line += 1;
+            edit.old_count += 1;
+        }
+        while new_line < new_len && changes.new[new_line] {
+            new_line += 1;
+            edit.new_count += 1;
+        }
+        edits.push(edit);
+    }
+    edits
+}
+
+/// Intervalle d'un en-tête de section : `début,nombre`, le nombre étant omis s'il vaut 1.
+/// Un intervalle vide désigne la ligne qui le précède.
+fn range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, count),
+    }
+}
+
+/// Nom de fonction que représente une ligne, selon la règle par défaut de git.
+fn function_line(line: &[u8]) -> Option<Vec<u8>> {
+    let first = *line.first()?;
+    if !(first.is_ascii_alphabetic() || first == b'_' || first == b'$') {
+        return None;
+    }
+    let mut name = &line[..line.len().min(FUNCTION_NAME_LENGTH)];
+    while let Some((last, rest)) = name.split_last() {
+        if !last.is_ascii_whitespace() && *last != 0x0b {
+            break;
+        }
+        name = rest;
+    }
+    Some(name.to_vec())
+}
+
+fn write_line<W: Write>(marker: u8, line: &[u8], out: &mut W) -> Result<()> {
+    out.write_all(&[marker])?;
+    out.write_all(line)?;
+    if !line.ends_with(b"\n") {
+        out.write_all(b"\n")?;
+        out.write_all(NO_NEWLINE_MARKER)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use crate::diff::myers;
+use std::collections::HashMap;
+
+/// Marque les lignes modifiées avec l'algorithme patience, comme `xdl_do_patience_diff` de git.
+///
+/// Les lignes présentes une seule fois de chaque côté servent d'ancres : la plus longue suite
+/// d'ancres dans le même ordre est alignée, puis chaque intervalle entre deux ancres est
+/// comparé de la même façon. Sans ancre, l'intervalle est confié à Myers.
+pub(crate) fn diff(old: &[usize], new: &[usize], old_changed: &mut [bool], new_changed: &mut [bool]) {
+    Patience { old, new, old_changed, new_changed }.compare(0, old.len(), 0, new.len());
+}
+
+struct Patience<'a> {
+    old: &'a [usize],
+    new: &'a [usize],
+    old_changed: &'a mut [bool],
+    new_changed: &'a mut [bool],
+}
+
+/// Occurrences d'une ligne dans l'intervalle comparé.
+#[derive(Default)]
+struct Occurrences {
+    old_count: usize,
+    old_line: usize,
+    new_count: usize,
+    new_line: usize,
+}
+
+impl Patience<'_> {
+    fn compare(&mut self, old_start: usize, old_count: usize, new_start: usize, new_count: usize) {
+        let old_range = old_start..old_start + old_count;
+        let new_range = new_start..new_start + new_count;
+        if old_count == 0 || new_count == 0 {
+            self.old_changed[old_range].fill(true);
+            self.new_changed[new_range].fill(true);
+            return;
+        }
+
+        let mut occurrences: HashMap<usize, Occurrences> = HashMap::new();
+        let mut order = vec![];
+        for i in old_range.clone() {
+            let entry = occurrences.entry(self.old[i]).or_insert_with(|| {
+                order.push(self.old[i]);
+                Occurrences { old_line: i, ..Occurrences::default() }
+            });
+            entry.old_count += 1;
+        }
+        let mut has_matches = false;
+        for i in new_range.clone() {
+            if let Some(entry) = occurrences.get_mut(&self.new[i]) {
+                has_matches = true;
+                entry.new_count += 1;
+                entry.new_line = i;
+            }
+        }
+
+        if !has_matches {
+            self.old_changed[old_range].fill(true);
+            self.new_changed[new_range].fill(true);
+            return;
+        }
+
+        let unique: Vec<(usize, usize)> = order.iter()
+            .map(|id| &occurrences[id])
+            .filter(|entry| entry.old_count == 1 && entry.new_count == 1)
+            .map(|entry| (entry.old_line, entry.new_line))
+            .collect();
+        let anchors = longest_increasing_sequence(&unique);
+
+        if anchors.is_empty() {
+            myers::diff(&self.old[old_range.clone()], &self.new[new_range.clone()], false,
+                        &mut self.old_changed[old_range], &mut self.new_changed[new_range]);
+        } else {
+            self.walk_anchors(&anchors, old_range.end, new_range.end, old_start, new_start);
+        }
+    }
+
+    /// Aligne les ancres et les lignes identiques qui les entourent, puis compare les intervalles
+    /// restants entre elles.
+    fn walk_anchors(&mut self, anchors: &[(usize, usize)], old_end: usize, new_end: usize,
+                    mut old_line: usize, mut new_line: usize) {
+        let mut k = 0;
+        loop {
+            let (old_next, new_next) = match anchors.get(k) {
+                Some(&(old_anchor, new_anchor)) => {
+                    let (mut old_next, mut new_next) = (old_anchor, new_anchor);
+                    while old_next > old_line && new_next > new_line && self.old[old_next - 1] == self.new[new_next - 1] {
+                        old_next -= 1;
+                        new_next -= 1;
+                    }
+                    (old_next, new_next)
+                }
+                None => (old_end, new_end),
+            };
+            while old_line < old_next && new_line < new_next && self.old[old_line] == self.new[new_line] {
+                old_line += 1;
+                new_line += 1;
+            }
+
+            if old_next > old_line || new_next > new_line {
+                self.compare(old_line, old_next - old_line, new_line, new_next - new_line);
+            }
+            if k == anchors.len() {
+                return;
+            }
+
+            // Les ancres consécutives des deux côtés forment un seul bloc commun
+            while k + 1 < anchors.len() && anchors[k + 1].0 == anchors[k].0 + 1 && anchors[k + 1].1 == anchors[k].1 + 1 {
+                k += 1;
+            }
+            old_line = anchors[k].0 + 1;
+            new_line = anchors[k].1 + 1;
+            k += 1;
+        }
+    }
+}
+
+/// Plus longue suite de paires `(ancienne ligne, nouvelle ligne)` croissante des deux côtés,
+/// les paires étant données dans l'ordre de l'ancienne version.
+fn longest_increasing_sequence(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // Pour chaque longueur, la suite qui se termine par la plus petite nouvelle ligne
+    let mut tails: Vec<usize> = vec![];
+    let mut previous: Vec<Option<usize>> = vec![None; pairs.len()];
+
+    for (i, &(_, new_line)) in pairs.iter().enumerate() {
+        let length = tails.partition_point(|&tail| pairs[tail].1 < new_line);
+        previous[i] = length.checked_sub(1).map(|last| tails[last]);
+        if length == tails.len() {
+            tails.push(i);
+        } else {
+            tails[length] = i;
+        }
+    }
+
+    let mut sequence = vec![];
+    let mut current = tails.last().copied();
+    while let Some(i) = current {
+        sequence.push(pairs[i]);
+        current = previous[i];
+    }
+    sequence.reverse();
+    sequence
+}
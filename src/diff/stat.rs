@@ -0,0 +1,199 @@
+use crate::quote::quote_path;
+use anyhow::Result;
+use std::io::Write;
+
+/// Largeur minimale réservée au nom des fichiers et au graphe.
+const MIN_STAT_WIDTH: usize = 16 + 6;
+/// Largeur minimale du graphe quand la place manque.
+const MIN_GRAPH_WIDTH: i64 = 6;
+
+/// Lignes ajoutées et supprimées d'un fichier.
+#[derive(Debug)]
+pub struct FileStat {
+    /// Nom affiché : le chemin, ou `ancien => nouveau` pour un renommage.
+    pub name: String,
+    /// Lignes ajoutées, ou taille de la nouvelle version d'un fichier binaire.
+    pub added: usize,
+    /// Lignes supprimées, ou taille de l'ancienne version d'un fichier binaire.
+    pub deleted: usize,
+    pub binary: bool,
+}
+
+/// Nom affiché d'un fichier renommé, la partie commune des chemins étant factorisée
+/// comme dans git : `dir/{old => new}/file`.
+pub fn rename_name(old: &[u8], new: &[u8]) -> String {
+    let (quoted_old, quoted_new) = (quote_path(old), quote_path(new));
+    if quoted_old.as_bytes() != old || quoted_new.as_bytes() != new {
+        return format!("{} => {}", quoted_old, quoted_new);
+    }
+
+    // Préfixe commun, jusqu'à un `/` inclus
+    let mut prefix = 0;
+    for (i, (a, b)) in old.iter().zip(new).enumerate() {
+        if a != b {
+            break;
+        }
+        if *a == b'/' {
+            prefix = i + 1;
+        }
+    }
+
+    // Suffixe commun, depuis un `/` inclus ; il peut partager le `/` final du préfixe
+    let byte = |path: &[u8], i: usize| path.get(i).copied().unwrap_or(0);
+    let lower_bound = prefix.saturating_sub(1);
+    let (mut i, mut j) = (old.len(), new.len());
+    let mut suffix = 0;
+    loop {
+        if byte(old, i) != byte(new, j) {
+            break;
+        }
+        if byte(old, i) == b'/' {
+            suffix = old.len() - i;
+        }
+        if i == lower_bound || j == lower_bound {
+            break;
+        }
+        i -= 1;
+        j -= 1;
+    }
+
+    let old_middle = old.len().saturating_sub(prefix + suffix);
+    let new_middle = new.len().saturating_sub(prefix + suffix);
+    let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+    let middle = format!("{} => {}", text(&old[prefix..prefix + old_middle]), text(&new[prefix..prefix + new_middle]));
+    if prefix + suffix == 0 {
+        middle
+    } else {
+        format!("{}{{{}}}{}", text(&old[..prefix]), middle, text(&old[old.len() - suffix..]))
+    }
+}
+
+/// Affiche une ligne `ajouts<TAB>suppressions<TAB>nom` par fichier, comme `--numstat`.
+pub fn write_numstat<W: Write>(stats: &[FileStat], out: &mut W) -> Result<()> {
+    for stat in stats {
+        if stat.binary {
+            writeln!(out, "-\t-\t{}", stat.name)?;
+        } else {
+            writeln!(out, "{}\t{}\t{}", stat.added, stat.deleted, stat.name)?;
+        }
+    }
+    Ok(())
+}
+
+/// Affiche l'histogramme des modifications de chaque fichier, puis le total, comme `--stat`.
+///
+/// # Paramètres
+/// - `width` : largeur totale disponible ; les noms trop longs sont tronqués par le début
+///   et le graphe est mis à l'échelle.
+pub fn write_stat<W: Write>(stats: &[FileStat], width: usize, out: &mut W) -> Result<()> {
+    if stats.is_empty() {
+        return Ok(());
+    }
+
+    let max_name = stats.iter().map(|stat| stat.name.len()).max().unwrap_or(0);
+    let max_change = stats.iter().filter(|stat| !stat.binary).map(|stat| stat.added + stat.deleted).max().unwrap_or(0);
+    // `Bin XXX -> YYY bytes`
+    let binary_width = stats.iter().filter(|stat| stat.binary)
+        .map(|stat| 14 + decimal_width(stat.added) + decimal_width(stat.deleted))
+        .max().unwrap_or(0);
+    let number_width = decimal_width(max_change).max(if binary_width > 0 { 3 } else { 0 });
+    let width = width.max(MIN_STAT_WIDTH + number_width) as i64;
+
+    let mut graph_width = if max_change + 4 > binary_width { max_change } else { binary_width - 4 } as i64;
+    let mut name_width = max_name as i64;
+    let fixed_width = number_width as i64 + 6;
+    if name_width + fixed_width + graph_width > width {
+        if graph_width > width * 3 / 8 - fixed_width {
+            graph_width = (width * 3 / 8 - fixed_width).max(MIN_GRAPH_WIDTH);
+        }
+        if name_width > width - fixed_width - graph_width {
+            name_width = width - fixed_width - graph_width;
+        } else {
+            graph_width = width - fixed_width - name_width;
+        }
+    }
+    let (name_width, graph_width) = (name_width.max(0) as usize, graph_width.max(0) as usize);
+
+    let (mut insertions, mut deletions) = (0, 0);
+    for stat in stats {
+        let mut name = stat.name.as_str();
+        let mut prefix = "";
+        let mut available = name_width;
+        if name.len() > name_width {
+            prefix = "...";
+            available = available.saturating_sub(3);
+            name = &name[name.len() - available..];
+            if let Some(slash) = name.find('/') {
+                name = &name[slash..];
+            }
+        }
+        let padding = " ".repeat(available.saturating_sub(name.len()));
+
+        if stat.binary {
+            write!(out, " {}{}{} | {:>width$}", prefix, name, padding, "Bin", width = number_width)?;
+            if stat.added == 0 && stat.deleted == 0 {
+                writeln!(out)?;
+            } else {
+                writeln!(out, " {} -> {} bytes", stat.deleted, stat.added)?;
+            }
+            continue;
+        }
+
+        insertions += stat.added;
+        deletions += stat.deleted;
+        let (mut added, mut deleted) = (stat.added, stat.deleted);
+        if graph_width <= max_change {
+            let mut total = scale_linear(added + deleted, graph_width, max_change);
+            if total < 2 && added > 0 && deleted > 0 {
+                total = 2;
+            }
+            if added < deleted {
+                added = scale_linear(added, graph_width, max_change);
+                deleted = total - added;
+            } else {
+                deleted = scale_linear(deleted, graph_width, max_change);
+                added = total - deleted;
+            }
+        }
+        let changes = stat.added + stat.deleted;
+        writeln!(out, " {}{}{} | {:>width$}{}{}{}", prefix, name, padding, changes,
+                 if changes > 0 { " " } else { "" }, "+".repeat(added), "-".repeat(deleted), width = number_width)?;
+    }
+
+    writeln!(out, "{}", summary(stats.len(), insertions, deletions))?;
+    Ok(())
+}
+
+/// Ligne de total : ` N files changed, X insertions(+), Y deletions(-)`, un total nul étant
+/// omis sauf si les deux le sont.
+fn summary(files: usize, insertions: usize, deletions: usize) -> String {
+    let plural = |count: usize, singular: &str, plural: &str| {
+        format!("{} {}", count, if count == 1 { singular } else { plural })
+    };
+    let mut summary = format!(" {}", plural(files, "file changed", "files changed"));
+    if insertions > 0 || deletions == 0 {
+        summary.push_str(&format!(", {}", plural(insertions, "insertion(+)", "insertions(+)")));
+    }
+    if deletions > 0 || insertions == 0 {
+        summary.push_str(&format!(", {}", plural(deletions, "deletion(-)", "deletions(-)")));
+    }
+    summary
+}
+
+/// Met un nombre de modifications à l'échelle du graphe, sans jamais réduire à zéro
+/// un nombre non nul.
+fn scale_linear(count: usize, width: usize, max_change: usize) -> usize {
+    if count == 0 {
+        return 0;
+    }
+    1 + count * (width.saturating_sub(1)) / max_change
+}
+
+fn decimal_width(mut number: usize) -> usize {
+    let mut width = 1;
+    while number >= 10 {
+        number /= 10;
+        width += 1;
+    }
+    width
+}
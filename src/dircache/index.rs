@@ -0,0 +1,276 @@
+use crate::dircache::index_entry::{IndexEntry, FLAG_ASSUME_VALID, FLAG_EXTENDED, FLAG_STAGE_MASK, FLAG_STAGE_SHIFT};
+use crate::repository;
+use anyhow::{anyhow, Result};
+use sha1::{Digest, Sha1};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const INDEX_SIGNATURE: &[u8; 4] = b"DIRC";
+const HEADER_SIZE: usize = 12;
+const CHECKSUM_SIZE: usize = 20;
+/// Taille de la partie fixe d'une entrée, jusqu'aux drapeaux inclus.
+const ENTRY_FIXED_SIZE: usize = 62;
+
+/// Verrou `.git/index.lock` : le nouvel index y est écrit puis renommé sur `.git/index`.
+///
+/// Le fichier de verrou est supprimé si l'écriture n'est pas validée.
+pub struct IndexLock {
+    path: PathBuf,
+    lock_path: PathBuf,
+    file: File,
+    committed: bool,
+}
+
+impl IndexLock {
+    /// Prend le verrou de l'index.
+    ///
+    /// # Erreurs
+    /// - Le verrou est déjà pris, par une autre commande en cours ou interrompue.
+    pub fn acquire() -> Result<IndexLock> {
+        let path = Index::path()?;
+        let lock_path = path.with_extension("lock");
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+            .map_err(|e| anyhow!("Unable to create '{}': {}", lock_path.display(), e))?;
+
+        Ok(IndexLock { path, lock_path, file, committed: false })
+    }
+
+    /// Écrit l'index dans le verrou, puis le renomme sur `.git/index`.
+    pub fn commit(mut self, index: &Index) -> Result<()> {
+        self.file.write_all(&index.serialize())?;
+        self.file.sync_all()?;
+        fs::rename(&self.lock_path, &self.path)?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for IndexLock {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.lock_path);
+        }
+    }
+}
+
+/// L'index (staging area) stocké dans `.git/index`.
+#[derive(Debug, Default, Clone)]
+pub struct Index {
+    pub version: u32,
+    /// Entrées triées par chemin puis par étape de fusion.
+    pub entries: Vec<IndexEntry>,
+}
+
+impl Index {
+    /// Chemin du fichier d'index du dépôt.
+    pub fn path() -> Result<PathBuf> {
+        Ok(repository::current()?.path("index"))
+    }
+
+    /// Charge l'index du dépôt, ou un index vide s'il n'existe pas encore.
+    pub fn load() -> Result<Index> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Index { version: 2, entries: vec![] });
+        }
+        Self::read_from(&path)
+    }
+
+    /// Date de dernière écriture de l'index, en secondes et nanosecondes, ou `None` s'il
+    /// n'existe pas encore.
+    pub fn modification_time() -> Result<Option<(u32, u32)>> {
+        let Ok(metadata) = fs::metadata(Self::path()?) else {
+            return Ok(None);
+        };
+        let since_epoch = metadata.modified()?.duration_since(UNIX_EPOCH).unwrap_or_default();
+        Ok(Some((since_epoch.as_secs() as u32, since_epoch.subsec_nanos())))
+    }
+
+    /// Lit et valide un fichier d'index au format DIRC (versions 2 et 3).
+    pub fn read_from(path: &Path) -> Result<Index> {
+        let data = fs::read(path)?;
+        Self::parse(&data).map_err(|e| anyhow!("Invalid index file {:?}: {}", path, e))
+    }
+
+    fn parse(data: &[u8]) -> Result<Index> {
+        if data.len() < HEADER_SIZE + CHECKSUM_SIZE || &data[..4] != INDEX_SIGNATURE {
+            return Err(anyhow!("bad signature"));
+        }
+
+        let (content, checksum) = data.split_at(data.len() - CHECKSUM_SIZE);
+        if Sha1::digest(content).as_slice() != checksum {
+            return Err(anyhow!("bad checksum"));
+        }
+
+        let version = read_u32(content, 4);
+        if version != 2 && version != 3 {
+            return Err(anyhow!("unsupported version {}", version));
+        }
+        let count = read_u32(content, 8) as usize;
+
+        let mut entries = Vec::with_capacity(count);
+        let mut idx = HEADER_SIZE;
+        for _ in 0..count {
+            let start = idx;
+            if idx + ENTRY_FIXED_SIZE > content.len() {
+                return Err(anyhow!("truncated entry"));
+            }
+
+            let flags = read_u16(content, idx + 60);
+            idx += ENTRY_FIXED_SIZE;
+
+            let extended_flags = if flags & FLAG_EXTENDED != 0 {
+                if version < 3 {
+                    return Err(anyhow!("extended flags in a version {} index", version));
+                }
+                idx += 2;
+                read_u16(content, idx - 2)
+            } else {
+                0
+            };
+
+            // Le chemin est terminé par un NUL, puis complété par des NUL jusqu'à un multiple de 8 octets
+            let path_length = content[idx..].iter()
+                .position(|&b| b == 0)
+                .ok_or_else(|| anyhow!("unterminated entry path"))?;
+            let path = content[idx..idx + path_length].to_vec();
+            idx = start + padded_entry_size(idx - start + path_length);
+
+            entries.push(IndexEntry {
+                ctime_seconds: read_u32(content, start),
+                ctime_nanoseconds: read_u32(content, start + 4),
+                mtime_seconds: read_u32(content, start + 8),
+                mtime_nanoseconds: read_u32(content, start + 12),
+                dev: read_u32(content, start + 16),
+                ino: read_u32(content, start + 20),
+                mode: read_u32(content, start + 24),
+                uid: read_u32(content, start + 28),
+                gid: read_u32(content, start + 32),
+                size: read_u32(content, start + 36),
+                hash: hex::encode(&content[start + 40..start + 60]),
+                assume_valid: flags & FLAG_ASSUME_VALID != 0,
+                stage: ((flags & FLAG_STAGE_MASK) >> FLAG_STAGE_SHIFT) as u8,
+                extended_flags,
+                path,
+            });
+        }
+
+        // Les extensions (cache-tree, resolve-undo...) sont facultatives si leur signature
+        // commence par une majuscule ; elles sont ignorées et ne seront pas réécrites
+        while idx + 8 <= content.len() {
+            let signature = &content[idx..idx + 4];
+            let size = read_u32(content, idx + 4) as usize;
+            if !signature[0].is_ascii_uppercase() {
+                return Err(anyhow!("unsupported required extension {}", String::from_utf8_lossy(signature)));
+            }
+            idx += 8 + size;
+        }
+
+        Ok(Index { version, entries })
+    }
+
+    /// Écrit l'index du dépôt de façon atomique via `.git/index.lock`.
+    ///
+    /// Pour modifier l'index, mieux vaut prendre le verrou avant de le lire (voir [`IndexLock`]) :
+    /// sinon une modification concurrente enregistrée entre-temps serait écrasée.
+    pub fn save(&self) -> Result<()> {
+        IndexLock::acquire()?.commit(self)
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        // La version 3 n'est nécessaire que si une entrée porte des drapeaux étendus
+        let version = if self.entries.iter().any(|entry| entry.extended_flags != 0) { 3 } else { 2 };
+
+        let mut data = Vec::new();
+        data.extend_from_slice(INDEX_SIGNATURE);
+        data.extend_from_slice(&(version as u32).to_be_bytes());
+        data.extend_from_slice(&(self.entries.len() as u32).to_be_bytes());
+
+        for entry in &self.entries {
+            let start = data.len();
+            for value in [
+                entry.ctime_seconds, entry.ctime_nanoseconds, entry.mtime_seconds, entry.mtime_nanoseconds,
+                entry.dev, entry.ino, entry.mode, entry.uid, entry.gid, entry.size,
+            ] {
+                data.extend_from_slice(&value.to_be_bytes());
+            }
+            data.extend_from_slice(&hex::decode(&entry.hash).expect("Invalid hash format"));
+            data.extend_from_slice(&entry.flags().to_be_bytes());
+            if entry.extended_flags != 0 {
+                data.extend_from_slice(&entry.extended_flags.to_be_bytes());
+            }
+            data.extend_from_slice(&entry.path);
+
+            let entry_size = padded_entry_size(data.len() - start);
+            data.resize(start + entry_size, 0);
+        }
+
+        let checksum = Sha1::digest(&data);
+        data.extend_from_slice(&checksum);
+        data
+    }
+
+    /// Recherche l'entrée (hors conflit) correspondant au chemin.
+    pub fn get(&self, path: &[u8]) -> Option<&IndexEntry> {
+        self.entries.iter().find(|entry| entry.path == path && entry.stage == 0)
+    }
+
+    /// Ajoute ou remplace l'entrée du chemin, en résolvant les conflits fichier/répertoire.
+    pub fn add(&mut self, entry: IndexEntry) {
+        let path = entry.path.clone();
+        self.entries.retain(|existing| {
+            // Toutes les étapes du même chemin sont remplacées
+            existing.path != path
+                // Un fichier remplace un répertoire du même nom...
+                && !(existing.path.starts_with(&path) && existing.path.get(path.len()) == Some(&b'/'))
+                // ...et un répertoire remplace un fichier du même nom
+                && !(path.starts_with(&existing.path) && path.get(existing.path.len()) == Some(&b'/'))
+        });
+
+        let position = self.entries
+            .partition_point(|existing| (&existing.path, existing.stage) < (&entry.path, entry.stage));
+        self.entries.insert(position, entry);
+    }
+
+    /// Retire un chemin de l'index, ou tout un répertoire si `recursive`.
+    ///
+    /// # Renvoie
+    /// - Les chemins retirés.
+    pub fn remove(&mut self, path: &[u8], recursive: bool) -> Vec<Vec<u8>> {
+        let mut removed = vec![];
+        self.entries.retain(|entry| {
+            let in_directory = path.is_empty()
+                || (entry.path.starts_with(path) && entry.path.get(path.len()) == Some(&b'/'));
+            let matches = entry.path == path || (recursive && in_directory);
+            if matches && removed.last() != Some(&entry.path) {
+                removed.push(entry.path.clone());
+            }
+            !matches
+        });
+        removed
+    }
+
+    /// Indique si le chemin désigne un répertoire contenant des entrées de l'index.
+    pub fn contains_directory(&self, path: &[u8]) -> bool {
+        self.entries.iter()
+            .any(|entry| path.is_empty() || (entry.path.starts_with(path) && entry.path.get(path.len()) == Some(&b'/')))
+    }
+}
+
+/// Taille d'une entrée, chemin compris, complétée par 1 à 8 NUL jusqu'à un multiple de 8.
+fn padded_entry_size(unpadded: usize) -> usize {
+    (unpadded + 8) & !7
+}
+
+fn read_u32(data: &[u8], position: usize) -> u32 {
+    u32::from_be_bytes(data[position..position + 4].try_into().unwrap())
+}
+
+fn read_u16(data: &[u8], position: usize) -> u16 {
+    u16::from_be_bytes(data[position..position + 2].try_into().unwrap())
+}
@@ -0,0 +1,149 @@
+use std::fs::Metadata;
+
+/// Mode d'un fichier standard.
+pub const MODE_FILE: u32 = 0o100644;
+/// Mode d'un fichier exécutable.
+pub const MODE_EXECUTABLE: u32 = 0o100755;
+/// Mode d'un lien symbolique (le blob contient la cible du lien).
+pub const MODE_SYMLINK: u32 = 0o120000;
+/// Mode d'un sous-module (gitlink vers un commit).
+pub const MODE_GITLINK: u32 = 0o160000;
+
+pub(crate) const FLAG_ASSUME_VALID: u16 = 0x8000;
+pub(crate) const FLAG_EXTENDED: u16 = 0x4000;
+pub(crate) const FLAG_STAGE_MASK: u16 = 0x3000;
+pub(crate) const FLAG_STAGE_SHIFT: u16 = 12;
+pub(crate) const FLAG_NAME_MASK: u16 = 0x0fff;
+
+/// Drapeau étendu (index v3) : l'entrée est hors du sparse checkout.
+pub const EXTENDED_FLAG_SKIP_WORKTREE: u16 = 0x4000;
+/// Drapeau étendu (index v3) : le fichier a été ajouté avec `add -N`.
+pub const EXTENDED_FLAG_INTENT_TO_ADD: u16 = 0x2000;
+
+/// Détermine le mode git d'un fichier à partir de ses métadonnées (sans suivre les liens).
+#[cfg(unix)]
+pub fn mode_from_metadata(metadata: &Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+
+    if metadata.file_type().is_symlink() {
+        MODE_SYMLINK
+    } else if metadata.permissions().mode() & 0o111 != 0 {
+        MODE_EXECUTABLE
+    } else {
+        MODE_FILE
+    }
+}
+
+#[cfg(not(unix))]
+pub fn mode_from_metadata(metadata: &Metadata) -> u32 {
+    if metadata.file_type().is_symlink() {
+        MODE_SYMLINK
+    } else {
+        MODE_FILE
+    }
+}
+
+/// Une entrée de l'index : chemin, hash du blob et données `stat` du fichier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub ctime_seconds: u32,
+    pub ctime_nanoseconds: u32,
+    pub mtime_seconds: u32,
+    pub mtime_nanoseconds: u32,
+    pub dev: u32,
+    pub ino: u32,
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub size: u32,
+    pub hash: String,
+    pub assume_valid: bool,
+    /// Étape de fusion : 0 hors conflit, 1 à 3 pour les versions en conflit.
+    pub stage: u8,
+    pub extended_flags: u16,
+    /// Chemin relatif à la racine du dépôt, séparé par des `/`.
+    pub path: Vec<u8>,
+}
+
+impl IndexEntry {
+    /// Crée une entrée à partir des métadonnées du fichier présent sur le disque.
+    pub fn from_metadata(path: Vec<u8>, hash: String, mode: u32, metadata: &Metadata) -> IndexEntry {
+        let mut entry = IndexEntry {
+            ctime_seconds: 0,
+            ctime_nanoseconds: 0,
+            mtime_seconds: 0,
+            mtime_nanoseconds: 0,
+            dev: 0,
+            ino: 0,
+            mode,
+            uid: 0,
+            gid: 0,
+            size: 0,
+            hash,
+            assume_valid: false,
+            stage: 0,
+            extended_flags: 0,
+            path,
+        };
+        entry.update_stat(metadata);
+        entry
+    }
+
+    /// Met à jour les données `stat` de l'entrée. Les valeurs sont tronquées à 32 bits comme le fait git.
+    #[cfg(unix)]
+    pub fn update_stat(&mut self, metadata: &Metadata) {
+        use std::os::unix::fs::MetadataExt;
+
+        self.ctime_seconds = metadata.ctime() as u32;
+        self.ctime_nanoseconds = metadata.ctime_nsec() as u32;
+        self.mtime_seconds = metadata.mtime() as u32;
+        self.mtime_nanoseconds = metadata.mtime_nsec() as u32;
+        self.dev = metadata.dev() as u32;
+        self.ino = metadata.ino() as u32;
+        self.uid = metadata.uid();
+        self.gid = metadata.gid();
+        self.size = metadata.size() as u32;
+    }
+
+    #[cfg(not(unix))]
+    pub fn update_stat(&mut self, metadata: &Metadata) {
+        use std::time::UNIX_EPOCH;
+
+        if let Ok(modified) = metadata.modified().map(|time| time.duration_since(UNIX_EPOCH).unwrap_or_default()) {
+            self.mtime_seconds = modified.as_secs() as u32;
+            self.mtime_nanoseconds = modified.subsec_nanos();
+        }
+        self.size = metadata.len() as u32;
+    }
+
+    /// Indique si les données `stat` du fichier correspondent à celles enregistrées.
+    pub fn matches_stat(&self, metadata: &Metadata) -> bool {
+        let mut current = self.clone();
+        current.update_stat(metadata);
+        current == *self
+    }
+
+    /// Indique si le fichier a été enregistré dans la même seconde que l'écriture de l'index
+    /// (ou après) : il a pu changer sans que ses données `stat` le montrent et doit être relu.
+    ///
+    /// # Paramètres
+    /// - `index_mtime` : date d'écriture de l'index, voir [`Index::modification_time`].
+    ///
+    /// [`Index::modification_time`]: crate::dircache::index::Index::modification_time
+    pub fn is_racy(&self, index_mtime: Option<(u32, u32)>) -> bool {
+        index_mtime.is_some_and(|mtime| (self.mtime_seconds, self.mtime_nanoseconds) >= mtime)
+    }
+
+    /// Valeur du champ `flags` telle qu'écrite dans l'index.
+    pub(crate) fn flags(&self) -> u16 {
+        let mut flags = (self.path.len().min(FLAG_NAME_MASK as usize)) as u16;
+        flags |= ((self.stage as u16) << FLAG_STAGE_SHIFT) & FLAG_STAGE_MASK;
+        if self.assume_valid {
+            flags |= FLAG_ASSUME_VALID;
+        }
+        if self.extended_flags != 0 {
+            flags |= FLAG_EXTENDED;
+        }
+        flags
+    }
+}
@@ -0,0 +1,320 @@
+use crate::dircache::index::{Index, IndexLock};
+use crate::dircache::index_entry::{self, IndexEntry, EXTENDED_FLAG_INTENT_TO_ADD, MODE_GITLINK, MODE_SYMLINK};
+use crate::fs_reader::{self, FSReader};
+use crate::ignore::ignore_rules::IgnoreRules;
+use crate::objects::object_kind::GitObjectKind;
+use crate::objects::object_manager;
+use crate::refs;
+use crate::repository;
+use crate::status::changes;
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::fs::{self, Metadata};
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+/// Ajoute à l'index le contenu actuel des fichiers désignés (récursivement pour les répertoires).
+///
+/// Les chemins présents dans l'index mais supprimés du disque sont retirés de l'index.
+///
+/// # Paramètres
+/// - `force` : ajoute aussi les fichiers exclus par `.gitignore`.
+///
+/// # Erreurs
+/// - Chemin explicitement désigné, non suivi et exclu, sans `force` : les autres chemins
+///   sont tout de même ajoutés.
+pub fn add_paths(paths: &[PathBuf], force: bool) -> Result<()> {
+    let lock = IndexLock::acquire()?;
+    let mut index = Index::load()?;
+    let repository = repository::current()?;
+    let mut ignore_rules = IgnoreRules::new(&repository)?;
+    let index_mtime = Index::modification_time()?;
+    let mut ignored = vec![];
+
+    for path in paths {
+        let index_path = to_index_path(path)?;
+
+        match fs::symlink_metadata(path) {
+            Ok(metadata) => {
+                let tracked = index.get(&index_path).is_some() || index.contains_directory(&index_path);
+                if !force && !tracked {
+                    if let Some(ignored_path) = find_ignored(&mut ignore_rules, &index_path, metadata.is_dir())? {
+                        ignored.push(ignored_path);
+                        continue;
+                    }
+                }
+
+                let rules = (!force).then_some(&mut ignore_rules);
+                add_fs_entry(&mut index, rules, index_mtime, path, index_path.clone(), &metadata)?;
+
+                if metadata.is_dir() {
+                    // Les fichiers supprimés du répertoire sont aussi pris en compte
+                    let deleted: Vec<Vec<u8>> = index.entries.iter()
+                        .filter(|entry| index_path.is_empty()
+                            || (entry.path.starts_with(&index_path) && entry.path.get(index_path.len()) == Some(&b'/')))
+                        .filter(|entry| fs::symlink_metadata(fs_reader::path_from_bytes(&entry.path)).is_err())
+                        .map(|entry| entry.path.clone())
+                        .collect();
+                    for deleted_path in deleted {
+                        index.remove(&deleted_path, false);
+                    }
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                if index.remove(&index_path, true).is_empty() {
+                    return Err(anyhow!("pathspec '{}' did not match any files", path.display()));
+                }
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    lock.commit(&index)?;
+
+    if !ignored.is_empty() {
+        ignored.sort();
+        ignored.dedup();
+        let paths: Vec<String> = ignored.iter().map(|path| String::from_utf8_lossy(path).into_owned()).collect();
+        return Err(anyhow!(
+            "The following paths are ignored by one of your .gitignore files:\n{}\nhint: Use -f if you really want to add them.",
+            paths.join("\n")
+        ));
+    }
+    Ok(())
+}
+
+/// Cherche si un chemin est exclu, lui-même ou via l'un de ses répertoires parents.
+///
+/// # Renvoie
+/// - Le premier chemin exclu en partant de la racine (le chemin lui-même ou un parent),
+///   ou `None` si le chemin n'est pas exclu.
+fn find_ignored(ignore_rules: &mut IgnoreRules, index_path: &[u8], is_dir: bool) -> Result<Option<Vec<u8>>> {
+    if index_path.is_empty() {
+        return Ok(None);
+    }
+
+    for (position, _) in index_path.iter().enumerate().filter(|(_, &byte)| byte == b'/') {
+        if ignore_rules.is_ignored(&index_path[..position], true)? {
+            return Ok(Some(index_path[..position].to_vec()));
+        }
+    }
+    Ok(ignore_rules.is_ignored(index_path, is_dir)?.then(|| index_path.to_vec()))
+}
+
+/// Retire des chemins de l'index et, sauf avec `cached`, du répertoire de travail.
+///
+/// # Paramètres
+/// - `force` : retire aussi les fichiers dont le contenu serait perdu (voir [`check_removable`]).
+///
+/// # Renvoie
+/// - Les chemins retirés de l'index.
+///
+/// # Erreurs
+/// - Chemin absent de l'index, répertoire sans `recursive`, ou fichier modifié sans `force` :
+///   rien n'est alors retiré.
+pub fn remove_paths(paths: &[PathBuf], cached: bool, recursive: bool, force: bool) -> Result<Vec<String>> {
+    let lock = IndexLock::acquire()?;
+    let mut index = Index::load()?;
+    let original = (!force).then(|| index.clone());
+    let mut removed = vec![];
+
+    for path in paths {
+        let index_path = to_index_path(path)?;
+        if index.get(&index_path).is_none() && index.contains_directory(&index_path) && !recursive {
+            return Err(anyhow!("not removing '{}' recursively without -r", path.display()));
+        }
+
+        let removed_paths = index.remove(&index_path, recursive);
+        if removed_paths.is_empty() {
+            return Err(anyhow!("pathspec '{}' did not match any files", path.display()));
+        }
+        removed.extend(removed_paths);
+    }
+
+    if let Some(mut original) = original {
+        check_removable(&mut original, &removed, cached)?;
+    }
+
+    if !cached {
+        for path in &removed {
+            match fs::remove_file(fs_reader::path_from_bytes(path)) {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+    }
+
+    lock.commit(&index)?;
+    Ok(removed.iter().map(|path| String::from_utf8_lossy(path).into_owned()).collect())
+}
+
+/// Vérifie, comme git, qu'aucun des chemins à retirer n'a de contenu qui serait perdu.
+///
+/// Sans `cached`, le fichier ne doit différer ni de l'index ni, dans l'index, de HEAD ;
+/// avec `cached`, il doit correspondre à l'un des deux. Un fichier déjà supprimé du
+/// répertoire de travail peut toujours être retiré.
+fn check_removable(index: &mut Index, paths: &[Vec<u8>], cached: bool) -> Result<()> {
+    let (worktree_changes, _) = changes::diff_index_to_worktree(index)?;
+    let local: HashSet<&[u8]> = worktree_changes.iter()
+        .filter(|change| change.new.is_some())
+        .map(|change| change.path.as_slice())
+        .collect();
+
+    let head_tree = match refs::resolve_ref(refs::HEAD)? {
+        Some(head) => Some(object_manager::read_commit_tree(&head)?),
+        None => None,
+    };
+    let index_changes = changes::diff_tree_to_index(head_tree.as_deref(), index)?;
+    // Un fichier ajouté avec `add -N` n'est pas dans HEAD : il compte comme indexé
+    let intent_to_add: HashSet<&[u8]> = index.entries.iter()
+        .filter(|entry| entry.stage == 0 && entry.extended_flags & EXTENDED_FLAG_INTENT_TO_ADD != 0)
+        .map(|entry| entry.path.as_slice())
+        .collect();
+    let staged: HashSet<&[u8]> = index_changes.iter()
+        .filter(|change| change.new.is_some())
+        .map(|change| change.path.as_slice())
+        .chain(intent_to_add.iter().copied())
+        .collect();
+
+    let (mut staged_and_local, mut staged_only, mut local_only) = (vec![], vec![], vec![]);
+    for path in paths {
+        let path = path.as_slice();
+        match (staged.contains(path), local.contains(path)) {
+            (true, true) if !cached || !intent_to_add.contains(path) => staged_and_local.push(path),
+            (true, false) if !cached => staged_only.push(path),
+            (false, true) if !cached => local_only.push(path),
+            _ => {}
+        }
+    }
+
+    let messages: Vec<String> = [
+        (staged_and_local, "staged content different from both the\nfile and the HEAD", "(use -f to force removal)"),
+        (staged_only, "changes staged in the index", "(use --cached to keep the file, or -f to force removal)"),
+        (local_only, "local modifications", "(use --cached to keep the file, or -f to force removal)"),
+    ].into_iter()
+        .filter(|(paths, _, _)| !paths.is_empty())
+        .map(|(paths, problem, hint)| {
+            let subject = if paths.len() == 1 { "file has" } else { "files have" };
+            let list: String = paths.iter().map(|path| format!("    {}\n", String::from_utf8_lossy(path))).collect();
+            format!("the following {} {}:\n{}{}", subject, problem, list, hint)
+        })
+        .collect();
+    if messages.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("{}", messages.join("\n")))
+    }
+}
+
+/// Remplace l'index par les fichiers d'un arbre qui vient d'être extrait dans le répertoire
+/// de travail, leurs données `stat` étant lues sur le disque.
+///
+/// Contrairement à [`add_paths`], les fichiers exclus par `.gitignore` sont aussi indexés :
+/// ils font partie de l'arbre.
+pub fn reset_index(tree_hash: &str) -> Result<()> {
+    let lock = IndexLock::acquire()?;
+    // Les chemins de l'arbre sont déjà triés dans l'ordre de l'index
+    let entries = changes::read_tree_files(tree_hash)?.into_iter()
+        .map(|(path, version)| {
+            let metadata = fs::symlink_metadata(fs_reader::path_from_bytes(&path))?;
+            Ok(IndexEntry::from_metadata(path, version.hash, version.mode, &metadata))
+        })
+        .collect::<Result<_>>()?;
+    lock.commit(&Index { version: 2, entries })
+}
+
+/// Écrit les arbres correspondant à l'index.
+///
+/// En l'absence de fichier d'index, c'est l'arbre vide qui est écrit, comme dans git.
+pub fn write_tree() -> Result<GitObjectKind> {
+    object_manager::create_tree_from_index(&Index::load()?)
+}
+
+/// Ajoute à l'index un fichier ou, récursivement, le contenu d'un répertoire.
+///
+/// # Paramètres
+/// - `ignore_rules` : règles d'exclusion appliquées aux chemins non suivis, ou `None` pour
+///   tout ajouter.
+/// - `index_mtime` : date d'écriture de l'index, pour détecter les entrées dont les données
+///   `stat` ne suffisent pas à garantir que le contenu n'a pas changé.
+fn add_fs_entry(
+    index: &mut Index,
+    mut ignore_rules: Option<&mut IgnoreRules>,
+    index_mtime: Option<(u32, u32)>,
+    fs_path: &Path,
+    index_path: Vec<u8>,
+    metadata: &Metadata,
+) -> Result<()> {
+    // Un dépôt imbriqué est enregistré comme gitlink vers le commit de son HEAD
+    if metadata.is_dir() && !index_path.is_empty() && fs_path.join(repository::DOT_GIT).exists() {
+        let hash = refs::resolve_nested_head(fs_path)?
+            .ok_or_else(|| anyhow!("'{}' does not have a commit checked out", fs_path.display()))?;
+        index.add(IndexEntry::from_metadata(index_path, hash, MODE_GITLINK, metadata));
+        return Ok(());
+    }
+
+    if metadata.is_dir() {
+        for child in FSReader.read_directory(fs_path)? {
+            let Some(name) = child.file_name() else { continue };
+            if name == repository::DOT_GIT {
+                continue;
+            }
+
+            let mut child_index_path = index_path.clone();
+            if !child_index_path.is_empty() {
+                child_index_path.push(b'/');
+            }
+            child_index_path.extend_from_slice(&fs_reader::os_str_bytes(name));
+
+            let child_metadata = fs::symlink_metadata(&child)?;
+            // Les chemins exclus ne sont ajoutés que s'ils sont déjà suivis
+            if let Some(rules) = ignore_rules.as_deref_mut() {
+                let tracked = index.get(&child_index_path).is_some() || index.contains_directory(&child_index_path);
+                if !tracked && rules.is_ignored(&child_index_path, child_metadata.is_dir())? {
+                    continue;
+                }
+            }
+            add_fs_entry(index, ignore_rules.as_deref_mut(), index_mtime, &child, child_index_path, &child_metadata)?;
+        }
+        return Ok(());
+    }
+
+    let mode = index_entry::mode_from_metadata(metadata);
+
+    // Fichier inchangé depuis le dernier ajout : inutile de le hacher à nouveau, sauf si
+    // l'entrée est trop récente pour que ses données `stat` soient fiables
+    if let Some(entry) = index.get(&index_path) {
+        if entry.mode == mode && entry.matches_stat(metadata) && !entry.is_racy(index_mtime) {
+            return Ok(());
+        }
+    }
+
+    let hash = if mode == MODE_SYMLINK {
+        object_manager::write_blob(FSReader.read_link(fs_path)?)?
+    } else {
+        object_manager::write_blob_file(fs_path)?
+    };
+
+    index.add(IndexEntry::from_metadata(index_path, hash, mode, metadata));
+    Ok(())
+}
+
+/// Convertit un chemin relatif à la racine du dépôt en chemin d'index (`a/b/c`, vide pour la racine).
+pub(crate) fn to_index_path(path: &Path) -> Result<Vec<u8>> {
+    let mut index_path = Vec::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => continue,
+            Component::Normal(name) => {
+                if !index_path.is_empty() {
+                    index_path.push(b'/');
+                }
+                index_path.extend_from_slice(&fs_reader::os_str_bytes(name));
+            }
+            _ => return Err(anyhow!("'{}' is outside repository", path.display())),
+        }
+    }
+
+    Ok(index_path)
+}
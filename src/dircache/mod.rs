@@ -0,0 +1,3 @@
+pub mod index;
+pub mod index_entry;
+pub mod index_manager;
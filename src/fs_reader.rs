@@ -1,3 +1,4 @@
+use std::fs::File;
 use std::path::{Path, PathBuf};
 use anyhow::Result;
 
@@ -8,6 +9,16 @@ impl FSReader {
         std::fs::read(file_path).map_err(|e| anyhow::anyhow!(e))
     }
 
+    /// Ouvre un fichier pour le lire en flux.
+    ///
+    /// # Renvoie
+    /// - Le fichier ouvert et sa taille.
+    pub fn open_file(&self, file_path: &Path) -> Result<(File, u64)> {
+        let file = File::open(file_path)?;
+        let size = file.metadata()?.len();
+        Ok((file, size))
+    }
+
     pub fn read_directory(&self, dir_path: &Path) -> Result<Vec<PathBuf>> {
         let mut entries = vec![];
 
@@ -18,4 +29,59 @@ impl FSReader {
 
         Ok(entries)
     }
+
+    /// Lit la cible d'un lien symbolique, sous forme d'octets bruts.
+    pub fn read_link(&self, link_path: &Path) -> Result<Vec<u8>> {
+        Ok(os_str_bytes(std::fs::read_link(link_path)?.as_os_str()))
+    }
+}
+
+/// Octets bruts d'un nom de fichier (UTF-8 avec perte hors Unix).
+pub(crate) fn os_str_bytes(name: &std::ffi::OsStr) -> Vec<u8> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        name.as_bytes().to_vec()
+    }
+    #[cfg(not(unix))]
+    {
+        name.to_string_lossy().into_owned().into_bytes()
+    }
+}
+
+/// Chemin correspondant à des octets bruts, inverse de [`os_str_bytes`].
+pub(crate) fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+    }
+    #[cfg(not(unix))]
+    {
+        PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+/// Chemin relatif au répertoire `prefix`, avec des `..` pour remonter, comme l'affiche git.
+pub(crate) fn relative_path(path: &[u8], prefix: &[u8]) -> Vec<u8> {
+    let mut remaining = path;
+    let mut depth = 0;
+    for component in prefix.split(|&c| c == b'/').filter(|component| !component.is_empty()) {
+        let inside = depth == 0
+            && remaining.starts_with(component)
+            && matches!(remaining.get(component.len()), Some(&b'/') | None);
+        if inside {
+            // Le chemin peut être le répertoire lui-même ou l'un de ses parents
+            remaining = remaining.get(component.len() + 1..).unwrap_or_default();
+        } else {
+            depth += 1;
+        }
+    }
+
+    let mut relative = b"../".repeat(depth);
+    relative.extend_from_slice(remaining);
+    if relative.is_empty() {
+        relative.extend_from_slice(b"./");
+    }
+    relative
 }
@@ -0,0 +1,84 @@
+use crate::dircache::index::Index;
+use crate::dircache::index_manager;
+use crate::ignore::ignore_rules::IgnoreRules;
+use crate::quote::quote_path;
+use crate::repository::{self, Repository};
+use anyhow::Result;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::rc::Rc;
+
+/// Options de la commande `check-ignore`.
+#[derive(Debug, Default)]
+pub struct CheckIgnoreOptions {
+    /// Affiche le motif qui correspond (fichier, ligne et motif), y compris un motif `!`.
+    pub verbose: bool,
+    /// Affiche aussi les chemins qui ne correspondent à aucun motif (avec `verbose`).
+    pub non_matching: bool,
+    /// Sépare les champs et termine les lignes par un octet nul, sans guillemets.
+    pub null_terminated: bool,
+    /// N'affiche rien : seul le code de retour compte.
+    pub quiet: bool,
+    /// Vérifie aussi les chemins suivis dans l'index, normalement jamais exclus.
+    pub no_index: bool,
+}
+
+/// Vérifie chemin par chemin les règles d'exclusion, au format de `git check-ignore`.
+pub struct IgnoreChecker {
+    repository: Rc<Repository>,
+    rules: IgnoreRules,
+    index: Option<Index>,
+    options: CheckIgnoreOptions,
+}
+
+impl IgnoreChecker {
+    pub fn new(options: CheckIgnoreOptions) -> Result<IgnoreChecker> {
+        let repository = repository::current()?;
+        let index = if options.no_index { None } else { Some(Index::load()?) };
+        Ok(IgnoreChecker {
+            rules: IgnoreRules::new(&repository)?,
+            repository,
+            index,
+            options,
+        })
+    }
+
+    /// Vérifie un chemin et affiche le résultat.
+    ///
+    /// # Paramètres
+    /// - `path` : chemin tel que donné par l'utilisateur, relatif à son répertoire courant.
+    /// - `out` : destination de la sortie.
+    ///
+    /// # Renvoie
+    /// - `true` si le chemin est exclu, ou correspond à un motif en mode `verbose`.
+    pub fn check<W: Write>(&mut self, path: &str, out: &mut W) -> Result<bool> {
+        let fs_path = self.repository.work_tree_path(Path::new(path))?;
+        let index_path = index_manager::to_index_path(&fs_path)?;
+        if self.index.as_ref().is_some_and(|index| index.get(&index_path).is_some()) {
+            // Un fichier suivi n'est jamais exclu
+            return Ok(false);
+        }
+
+        let is_dir = fs::symlink_metadata(&fs_path).is_ok_and(|metadata| metadata.is_dir());
+        let pattern = self.rules.matching_pattern(&index_path, is_dir)?
+            .filter(|pattern| self.options.verbose || !pattern.negated);
+
+        if self.options.quiet || (pattern.is_none() && !self.options.non_matching) {
+            return Ok(pattern.is_some());
+        }
+
+        let (source, line_number, text) = match &pattern {
+            Some(pattern) => (pattern.source.as_str(), pattern.line_number.to_string(), pattern.text.as_str()),
+            None => ("", String::new(), ""),
+        };
+        match (self.options.verbose, self.options.null_terminated) {
+            (false, false) => writeln!(out, "{}", quote_path(path.as_bytes()))?,
+            (false, true) => write!(out, "{}\0", path)?,
+            (true, false) => writeln!(out, "{}:{}:{}\t{}", quote_path(source.as_bytes()), line_number, text, quote_path(path.as_bytes()))?,
+            (true, true) => write!(out, "{}\0{}\0{}\0{}\0", source, line_number, text, path)?,
+        }
+
+        Ok(pattern.is_some())
+    }
+}
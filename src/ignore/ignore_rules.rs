@@ -0,0 +1,145 @@
+use crate::config::config_set::ConfigSet;
+use crate::ignore::pattern::IgnorePattern;
+use crate::repository::Repository;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Nom des fichiers d'exclusion propres à chaque répertoire.
+pub const IGNORE_FILE: &str = ".gitignore";
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// Règles d'exclusion d'un répertoire de travail.
+///
+/// Par priorité décroissante : les `.gitignore` du plus profond au plus proche de la racine,
+/// `.git/info/exclude`, puis le fichier `core.excludesFile`. Dans un même fichier,
+/// la dernière ligne qui correspond l'emporte.
+pub struct IgnoreRules {
+    work_tree: PathBuf,
+    /// Motifs de `core.excludesFile` puis de `info/exclude`, dans l'ordre de lecture.
+    global: Vec<IgnorePattern>,
+    /// Motifs des `.gitignore` déjà lus, par répertoire relatif à la racine.
+    directories: HashMap<Vec<u8>, Vec<IgnorePattern>>,
+}
+
+impl IgnoreRules {
+    /// Charge les règles globales ; les `.gitignore` sont lus à la demande.
+    pub fn new(repository: &Repository) -> Result<IgnoreRules> {
+        let work_tree = repository.work_tree()?;
+        let mut global = vec![];
+        if let Some(excludes_file) = excludes_file()? {
+            global.extend(read_patterns(&excludes_file, b"", &excludes_file.to_string_lossy())?);
+        }
+        let info_exclude = repository.path("info").join("exclude");
+        // Affiché comme git, relativement à la racine quand le répertoire git s'y trouve
+        let source = info_exclude.strip_prefix(work_tree).unwrap_or(&info_exclude);
+        global.extend(read_patterns(&info_exclude, b"", &source.to_string_lossy())?);
+
+        Ok(IgnoreRules {
+            work_tree: work_tree.to_path_buf(),
+            global,
+            directories: HashMap::new(),
+        })
+    }
+
+    /// Indique si un chemin est exclu.
+    ///
+    /// # Paramètres
+    /// - `path` : chemin relatif à la racine du dépôt (`a/b/c`).
+    /// - `is_dir` : vrai si le chemin est un répertoire.
+    pub fn is_ignored(&mut self, path: &[u8], is_dir: bool) -> Result<bool> {
+        Ok(self.matching_pattern(path, is_dir)?.is_some_and(|pattern| !pattern.negated))
+    }
+
+    /// Cherche le motif qui décide du sort d'un chemin, comme `git check-ignore`.
+    ///
+    /// Comme git ne parcourt pas un répertoire exclu, un chemin qui s'y trouve est décidé
+    /// par le motif de ce répertoire.
+    ///
+    /// # Renvoie
+    /// - Le dernier motif qui correspond, éventuellement un motif `!`, ou `None`.
+    pub fn matching_pattern(&mut self, path: &[u8], is_dir: bool) -> Result<Option<IgnorePattern>> {
+        for (separator, _) in path.iter().enumerate().filter(|(_, &c)| c == b'/') {
+            let directory = &path[..separator];
+            if let Some(pattern) = self.last_match(directory, true)? {
+                if !pattern.negated {
+                    return Ok(Some(pattern));
+                }
+            }
+        }
+        self.last_match(path, is_dir)
+    }
+
+    /// Dernier motif qui correspond au chemin lui-même, sans regarder ses répertoires parents.
+    fn last_match(&mut self, path: &[u8], is_dir: bool) -> Result<Option<IgnorePattern>> {
+        // Répertoires dont le `.gitignore` s'applique au chemin, du plus profond à la racine
+        let mut directories = vec![];
+        let mut end = path.len();
+        while let Some(separator) = path[..end].iter().rposition(|&c| c == b'/') {
+            directories.push(&path[..separator]);
+            end = separator;
+        }
+        directories.push(b"");
+
+        for directory in directories {
+            let patterns = self.directory_patterns(directory)?;
+            if let Some(pattern) = patterns.iter().rev().find(|pattern| pattern.matches(path, is_dir)) {
+                return Ok(Some(pattern.clone()));
+            }
+        }
+
+        Ok(self.global.iter().rev().find(|pattern| pattern.matches(path, is_dir)).cloned())
+    }
+
+    fn directory_patterns(&mut self, directory: &[u8]) -> Result<&[IgnorePattern]> {
+        if !self.directories.contains_key(directory) {
+            let mut relative = String::from_utf8_lossy(directory).into_owned();
+            if !relative.is_empty() {
+                relative.push('/');
+            }
+            relative.push_str(IGNORE_FILE);
+
+            let patterns = read_patterns(&self.work_tree.join(&relative), directory, &relative)?;
+            self.directories.insert(directory.to_vec(), patterns);
+        }
+        Ok(&self.directories[directory])
+    }
+}
+
+/// Fichier désigné par `core.excludesFile`, ou à défaut `$XDG_CONFIG_HOME/git/ignore`.
+fn excludes_file() -> Result<Option<PathBuf>> {
+    if let Some(path) = ConfigSet::load()?.get_path("core.excludesFile")? {
+        return Ok(Some(path));
+    }
+
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    Ok(match std::env::var_os("XDG_CONFIG_HOME").filter(|value| !value.is_empty()) {
+        Some(config_home) => Some(PathBuf::from(config_home).join("git").join("ignore")),
+        None => home.map(|home| home.join(".config").join("git").join("ignore")),
+    })
+}
+
+/// Lit les motifs d'un fichier d'exclusion ; un fichier absent n'en contient aucun.
+///
+/// # Paramètres
+/// - `path` : chemin du fichier.
+/// - `base` : répertoire auquel les motifs sont relatifs.
+/// - `source` : nom du fichier affiché par `check-ignore -v`.
+fn read_patterns(path: &Path, base: &[u8], source: &str) -> Result<Vec<IgnorePattern>> {
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+    let content = content.strip_prefix(UTF8_BOM).unwrap_or(&content);
+
+    Ok(content.split(|&c| c == b'\n')
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            IgnorePattern::parse(line, base, source, i + 1)
+        })
+        .collect())
+}
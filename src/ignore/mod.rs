@@ -0,0 +1,4 @@
+pub mod ignore_manager;
+pub mod ignore_rules;
+pub mod pattern;
+pub mod wildmatch;
@@ -0,0 +1,117 @@
+use crate::ignore::wildmatch::wildmatch;
+
+/// Une ligne de motif d'un fichier d'exclusion (`.gitignore`, `info/exclude`...).
+#[derive(Debug, Clone)]
+pub struct IgnorePattern {
+    /// Ligne telle qu'écrite dans le fichier, sans les espaces finaux.
+    pub text: String,
+    /// Fichier d'où provient le motif, tel qu'affiché par `check-ignore -v`.
+    pub source: String,
+    /// Numéro de ligne dans ce fichier, à partir de 1.
+    pub line_number: usize,
+    /// Motif préfixé par `!` : ré-inclut les chemins qu'il désigne.
+    pub negated: bool,
+    /// Motif terminé par `/` : ne désigne que des répertoires.
+    directory_only: bool,
+    /// Motif contenant un `/` : relatif au répertoire du fichier, et non à n'importe quel niveau.
+    anchored: bool,
+    /// Motif à confronter, sans `!`, `/` initial ni `/` final.
+    glob: Vec<u8>,
+    /// Répertoire du fichier d'exclusion, relatif à la racine (`a/b`, vide pour la racine).
+    base: Vec<u8>,
+}
+
+impl IgnorePattern {
+    /// Analyse une ligne d'un fichier d'exclusion.
+    ///
+    /// # Paramètres
+    /// - `line` : la ligne, sans son saut de ligne.
+    /// - `base` : répertoire du fichier, relatif à la racine du dépôt.
+    /// - `source` / `line_number` : origine du motif, pour `check-ignore -v`.
+    ///
+    /// # Renvoie
+    /// - `None` pour une ligne vide ou un commentaire.
+    pub fn parse(line: &[u8], base: &[u8], source: &str, line_number: usize) -> Option<IgnorePattern> {
+        let line = trim_trailing_spaces(line);
+        if line.is_empty() || line[0] == b'#' {
+            return None;
+        }
+
+        let negated = line[0] == b'!';
+        let mut glob = if negated { &line[1..] } else { line };
+
+        let directory_only = glob.ends_with(b"/");
+        if directory_only {
+            glob = &glob[..glob.len() - 1];
+        }
+        let anchored = glob.contains(&b'/');
+        if let Some(stripped) = glob.strip_prefix(b"/") {
+            glob = stripped;
+        }
+        if glob.is_empty() {
+            return None;
+        }
+
+        Some(IgnorePattern {
+            text: String::from_utf8_lossy(line).into_owned(),
+            source: source.to_string(),
+            line_number,
+            negated,
+            directory_only,
+            anchored,
+            glob: glob.to_vec(),
+            base: base.to_vec(),
+        })
+    }
+
+    /// Indique si le motif désigne un chemin, sans tenir compte de `!`.
+    ///
+    /// # Paramètres
+    /// - `path` : chemin relatif à la racine du dépôt (`a/b/c`).
+    /// - `is_dir` : vrai si le chemin est un répertoire.
+    pub fn matches(&self, path: &[u8], is_dir: bool) -> bool {
+        if self.directory_only && !is_dir {
+            return false;
+        }
+
+        if !self.anchored {
+            // Sans `/`, le motif porte sur le dernier composant, à n'importe quelle profondeur
+            let name = match path.iter().rposition(|&c| c == b'/') {
+                Some(separator) => &path[separator + 1..],
+                None => path,
+            };
+            return wildmatch(&self.glob, name, false);
+        }
+
+        // Avec un `/`, le motif est relatif au répertoire du fichier qui le contient
+        let relative = if self.base.is_empty() {
+            path
+        } else if path.starts_with(&self.base) && path.get(self.base.len()) == Some(&b'/') {
+            &path[self.base.len() + 1..]
+        } else {
+            return false;
+        };
+        wildmatch(&self.glob, relative, true)
+    }
+}
+
+/// Retire les espaces de fin de ligne, sauf ceux échappés par `\`.
+fn trim_trailing_spaces(line: &[u8]) -> &[u8] {
+    let mut trailing_start = None;
+    let mut i = 0;
+    while i < line.len() {
+        match line[i] {
+            b' ' => {
+                trailing_start.get_or_insert(i);
+            }
+            b'\\' => {
+                // Le caractère échappé n'est jamais retiré
+                i += 1;
+                trailing_start = None;
+            }
+            _ => trailing_start = None,
+        }
+        i += 1;
+    }
+    &line[..trailing_start.unwrap_or(line.len())]
+}
@@ -0,0 +1,214 @@
+/// Résultat intermédiaire du filtrage, comme dans `wildmatch.c` de git.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WildResult {
+    Match,
+    NoMatch,
+    /// Le texte est épuisé : inutile d'essayer d'autres positions pour les `*` précédents.
+    AbortAll,
+    /// Un `*` a buté sur un `/` : seul un `**` englobant peut encore réussir.
+    AbortToStarStar,
+}
+
+/// Teste un nom contre un motif glob à la manière de git (`wildmatch`).
+///
+/// # Paramètres
+/// - `pattern` : motif avec `*`, `?`, `[...]` (classes `[:alpha:]`...) et échappements `\`.
+/// - `text` : chemin ou nom à tester.
+/// - `pathname` : si vrai, `*`, `?` et `[...]` ne correspondent pas à `/`, et `**`
+///   entre deux `/` (ou en début ou fin de motif) traverse les répertoires.
+pub fn wildmatch(pattern: &[u8], text: &[u8], pathname: bool) -> bool {
+    dowild(pattern, text, pathname) == WildResult::Match
+}
+
+/// Caractère à une position, `0` au-delà de la fin (les noms ne contiennent jamais d'octet nul).
+fn at(bytes: &[u8], index: usize) -> u8 {
+    bytes.get(index).copied().unwrap_or(0)
+}
+
+fn dowild(pattern: &[u8], text: &[u8], pathname: bool) -> WildResult {
+    let mut p = 0;
+    let mut t = 0;
+
+    while p < pattern.len() {
+        let p_ch = pattern[p];
+        let t_ch = at(text, t);
+        if t_ch == 0 && p_ch != b'*' {
+            return WildResult::AbortAll;
+        }
+
+        match p_ch {
+            b'\\' => {
+                // Le caractère échappé est comparé littéralement
+                p += 1;
+                if at(pattern, p) != t_ch {
+                    return WildResult::NoMatch;
+                }
+            }
+            b'?' => {
+                if pathname && t_ch == b'/' {
+                    return WildResult::NoMatch;
+                }
+            }
+            b'*' => {
+                let star = p;
+                p += 1;
+                let match_slash = if at(pattern, p) == b'*' {
+                    while at(pattern, p) == b'*' {
+                        p += 1;
+                    }
+                    if !pathname {
+                        true
+                    } else if (star == 0 || pattern[star - 1] == b'/') && matches!(at(pattern, p), 0 | b'/') {
+                        // `**/` peut aussi ne correspondre à aucun répertoire
+                        if at(pattern, p) == b'/' && dowild(&pattern[p + 1..], &text[t..], pathname) == WildResult::Match {
+                            return WildResult::Match;
+                        }
+                        true
+                    } else {
+                        // Un `**` collé à d'autres caractères se comporte comme `*`
+                        false
+                    }
+                } else {
+                    !pathname
+                };
+
+                if p == pattern.len() {
+                    // `*` final : tout le reste convient, sauf un `/` si `*` ne le traverse pas
+                    if !match_slash && text[t..].contains(&b'/') {
+                        return WildResult::NoMatch;
+                    }
+                    return WildResult::Match;
+                }
+                if !match_slash && pattern[p] == b'/' {
+                    // `*/` : le `*` s'arrête forcément au prochain `/` du texte
+                    match text[t..].iter().position(|&c| c == b'/') {
+                        Some(offset) => t += offset,
+                        None => return WildResult::NoMatch,
+                    }
+                } else {
+                    while t < text.len() {
+                        match dowild(&pattern[p..], &text[t..], pathname) {
+                            WildResult::NoMatch => {
+                                if !match_slash && text[t] == b'/' {
+                                    return WildResult::AbortToStarStar;
+                                }
+                            }
+                            WildResult::AbortToStarStar if match_slash => {}
+                            result => return result,
+                        }
+                        t += 1;
+                    }
+                    return WildResult::AbortAll;
+                }
+            }
+            b'[' => match match_class(pattern, &mut p, t_ch, pathname) {
+                Some(true) => {}
+                Some(false) => return WildResult::NoMatch,
+                None => return WildResult::AbortAll,
+            },
+            _ => {
+                if p_ch != t_ch {
+                    return WildResult::NoMatch;
+                }
+            }
+        }
+
+        p += 1;
+        t += 1;
+    }
+
+    if t < text.len() {
+        WildResult::NoMatch
+    } else {
+        WildResult::Match
+    }
+}
+
+/// Teste un caractère contre la classe `[...]` qui commence à `*p` et avance `*p` sur le `]` final.
+///
+/// # Renvoie
+/// - `None` si la classe est mal formée : le motif ne peut alors correspondre à rien.
+fn match_class(pattern: &[u8], p: &mut usize, t_ch: u8, pathname: bool) -> Option<bool> {
+    *p += 1;
+    let mut p_ch = at(pattern, *p);
+    if p_ch == b'^' {
+        p_ch = b'!';
+    }
+    let negated = p_ch == b'!';
+    if negated {
+        *p += 1;
+        p_ch = at(pattern, *p);
+    }
+
+    let mut prev_ch = 0;
+    let mut matched = false;
+    loop {
+        if p_ch == 0 {
+            return None;
+        }
+
+        if p_ch == b'\\' {
+            *p += 1;
+            p_ch = at(pattern, *p);
+            if p_ch == 0 {
+                return None;
+            }
+            matched |= t_ch == p_ch;
+        } else if p_ch == b'-' && prev_ch != 0 && !matches!(at(pattern, *p + 1), 0 | b']') {
+            *p += 1;
+            p_ch = at(pattern, *p);
+            if p_ch == b'\\' {
+                *p += 1;
+                p_ch = at(pattern, *p);
+                if p_ch == 0 {
+                    return None;
+                }
+            }
+            matched |= prev_ch <= t_ch && t_ch <= p_ch;
+            // Une borne de plage ne peut pas démarrer une nouvelle plage
+            p_ch = 0;
+        } else if p_ch == b'[' && at(pattern, *p + 1) == b':' {
+            let start = *p + 2;
+            let length = pattern[start..].iter().position(|&c| c == b']')?;
+            let end = start + length;
+            if length == 0 || pattern[end - 1] != b':' {
+                // Pas de `:]` : le `[` est un caractère ordinaire de la classe
+                matched |= t_ch == b'[';
+            } else {
+                matched |= match_named_class(&pattern[start..end - 1], t_ch)?;
+                *p = end;
+                p_ch = 0;
+            }
+        } else {
+            matched |= t_ch == p_ch;
+        }
+
+        prev_ch = p_ch;
+        *p += 1;
+        p_ch = at(pattern, *p);
+        if p_ch == b']' {
+            break;
+        }
+    }
+
+    Some(matched != negated && !(pathname && t_ch == b'/'))
+}
+
+fn match_named_class(name: &[u8], c: u8) -> Option<bool> {
+    let matched = match name {
+        b"alnum" => c.is_ascii_alphanumeric(),
+        b"alpha" => c.is_ascii_alphabetic(),
+        b"blank" => c == b' ' || c == b'\t',
+        b"cntrl" => c.is_ascii_control(),
+        b"digit" => c.is_ascii_digit(),
+        b"graph" => c.is_ascii_graphic(),
+        b"lower" => c.is_ascii_lowercase(),
+        b"print" => c.is_ascii_graphic() || c == b' ',
+        b"punct" => c.is_ascii_punctuation(),
+        b"space" => c.is_ascii_whitespace() || c == 0x0b,
+        b"upper" => c.is_ascii_uppercase(),
+        b"xdigit" => c.is_ascii_hexdigit(),
+        _ => return None,
+    };
+    Some(matched)
+}
@@ -1,3 +1,15 @@
 pub mod repository;
 pub mod objects;
-mod fs_reader;
\ No newline at end of file
+pub mod clone;
+pub mod dircache;
+pub mod refs;
+pub mod log;
+pub mod rev_parse;
+pub mod ls_tree;
+pub mod ignore;
+pub mod status;
+pub mod config;
+pub mod diff;
+mod fs_reader;
+mod quote;
+mod transport;
\ No newline at end of file
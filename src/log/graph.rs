@@ -0,0 +1,109 @@
+/// Dessin ASCII de l'historique, une colonne par branche en cours d'affichage.
+///
+/// Chaque colonne attend un commit ; les colonnes occupent deux caractères (`| `).
+pub struct Graph {
+    columns: Vec<String>,
+}
+
+/// Préfixes à placer devant les lignes de texte d'un commit.
+pub struct GraphRows {
+    /// Préfixe de la ligne qui sépare ce commit du précédent.
+    pub separator: String,
+    /// Ligne du commit (`*`) suivie des lignes de transition vers ses parents.
+    pub rows: Vec<String>,
+    /// Préfixe des lignes de texte suivantes.
+    pub padding: String,
+}
+
+impl Graph {
+    pub fn new() -> Graph {
+        Graph { columns: vec![] }
+    }
+
+    /// Place un commit dans le graphe et le relie à ses parents.
+    pub fn next_commit(&mut self, hash: &str, parents: &[String]) -> GraphRows {
+        let separator = self.bars();
+        let index = match self.columns.iter().position(|column| column == hash) {
+            Some(index) => index,
+            None => {
+                self.columns.push(hash.to_string());
+                self.columns.len() - 1
+            }
+        };
+
+        // Le commit cède sa colonne à ses parents ; une colonne qui attend un commit
+        // déjà placé plus à gauche fusionne avec lui
+        let mut new_columns: Vec<String> = vec![];
+        for (i, column) in self.columns.iter().enumerate() {
+            let waiting = if i == index { parents } else { std::slice::from_ref(column) };
+            for hash in waiting {
+                if !new_columns.contains(hash) {
+                    new_columns.push(hash.clone());
+                }
+            }
+        }
+
+        // Arêtes (colonne actuelle, colonne cible) entre l'ancienne et la nouvelle disposition
+        let mut edges = vec![];
+        for (i, column) in self.columns.iter().enumerate() {
+            if i != index {
+                edges.push((i, position(&new_columns, column)));
+            }
+        }
+        for parent in parents {
+            edges.push((index, position(&new_columns, parent)));
+        }
+
+        let width = 2 * self.columns.len().max(new_columns.len());
+        let commit_row: String = (0..self.columns.len())
+            .map(|i| if i == index { "* " } else { "| " })
+            .collect();
+        let mut rows = vec![pad(commit_row, width)];
+
+        // Chaque ligne de transition rapproche chaque arête d'une colonne de sa cible
+        while edges.iter().any(|(current, target)| current != target) {
+            let mut row = vec![b' '; width];
+            for (current, target) in edges.iter_mut() {
+                if *target > *current {
+                    row[2 * *current + 1] = b'\\';
+                    *current += 1;
+                } else if *target < *current {
+                    row[2 * *current - 1] = b'/';
+                    *current -= 1;
+                } else {
+                    row[2 * *current] = b'|';
+                }
+            }
+            rows.push(String::from_utf8_lossy(&row).into_owned());
+        }
+
+        self.columns = new_columns;
+
+        GraphRows {
+            separator: pad(separator, width),
+            rows,
+            padding: pad(self.bars(), width),
+        }
+    }
+
+    fn bars(&self) -> String {
+        "| ".repeat(self.columns.len())
+    }
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn position(columns: &[String], hash: &str) -> usize {
+    columns.iter().position(|column| column == hash).unwrap_or_default()
+}
+
+fn pad(mut row: String, width: usize) -> String {
+    while row.len() < width {
+        row.push(' ');
+    }
+    row
+}
@@ -0,0 +1,78 @@
+use crate::log::graph::Graph;
+use crate::log::pretty::{self, LogFormat};
+use crate::log::rev_walk::{self, RevWalk};
+use anyhow::Result;
+use std::io::{self, Write};
+
+/// Options de la commande `log`.
+#[derive(Debug)]
+pub struct LogOptions {
+    pub format: LogFormat,
+    pub max_count: Option<usize>,
+    pub graph: bool,
+    /// Limite l'historique aux commits qui modifient ces chemins.
+    pub paths: Vec<String>,
+}
+
+/// Affiche l'historique depuis les commits donnés.
+///
+/// # Paramètres
+/// - `starts` : hashes des commits de départ.
+/// - `options` : format, limite, graphe et chemins.
+pub fn log(starts: &[String], options: &LogOptions) -> Result<()> {
+    let mut walk = RevWalk::new(starts, &options.paths)?;
+    let max_count = options.max_count.unwrap_or(usize::MAX);
+
+    let mut commits = vec![];
+    if options.graph {
+        // Comme git, le graphe impose l'ordre topologique : tout l'historique est lu avant
+        while let Some(walked) = walk.next_commit()? {
+            commits.push(walked);
+        }
+        commits = rev_walk::sort_topologically(commits);
+        commits.truncate(max_count);
+    } else {
+        while commits.len() < max_count {
+            match walk.next_commit()? {
+                Some(walked) => commits.push(walked),
+                None => break,
+            }
+        }
+    }
+
+    let mut stdout = io::stdout().lock();
+    let mut graph = Graph::new();
+    let terminator = !matches!(options.format, LogFormat::Template { terminator: false, .. });
+
+    for (i, walked) in commits.iter().enumerate() {
+        let lines = pretty::format_commit(&walked.hash, &walked.commit, &options.format)?;
+        let (separator, rows, padding) = if options.graph {
+            let rows = graph.next_commit(&walked.hash, &walked.parents);
+            (rows.separator, rows.rows, rows.padding)
+        } else {
+            (String::new(), vec![], String::new())
+        };
+
+        if i > 0 {
+            if options.format.has_blank_separator() {
+                writeln!(stdout, "{}", separator)?;
+            } else if !terminator {
+                writeln!(stdout)?;
+            }
+        }
+
+        // Le texte suit les lignes du graphe, puis se poursuit sous le préfixe de remplissage
+        let line_count = lines.len().max(rows.len());
+        for j in 0..line_count {
+            let prefix = rows.get(j).unwrap_or(&padding);
+            let text = lines.get(j).map(String::as_str).unwrap_or_default();
+            if terminator || j + 1 < line_count {
+                writeln!(stdout, "{}{}", prefix, text)?;
+            } else {
+                write!(stdout, "{}{}", prefix, text)?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,4 @@
+pub mod graph;
+pub mod log_manager;
+pub mod pretty;
+pub mod rev_walk;
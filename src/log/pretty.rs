@@ -0,0 +1,244 @@
+use crate::objects::commit::commit::Commit;
+use crate::objects::commit::signature::Signature;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+
+/// Longueur des hashes abrégés.
+const ABBREV_LENGTH: usize = 7;
+
+/// Format d'affichage d'un commit, choisi par `--oneline` ou `--format`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogFormat {
+    Oneline,
+    Short,
+    Medium,
+    Full,
+    Fuller,
+    /// Gabarit à placeholders ; `terminator` indique si chaque entrée se termine par
+    /// un saut de ligne (`tformat:`) ou si les entrées en sont séparées (`format:`).
+    Template { template: String, terminator: bool },
+}
+
+impl LogFormat {
+    /// Analyse la valeur de `--format` / `--pretty`.
+    pub fn parse(value: &str) -> Result<LogFormat> {
+        let format = match value {
+            "oneline" => LogFormat::Oneline,
+            "short" => LogFormat::Short,
+            "medium" => LogFormat::Medium,
+            "full" => LogFormat::Full,
+            "fuller" => LogFormat::Fuller,
+            _ => {
+                if let Some(template) = value.strip_prefix("format:") {
+                    LogFormat::Template { template: template.to_string(), terminator: false }
+                } else if let Some(template) = value.strip_prefix("tformat:") {
+                    LogFormat::Template { template: template.to_string(), terminator: true }
+                } else if value.contains('%') {
+                    LogFormat::Template { template: value.to_string(), terminator: true }
+                } else {
+                    return Err(anyhow!("invalid --pretty format: {}", value));
+                }
+            }
+        };
+        Ok(format)
+    }
+
+    /// Indique si les entrées sont séparées par une ligne vide.
+    pub fn has_blank_separator(&self) -> bool {
+        !matches!(self, LogFormat::Oneline | LogFormat::Template { .. })
+    }
+}
+
+/// Met en forme un commit, sans le séparateur entre entrées.
+///
+/// # Renvoie
+/// - Les lignes à afficher pour ce commit.
+pub fn format_commit(hash: &str, commit: &Commit, format: &LogFormat) -> Result<Vec<String>> {
+    let message = String::from_utf8_lossy(&commit.message);
+
+    let lines = match format {
+        LogFormat::Oneline => vec![format!("{} {}", abbreviate(hash), subject(&message))],
+        LogFormat::Template { template, .. } => {
+            expand_template(template, hash, commit, &message)?
+                .split('\n')
+                .map(|line| line.to_string())
+                .collect()
+        }
+        _ => {
+            let author = commit.author()?;
+            let committer = commit.committer()?;
+
+            let mut lines = vec![format!("commit {}", hash)];
+            let parents = commit.parents();
+            if parents.len() > 1 {
+                let parents: Vec<&str> = parents.iter().map(|parent| abbreviate(parent)).collect();
+                lines.push(format!("Merge: {}", parents.join(" ")));
+            }
+
+            match format {
+                LogFormat::Short => lines.push(format!("Author: {}", identity(&author))),
+                LogFormat::Medium => {
+                    lines.push(format!("Author: {}", identity(&author)));
+                    lines.push(format!("Date:   {}", format_date(&author)));
+                }
+                LogFormat::Full => {
+                    lines.push(format!("Author: {}", identity(&author)));
+                    lines.push(format!("Commit: {}", identity(&committer)));
+                }
+                _ => {
+                    lines.push(format!("Author:     {}", identity(&author)));
+                    lines.push(format!("AuthorDate: {}", format_date(&author)));
+                    lines.push(format!("Commit:     {}", identity(&committer)));
+                    lines.push(format!("CommitDate: {}", format_date(&committer)));
+                }
+            }
+
+            lines.push(String::new());
+            if *format == LogFormat::Short {
+                lines.push(format!("    {}", subject(&message)));
+            } else {
+                // Message indenté, sans les lignes vides de début et de fin
+                let message_lines: Vec<&str> = message.lines().collect();
+                let start = message_lines.iter().position(|line| !line.trim().is_empty()).unwrap_or(message_lines.len());
+                let end = message_lines.iter().rposition(|line| !line.trim().is_empty()).map_or(start, |end| end + 1);
+                lines.extend(message_lines[start..end].iter().map(|line| format!("    {}", line)));
+            }
+            lines
+        }
+    };
+
+    Ok(lines)
+}
+
+fn abbreviate(hash: &str) -> &str {
+    &hash[..ABBREV_LENGTH.min(hash.len())]
+}
+
+fn identity(signature: &Signature) -> String {
+    format!("{} <{}>", signature.name, signature.email)
+}
+
+/// Date au format par défaut de git, par exemple `Tue Nov 14 23:13:20 2023 +0100`.
+fn format_date(signature: &Signature) -> String {
+    signature.datetime().format("%a %b %-d %H:%M:%S %Y %z").to_string()
+}
+
+/// Date relative, par exemple `3 days ago`, selon les seuils de git.
+fn format_relative_date(signature: &Signature) -> String {
+    let seconds = Utc::now().timestamp() - signature.timestamp;
+    if seconds < 0 {
+        return "in the future".to_string();
+    }
+
+    let plural = |count: i64, unit: &str| {
+        format!("{} {}{} ago", count, unit, if count == 1 { "" } else { "s" })
+    };
+    let days = (seconds + 43200) / 86400;
+    match seconds {
+        s if s < 90 => plural(s, "second"),
+        s if s < 90 * 60 => plural((s + 30) / 60, "minute"),
+        s if s < 36 * 3600 => plural((s + 1800) / 3600, "hour"),
+        _ if days < 14 => plural(days, "day"),
+        _ if days < 70 => plural((days + 3) / 7, "week"),
+        _ if days < 365 => plural((days + 15) / 30, "month"),
+        _ => plural((days * 2 + 365) / 730, "year"),
+    }
+}
+
+/// Sujet du message : le premier paragraphe, ses lignes jointes par des espaces.
+fn subject(message: &str) -> String {
+    message.lines()
+        .skip_while(|line| line.trim().is_empty())
+        .take_while(|line| !line.trim().is_empty())
+        .map(|line| line.trim())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Corps du message : tout ce qui suit le premier paragraphe.
+fn body(message: &str) -> String {
+    let lines: Vec<&str> = message.lines()
+        .skip_while(|line| line.trim().is_empty())
+        .skip_while(|line| !line.trim().is_empty())
+        .skip_while(|line| line.trim().is_empty())
+        .collect();
+    if lines.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", lines.join("\n"))
+    }
+}
+
+/// Remplace les placeholders d'un gabarit `--format`.
+///
+/// Les placeholders inconnus sont recopiés tels quels, comme le fait git.
+fn expand_template(template: &str, hash: &str, commit: &Commit, message: &str) -> Result<String> {
+    let author = commit.author()?;
+    let committer = commit.committer()?;
+    let parents = commit.parents();
+
+    let mut output = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+
+        let Some(placeholder) = chars.next() else {
+            output.push('%');
+            break;
+        };
+        match placeholder {
+            '%' => output.push('%'),
+            'n' => output.push('\n'),
+            'H' => output.push_str(hash),
+            'h' => output.push_str(abbreviate(hash)),
+            'T' => output.push_str(commit.tree_hash()),
+            't' => output.push_str(abbreviate(commit.tree_hash())),
+            'P' => output.push_str(&parents.join(" ")),
+            'p' => output.push_str(&parents.iter().map(|parent| abbreviate(parent)).collect::<Vec<_>>().join(" ")),
+            's' => output.push_str(&subject(message)),
+            'b' => output.push_str(&body(message)),
+            'B' => output.push_str(message),
+            'a' | 'c' => {
+                let signature = if placeholder == 'a' { &author } else { &committer };
+                match chars.peek().copied() {
+                    Some(field) if expand_signature_field(field, signature, &mut output) => {
+                        chars.next();
+                    }
+                    _ => {
+                        output.push('%');
+                        output.push(placeholder);
+                    }
+                }
+            }
+            other => {
+                output.push('%');
+                output.push(other);
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Ajoute le champ d'une signature désigné par `field` (`%an`, `%ae`, `%ad`...).
+///
+/// # Renvoie
+/// - `false` si le champ est inconnu.
+fn expand_signature_field(field: char, signature: &Signature, output: &mut String) -> bool {
+    let value = match field {
+        'n' => signature.name.clone(),
+        'e' => signature.email.clone(),
+        'd' => format_date(signature),
+        't' => signature.timestamp.to_string(),
+        'r' => format_relative_date(signature),
+        'i' => signature.datetime().format("%Y-%m-%d %H:%M:%S %z").to_string(),
+        'I' => signature.datetime().format("%Y-%m-%dT%H:%M:%S%:z").to_string(),
+        _ => return false,
+    };
+    output.push_str(&value);
+    true
+}
@@ -0,0 +1,216 @@
+use crate::objects::commit::commit::Commit;
+use crate::objects::object_manager;
+use anyhow::Result;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// Commit produit par le parcours, avec ses parents réécrits vers les commits affichés.
+pub struct WalkedCommit {
+    pub hash: String,
+    pub commit: Commit,
+    pub parents: Vec<String>,
+}
+
+/// Ce que le parcours sait d'un commit déjà lu.
+struct CommitInfo {
+    committer_time: i64,
+    /// Parents suivis : tous, ou un seul parent identique quand l'historique est limité à des chemins.
+    followed: Vec<String>,
+    shown: bool,
+}
+
+/// Parcours de l'historique du plus récent au plus ancien, par date de committer.
+///
+/// Avec des chemins, seuls les commits qui les modifient sont renvoyés et, comme git,
+/// une fusion identique à l'un de ses parents sur ces chemins n'est suivie que par ce parent.
+pub struct RevWalk {
+    paths: Vec<String>,
+    /// File de priorité : date de committer la plus récente d'abord, puis ordre d'insertion.
+    queue: BinaryHeap<(i64, Reverse<usize>, String)>,
+    queued: HashSet<String>,
+    inserted: usize,
+    infos: HashMap<String, CommitInfo>,
+    /// Commits lus mais pas encore renvoyés.
+    commits: HashMap<String, Commit>,
+    /// Entrées des chemins suivis dans l'arbre de chaque commit.
+    path_entries: HashMap<String, Vec<Option<String>>>,
+}
+
+impl RevWalk {
+    pub fn new(starts: &[String], paths: &[String]) -> Result<RevWalk> {
+        let mut walk = RevWalk {
+            paths: paths.iter()
+                .map(|path| path.trim_matches('/').to_string())
+                .collect(),
+            queue: BinaryHeap::new(),
+            queued: HashSet::new(),
+            inserted: 0,
+            infos: HashMap::new(),
+            commits: HashMap::new(),
+            path_entries: HashMap::new(),
+        };
+
+        for start in starts {
+            walk.push(start)?;
+        }
+
+        Ok(walk)
+    }
+
+    /// Renvoie le prochain commit à afficher, ou `None` à la fin de l'historique.
+    pub fn next_commit(&mut self) -> Result<Option<WalkedCommit>> {
+        while let Some((_, _, hash)) = self.queue.pop() {
+            let followed = self.info(&hash)?.followed.clone();
+            for parent in &followed {
+                self.push(parent)?;
+            }
+
+            if !self.info(&hash)?.shown {
+                self.commits.remove(&hash);
+                continue;
+            }
+
+            let mut parents = vec![];
+            for parent in &followed {
+                if let Some(parent) = self.rewrite_parent(parent)? {
+                    if !parents.contains(&parent) {
+                        parents.push(parent);
+                    }
+                }
+            }
+
+            let commit = match self.commits.remove(&hash) {
+                Some(commit) => commit,
+                None => object_manager::read_commit(&hash)?,
+            };
+            return Ok(Some(WalkedCommit { hash, commit, parents }));
+        }
+
+        Ok(None)
+    }
+
+    fn push(&mut self, hash: &str) -> Result<()> {
+        if !self.queued.insert(hash.to_string()) {
+            return Ok(());
+        }
+
+        let committer_time = self.info(hash)?.committer_time;
+        self.queue.push((committer_time, Reverse(self.inserted), hash.to_string()));
+        self.inserted += 1;
+
+        Ok(())
+    }
+
+    /// Remonte depuis un parent jusqu'au premier commit affiché, en sautant les commits masqués.
+    fn rewrite_parent(&mut self, hash: &str) -> Result<Option<String>> {
+        let mut current = hash.to_string();
+        loop {
+            let info = self.info(&current)?;
+            if info.shown {
+                return Ok(Some(current));
+            }
+            // Un commit masqué ne suit jamais plus d'un parent
+            match info.followed.first() {
+                Some(parent) => current = parent.clone(),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn info(&mut self, hash: &str) -> Result<&CommitInfo> {
+        if !self.infos.contains_key(hash) {
+            let info = self.load_info(hash)?;
+            self.infos.insert(hash.to_string(), info);
+        }
+        Ok(&self.infos[hash])
+    }
+
+    fn load_info(&mut self, hash: &str) -> Result<CommitInfo> {
+        let commit = object_manager::read_commit(hash)?;
+        let committer_time = commit.committer()?.timestamp;
+        let parents: Vec<String> = commit.parents().iter().map(|parent| parent.to_string()).collect();
+        let tree_hash = commit.tree_hash().to_string();
+        self.commits.insert(hash.to_string(), commit);
+
+        if self.paths.is_empty() {
+            return Ok(CommitInfo { committer_time, followed: parents, shown: true });
+        }
+
+        let entries = self.entries(hash, &tree_hash)?;
+        if parents.is_empty() {
+            let shown = entries.iter().any(|entry| entry.is_some());
+            return Ok(CommitInfo { committer_time, followed: parents, shown });
+        }
+
+        for parent in &parents {
+            let parent_tree = object_manager::read_commit_tree(parent)?;
+            if self.entries(parent, &parent_tree)? == entries {
+                // Identique à ce parent sur les chemins suivis : le commit n'apporte rien
+                return Ok(CommitInfo { committer_time, followed: vec![parent.clone()], shown: false });
+            }
+        }
+
+        Ok(CommitInfo { committer_time, followed: parents, shown: true })
+    }
+
+    fn entries(&mut self, hash: &str, tree_hash: &str) -> Result<Vec<Option<String>>> {
+        if let Some(entries) = self.path_entries.get(hash) {
+            return Ok(entries.clone());
+        }
+
+        let mut entries = vec![];
+        for path in &self.paths {
+            let entry = object_manager::find_tree_entry(tree_hash, path)?;
+            entries.push(entry.map(|entry| format!("{} {}", entry.mode, entry.hash)));
+        }
+        self.path_entries.insert(hash.to_string(), entries.clone());
+
+        Ok(entries)
+    }
+}
+
+/// Réordonne des commits pour qu'aucun n'apparaisse avant l'un de ses enfants.
+///
+/// Comme l'ordre du graphe de git, les branches sont vidées une à une : le dernier
+/// parent libéré est traité en premier.
+pub fn sort_topologically(commits: Vec<WalkedCommit>) -> Vec<WalkedCommit> {
+    let mut indegree: HashMap<String, usize> = commits.iter()
+        .map(|walked| (walked.hash.clone(), 0))
+        .collect();
+    for walked in &commits {
+        for parent in &walked.parents {
+            if let Some(count) = indegree.get_mut(parent) {
+                *count += 1;
+            }
+        }
+    }
+
+    let mut stack: Vec<String> = commits.iter()
+        .filter(|walked| indegree[&walked.hash] == 0)
+        .map(|walked| walked.hash.clone())
+        .collect();
+    // La première tête doit être traitée en premier
+    stack.reverse();
+
+    let mut by_hash: HashMap<String, WalkedCommit> = commits.into_iter()
+        .map(|walked| (walked.hash.clone(), walked))
+        .collect();
+    let mut sorted = vec![];
+
+    while let Some(hash) = stack.pop() {
+        let Some(walked) = by_hash.remove(&hash) else {
+            continue;
+        };
+        for parent in &walked.parents {
+            if let Some(count) = indegree.get_mut(parent) {
+                *count -= 1;
+                if *count == 0 {
+                    stack.push(parent.clone());
+                }
+            }
+        }
+        sorted.push(walked);
+    }
+
+    sorted
+}
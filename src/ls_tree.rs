@@ -0,0 +1,136 @@
+use crate::fs_reader;
+use crate::objects::object_manager;
+use crate::objects::tree::tree::TreeEntry;
+use crate::quote::quote_path;
+use anyhow::Result;
+use std::io::Write;
+
+/// Options de la commande `ls-tree`.
+#[derive(Debug, Default)]
+pub struct LsTreeOptions {
+    /// Descend dans les sous-arbres.
+    pub recursive: bool,
+    /// Affiche aussi les arbres dans lesquels on descend.
+    pub show_trees: bool,
+    /// N'affiche que les arbres.
+    pub trees_only: bool,
+    /// Ajoute la taille des blobs.
+    pub long: bool,
+    /// N'affiche que les chemins.
+    pub name_only: bool,
+    /// Termine les lignes par un octet nul, sans mettre les chemins entre guillemets.
+    pub null_terminated: bool,
+    /// Chemins à afficher, relatifs à la racine (un `/` final pour le contenu d'un dossier) ;
+    /// vide pour tout l'arbre.
+    pub paths: Vec<String>,
+    /// Répertoire de l'utilisateur relatif à la racine (`a/b`) : les chemins sont affichés
+    /// relativement à lui. `None` pour des chemins relatifs à la racine.
+    pub prefix: Option<Vec<u8>>,
+}
+
+/// Liste le contenu d'un arbre au format de `git ls-tree`.
+///
+/// # Paramètres
+/// - `tree_hash` : hash de l'arbre à lister.
+/// - `options` : options d'affichage et chemins à filtrer.
+/// - `out` : destination de la sortie.
+pub fn ls_tree<W: Write>(tree_hash: &str, options: &LsTreeOptions, out: &mut W) -> Result<()> {
+    list_entries(tree_hash, b"", options, out)
+}
+
+fn list_entries<W: Write>(tree_hash: &str, prefix: &[u8], options: &LsTreeOptions, out: &mut W) -> Result<()> {
+    let tree = object_manager::read_tree(tree_hash)?;
+
+    for entry in &tree.entries {
+        let path = [prefix, &entry.name].concat();
+        let is_tree = entry.kind() == "tree";
+        // Avec -d, seuls les blobs sont masqués : arbres et sous-modules restent affichés
+        let is_blob = entry.kind() == "blob";
+
+        let (matched, leading) = match_paths(&path, &options.paths);
+        if is_tree && leading {
+            // Le dossier mène à un chemin demandé : on y descend sans l'afficher, sauf avec -t
+            if options.show_trees {
+                write_entry(&path, entry, options, out)?;
+            }
+            list_entries(&entry.hash, &[&path[..], b"/"].concat(), options, out)?;
+        } else if matched {
+            if is_tree && options.recursive {
+                if options.show_trees || options.trees_only {
+                    write_entry(&path, entry, options, out)?;
+                }
+                list_entries(&entry.hash, &[&path[..], b"/"].concat(), options, out)?;
+            } else if !options.trees_only || !is_blob {
+                write_entry(&path, entry, options, out)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Confronte un chemin aux chemins demandés.
+///
+/// # Renvoie
+/// - Si le chemin est demandé (ou se trouve sous un dossier demandé).
+/// - S'il est un dossier parent d'un chemin demandé (ou un dossier demandé avec un `/` final).
+fn match_paths(path: &[u8], paths: &[String]) -> (bool, bool) {
+    if paths.is_empty() {
+        return (true, false);
+    }
+
+    let mut matched = false;
+    let mut leading = false;
+    for wanted in paths {
+        let trimmed = wanted.trim_start_matches("./").trim_end_matches('/').as_bytes();
+        if trimmed.is_empty() || trimmed == b"." {
+            // "." ou "/" : tout l'arbre
+            matched = true;
+        } else if path == trimmed {
+            if wanted.ends_with('/') {
+                leading = true;
+            } else {
+                matched = true;
+            }
+        } else if is_under(path, trimmed) {
+            matched = true;
+        } else if is_under(trimmed, path) {
+            leading = true;
+        }
+    }
+    (matched, leading)
+}
+
+/// Indique si `path` se trouve sous le dossier `directory`.
+fn is_under(path: &[u8], directory: &[u8]) -> bool {
+    path.starts_with(directory) && path.get(directory.len()) == Some(&b'/')
+}
+
+fn write_entry<W: Write>(path: &[u8], entry: &TreeEntry, options: &LsTreeOptions, out: &mut W) -> Result<()> {
+    let path = match &options.prefix {
+        Some(prefix) => fs_reader::relative_path(path, prefix),
+        None => path.to_vec(),
+    };
+    // Avec -z, le chemin est écrit tel quel, octets compris
+    let name = if options.null_terminated {
+        path
+    } else {
+        quote_path(&path).into_bytes()
+    };
+
+    if !options.name_only {
+        write!(out, "{} {} {}", entry.mode_padded(), entry.kind(), entry.hash)?;
+        if options.long {
+            let size = match entry.kind() {
+                "blob" => object_manager::read_object_size(&entry.hash)?.to_string(),
+                _ => "-".to_string(),
+            };
+            write!(out, " {:>7}", size)?;
+        }
+        out.write_all(b"\t")?;
+    }
+    out.write_all(&name)?;
+
+    out.write_all(if options.null_terminated { b"\0" } else { b"\n" })?;
+    Ok(())
+}
@@ -1,22 +1,22 @@
 use crate::objects::object_base::{GitObject, GitObjectBase};
 use crate::objects::utils;
-use anyhow::{anyhow, Result};
+use anyhow::Result;
+use std::io;
 
 pub(crate) const HEADER_PREFIX: &str = "blob";
 
 #[derive(Debug)]
 pub struct Blob {
     pub base: GitObjectBase,
-    pub content: String,
+    /// Contenu brut du fichier, sans aucune hypothèse d'encodage.
+    pub content: Vec<u8>,
 }
 
 impl Blob {
     pub(crate) fn new(content: Vec<u8>) -> Result<Blob>
     {
-        let size = content.len();
-        let content = String::from_utf8(content).map_err(|e| anyhow!("Invalid UTF-8 in object file: {}", e))?;
-        let blob_data = format!("{} {}{}{}", HEADER_PREFIX, size, "\0", content);
-        let hash = utils::compute_sha1(&blob_data);
+        // Le contenu est haché sur place, sans copie derrière son en-tête
+        let hash = utils::hash_blob_stream(content.as_slice(), content.len() as u64, &mut io::sink())?;
         Ok(Blob {
             base: GitObjectBase {
                 hash,
@@ -37,7 +37,7 @@ impl GitObject for Blob {
     }
 
     fn compute_size(&self) -> usize {
-        self.content.as_bytes().len()
+        self.content.len()
     }
 
     fn compute_object_data(&self) -> Vec<u8> {
@@ -47,18 +47,17 @@ impl GitObject for Blob {
 
         result.extend_from_slice(header);
 
-        result.extend_from_slice(&self.content.as_bytes());
+        result.extend_from_slice(&self.content);
 
         result
     }
 
     fn from_object_file(hash: &str, content: &[u8]) -> Result<Blob> {
-        let content_str = std::str::from_utf8(content)?.to_string();
         Ok(Blob {
             base: GitObjectBase {
                 hash: hash.to_string(),
             },
-            content: content_str,
+            content: content.to_vec(),
         })
     }
 }
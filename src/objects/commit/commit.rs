@@ -1,3 +1,4 @@
+use crate::objects::commit::signature::Signature;
 use crate::objects::object_base::{GitObject, GitObjectBase};
 use crate::objects::tree::tree;
 use crate::objects::utils;
@@ -6,79 +7,119 @@ use anyhow::{anyhow, Result};
 pub(crate) const HEADER_PREFIX: &str = "commit";
 const PARENT_PREFIX: &str = "parent";
 const AUTHOR_PREFIX: &str = "author";
+const COMMITTER_PREFIX: &str = "committer";
+
+/// Un en-tête de commit (`tree`, `parent`, `author`, `gpgsig`...).
+///
+/// Les valeurs sur plusieurs lignes (signatures, `mergetag`) sont stockées avec des `\n`,
+/// sans l'espace qui préfixe chaque ligne de continuation dans l'objet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitHeader {
+    pub key: String,
+    pub value: Vec<u8>,
+}
 
 #[derive(Debug)]
 pub struct Commit {
     pub base: GitObjectBase,
-    pub tree_hash: String,
-    pub parent_hash: Option<String>,
-    pub message: String,
-    pub author: CommitAuthor,
-}
-
-#[derive(Debug)]
-pub struct CommitAuthor {
-    name: String,
-    email: String,
+    /// Tous les en-têtes, dans leur ordre d'origine : l'objet est resérialisé à l'identique.
+    pub headers: Vec<CommitHeader>,
+    /// Message brut, tel que stocké dans l'objet (saut de ligne final compris).
+    pub message: Vec<u8>,
 }
 
 impl Commit {
-    pub(crate) fn new(tree_hash: &String, parent_hash: &Option<String>, message: &String, author: Option<CommitAuthor>) -> Result<Commit> {
-        let author = author.unwrap_or_else(|| CommitAuthor {
-            name: "unpseudo".to_string(),
-            email: "unpseudo@mail.mail".to_string(),
-        });
+    pub(crate) fn new(tree_hash: &str, parents: &[String], message: &str, author: Signature, committer: Signature) -> Result<Commit> {
+        let mut headers = vec![CommitHeader::new(tree::HEADER_PREFIX, tree_hash)];
+        for parent in parents {
+            headers.push(CommitHeader::new(PARENT_PREFIX, parent));
+        }
+        headers.push(CommitHeader::new(AUTHOR_PREFIX, &author.to_string()));
+        headers.push(CommitHeader::new(COMMITTER_PREFIX, &committer.to_string()));
 
-        // Créer un buffer pour stocker les données de l'objet commit
-        let mut content = Vec::new();
+        // Comme git, le message se termine toujours par un saut de ligne
+        let mut message = message.as_bytes().to_vec();
+        if !message.ends_with(b"\n") {
+            message.push(b'\n');
+        }
 
-        content.extend_from_slice(&tree::HEADER_PREFIX.as_bytes());
-        content.push(b' ');
-        content.extend_from_slice(tree_hash.as_bytes());
-        content.push(b'\n');
+        let mut commit = Commit {
+            base: GitObjectBase { hash: String::new() },
+            headers,
+            message,
+        };
 
-        if let Some(parent_hash) = parent_hash {
-            content.extend_from_slice(&PARENT_PREFIX.as_bytes());
-            content.push(b' ');
-            content.extend_from_slice(parent_hash.as_bytes());
-            content.push(b'\n');
-        }
+        // Le hash porte sur l'objet complet, en-tête "commit <taille>\0" compris
+        commit.base.hash = utils::compute_sha1_from_bytes(&commit.compute_object_data());
 
-        content.extend_from_slice(&AUTHOR_PREFIX.as_bytes());
-        content.push(b' ');
-        content.extend_from_slice(author.name.as_bytes());
-        content.push(b' ');
-        content.extend_from_slice(format!("<{}>", author.email).as_bytes());
-        content.push(b'\n');
+        Ok(commit)
+    }
 
-        content.push(b'\n');
-        content.extend_from_slice(message.as_bytes());
-        content.push(b'\n');
+    /// Hash de l'arbre racine du commit.
+    pub fn tree_hash(&self) -> &str {
+        self.header(tree::HEADER_PREFIX)
+            .and_then(|value| std::str::from_utf8(value).ok())
+            .unwrap_or_default()
+    }
 
-        // Taille du contenu de l'objet tree
-        let size = content.len();
+    /// Hashes des parents, dans l'ordre (le premier est le parent principal d'une fusion).
+    pub fn parents(&self) -> Vec<&str> {
+        self.headers.iter()
+            .filter(|header| header.key == PARENT_PREFIX)
+            .filter_map(|header| std::str::from_utf8(&header.value).ok())
+            .collect()
+    }
 
-        // Créer l'en-tête du blob avec le préfixe "tree" et la taille
-        let mut blob_data = Vec::new();
-        blob_data.extend_from_slice(format!("tree {}\0", size).as_bytes());
+    pub fn author(&self) -> Result<Signature> {
+        self.signature(AUTHOR_PREFIX)
+    }
 
-        // Ajouter les données des entrées
-        blob_data.extend_from_slice(&content);
+    pub fn committer(&self) -> Result<Signature> {
+        self.signature(COMMITTER_PREFIX)
+    }
 
-        // Calculer le hash SHA-1 pour les données complètes de l'objet tree
-        let hash = utils::compute_sha1_from_bytes(&blob_data);
+    /// Valeur du premier en-tête portant ce nom.
+    pub fn header(&self, key: &str) -> Option<&[u8]> {
+        self.headers.iter()
+            .find(|header| header.key == key)
+            .map(|header| header.value.as_slice())
+    }
 
-        Ok(Commit {
-            base: GitObjectBase { hash },
-            tree_hash: tree_hash.to_owned(),
-            parent_hash: parent_hash.to_owned(),
-            message: message.to_owned(),
-            author,
-        })
+    fn signature(&self, key: &str) -> Result<Signature> {
+        let value = self.header(key).ok_or_else(|| anyhow!("Invalid commit object: missing {}", key))?;
+        Signature::parse(&String::from_utf8_lossy(value))
+    }
+
+    /// Sérialise le contenu de l'objet commit, sans l'en-tête.
+    pub(crate) fn compute_content(&self) -> Vec<u8> {
+        let mut content = Vec::new();
+
+        for header in &self.headers {
+            content.extend_from_slice(header.key.as_bytes());
+            content.push(b' ');
+            // Chaque ligne de continuation est préfixée d'un espace
+            for (i, line) in header.value.split(|&b| b == b'\n').enumerate() {
+                if i > 0 {
+                    content.extend_from_slice(b"\n ");
+                }
+                content.extend_from_slice(line);
+            }
+            content.push(b'\n');
+        }
+
+        // Ligne vide entre les en-têtes et le message
+        content.push(b'\n');
+        content.extend_from_slice(&self.message);
+
+        content
     }
 }
 
-impl Commit {}
+impl CommitHeader {
+    fn new(key: &str, value: &str) -> CommitHeader {
+        CommitHeader { key: key.to_string(), value: value.as_bytes().to_vec() }
+    }
+}
 
 impl GitObject for Commit {
     fn get_hash(&self) -> &str {
@@ -90,52 +131,16 @@ impl GitObject for Commit {
     }
 
     fn compute_size(&self) -> usize {
-        format!("{} ", tree::HEADER_PREFIX).len()       // Size of tree prefix + ' ' separator
-            + 10                                        // Size of tree hash
-            + 1                                         // Size of '\n'
-            + if self.parent_hash.is_some() {
-            format!("{} ", PARENT_PREFIX).len()     // Size of parent commit prefix + ' ' separator
-                + 10                                // Size of parent commit hash
-                + 1                                  // Size of '\n'
-        } else { 0 }
-            + format!("{} ", AUTHOR_PREFIX).len()       // Size of author prefix + ' ' separator
-            + self.author.name.len()                    // Size of author name
-            + format!(" <{}>", self.author.email).len() // Size of author email with separators
-            + 1                                          // Size of '\n'
-            + 1                                          // Size of '\n' for the blank line between commit infos and commit message
-            + self.message.len()
+        self.compute_content().len()
     }
 
     fn compute_object_data(&self) -> Vec<u8> {
-        let header_str = self.get_header();
-        let header = header_str.as_bytes();
-
-        let mut result = Vec::with_capacity(header.len() + self.compute_size());
-
-        result.extend_from_slice(&header);
-
-        result.extend_from_slice(&tree::HEADER_PREFIX.as_bytes());
-        result.push(b' ');
-        result.extend_from_slice(&self.tree_hash.as_bytes());
-        result.push(b'\n');
-
-        if let Some(parent_hash) = &self.parent_hash {
-            result.extend_from_slice(&PARENT_PREFIX.as_bytes());
-            result.push(b' ');
-            result.extend_from_slice(parent_hash.as_bytes());
-            result.push(b'\n');
-        }
-
-        result.extend_from_slice(&AUTHOR_PREFIX.as_bytes());
-        result.push(b' ');
-        result.extend_from_slice(&self.author.name.as_bytes());
-        result.push(b' ');
-        result.extend_from_slice(format!("<{}>", &self.author.email).as_bytes());
-        result.push(b'\n');
+        let content = self.compute_content();
+        let header = format!("{} {}\0", HEADER_PREFIX, content.len());
 
-        result.push(b'\n');
-        result.extend_from_slice(self.message.as_bytes());
-        result.push(b'\n');
+        let mut result = Vec::with_capacity(header.len() + content.len());
+        result.extend_from_slice(header.as_bytes());
+        result.extend_from_slice(&content);
 
         result
     }
@@ -144,51 +149,58 @@ impl GitObject for Commit {
     where
         Self: Sized,
     {
-        let mut tree_hash = String::new();
-        let mut parent_hash: Option<String> = None;
-        let mut message = String::new();
-        let mut author = CommitAuthor {
-            name: String::new(),
-            email: String::new(),
-        };
+        let mut headers: Vec<CommitHeader> = Vec::new();
+        let mut message = Vec::new();
         let mut idx = 0;
 
         while idx < content.len() {
             let line_end = content[idx..].iter()
                 .position(|&c| c == b'\n')
-                .ok_or_else(|| anyhow!("Invalid commit object: missing line end"))?;
-
-            let line = std::str::from_utf8(&content[idx..idx + line_end])?;
+                .map(|position| idx + position)
+                .unwrap_or(content.len());
+            let line = &content[idx..line_end];
+            idx = line_end + 1; // Avancer à la ligne suivante
 
             if line.is_empty() {
                 // Passage aux données du message après la première ligne vide
-                idx += line_end + 1;
-                message = std::str::from_utf8(&content[idx..])?.to_owned();
+                message = content.get(idx..).unwrap_or_default().to_vec();
                 break;
+            }
+
+            if let Some(continuation) = line.strip_prefix(b" ") {
+                // Ligne de continuation de l'en-tête précédent (gpgsig, mergetag...)
+                let previous = headers.last_mut()
+                    .ok_or_else(|| anyhow!("Invalid commit object: continuation line without header"))?;
+                previous.value.push(b'\n');
+                previous.value.extend_from_slice(continuation);
             } else {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                match parts.as_slice() {
-                    // Traitement des différentes parties d'un commit Git
-                    [tree::HEADER_PREFIX, tree_hash_value] => tree_hash = tree_hash_value.to_string(),
-                    [PARENT_PREFIX, parent_hash_value] => parent_hash = Some(parent_hash_value.to_string()),
-                    [AUTHOR_PREFIX, author_name, author_email, ..] => {
-                        author.name = author_name.to_string();
-                        author.email = author_email.to_string();
-                    }
-                    _ => return Err(anyhow!("Unknown commit object field: {}", line)),
-                }
-                idx += line_end + 1; // Avancer à la ligne suivante
+                let separator = line.iter().position(|&b| b == b' ').unwrap_or(line.len());
+                headers.push(CommitHeader {
+                    key: String::from_utf8(line[..separator].to_vec())
+                        .map_err(|_| anyhow!("Invalid commit object: non UTF-8 header name"))?,
+                    value: line.get(separator + 1..).unwrap_or_default().to_vec(),
+                });
             }
         }
 
-        Ok(Commit {
+        let commit = Commit {
             base: GitObjectBase {
                 hash: hash.to_string(),
             },
-            tree_hash,
-            parent_hash,
+            headers,
             message,
-            author,
-        })
+        };
+
+        // Validation minimale : un arbre et des parents désignés par des hashes valides
+        let is_hash = |value: &str| value.len() == 40 && value.chars().all(|c| c.is_ascii_hexdigit());
+        if commit.headers.first().map(|header| header.key.as_str()) != Some(tree::HEADER_PREFIX) || !is_hash(commit.tree_hash()) {
+            return Err(anyhow!("Invalid commit object {}: missing or invalid tree", hash));
+        }
+        let parent_count = commit.headers.iter().filter(|header| header.key == PARENT_PREFIX).count();
+        if commit.parents().len() != parent_count || !commit.parents().iter().all(|parent| is_hash(parent)) {
+            return Err(anyhow!("Invalid commit object {}: invalid parent", hash));
+        }
+
+        Ok(commit)
     }
-}
\ No newline at end of file
+}
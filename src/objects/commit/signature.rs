@@ -0,0 +1,181 @@
+use crate::config::config_set::ConfigSet;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, Offset, TimeZone};
+use std::fmt;
+
+/// Identité et date d'un auteur, d'un committer ou d'un tagger :
+/// `Nom <email> <secondes depuis epoch> <+hhmm>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub name: String,
+    pub email: String,
+    pub timestamp: i64,
+    /// Décalage du fuseau horaire, en minutes par rapport à UTC.
+    pub tz_offset_minutes: i32,
+}
+
+/// Rôle d'une signature, qui détermine les variables d'environnement consultées.
+#[derive(Debug, Clone, Copy)]
+pub enum SignatureRole {
+    Author,
+    Committer,
+}
+
+impl SignatureRole {
+    fn env_prefix(&self) -> &'static str {
+        match self {
+            SignatureRole::Author => "GIT_AUTHOR",
+            SignatureRole::Committer => "GIT_COMMITTER",
+        }
+    }
+}
+
+impl Signature {
+    /// Construit la signature de l'utilisateur courant pour le rôle donné.
+    ///
+    /// L'identité provient de `GIT_<ROLE>_NAME` / `GIT_<ROLE>_EMAIL`, à défaut de
+    /// `user.name` / `user.email` dans la configuration, et la date de `GIT_<ROLE>_DATE`
+    /// ou de l'heure courante.
+    pub fn from_environment(role: SignatureRole) -> Result<Signature> {
+        let prefix = role.env_prefix();
+        let config = ConfigSet::load()?;
+
+        let name = non_empty_env(&format!("{}_NAME", prefix))
+            .or(config.get_string("user.name")?)
+            .or_else(|| non_empty_env("USER"))
+            .or_else(|| non_empty_env("LOGNAME"))
+            .ok_or_else(|| unknown_identity_error(role))?;
+
+        let email = non_empty_env(&format!("{}_EMAIL", prefix))
+            .or(config.get_string("user.email")?)
+            .or_else(|| non_empty_env("EMAIL"))
+            .or_else(|| default_email(&name))
+            .ok_or_else(|| unknown_identity_error(role))?;
+
+        let (timestamp, tz_offset_minutes) = match non_empty_env(&format!("{}_DATE", prefix)) {
+            Some(date) => parse_date(&date)?,
+            None => {
+                let now = Local::now();
+                (now.timestamp(), now.offset().fix().local_minus_utc() / 60)
+            }
+        };
+
+        Ok(Signature { name, email, timestamp, tz_offset_minutes })
+    }
+
+    /// Analyse une signature au format `Nom <email> <timestamp> <+hhmm>`.
+    pub fn parse(value: &str) -> Result<Signature> {
+        let email_start = value.find('<').ok_or_else(|| anyhow!("Invalid signature: {}", value))?;
+        let email_end = value.rfind('>').filter(|&end| end > email_start)
+            .ok_or_else(|| anyhow!("Invalid signature: {}", value))?;
+
+        let name = value[..email_start].trim_end().to_string();
+        let email = value[email_start + 1..email_end].to_string();
+
+        let mut date = value[email_end + 1..].split_whitespace();
+        let timestamp = date.next()
+            .and_then(|timestamp| timestamp.parse().ok())
+            .ok_or_else(|| anyhow!("Invalid signature date: {}", value))?;
+        let tz_offset_minutes = date.next()
+            .map(parse_tz_offset)
+            .transpose()?
+            .unwrap_or(0);
+
+        Ok(Signature { name, email, timestamp, tz_offset_minutes })
+    }
+
+    /// Fuseau horaire au format git : `+hhmm` ou `-hhmm`.
+    pub fn format_tz_offset(&self) -> String {
+        let sign = if self.tz_offset_minutes < 0 { '-' } else { '+' };
+        let minutes = self.tz_offset_minutes.abs();
+        format!("{}{:02}{:02}", sign, minutes / 60, minutes % 60)
+    }
+
+    /// Date de la signature dans son propre fuseau horaire.
+    pub fn datetime(&self) -> DateTime<FixedOffset> {
+        let offset = FixedOffset::east_opt(self.tz_offset_minutes * 60)
+            .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+        offset.timestamp_opt(self.timestamp, 0)
+            .single()
+            .unwrap_or_else(|| offset.timestamp_opt(0, 0).unwrap())
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} <{}> {} {}", self.name, self.email, self.timestamp, self.format_tz_offset())
+    }
+}
+
+/// Analyse une date fournie par l'utilisateur : `<epoch> <+hhmm>`, `@<epoch>`,
+/// RFC 2822 ou ISO 8601 (`2024-01-31T12:00:00+01:00`, `2024-01-31 12:00:00 +0100`).
+pub fn parse_date(value: &str) -> Result<(i64, i32)> {
+    let value = value.trim();
+    let raw = value.strip_prefix('@').unwrap_or(value);
+
+    let mut parts = raw.split_whitespace();
+    if let Some(Ok(timestamp)) = parts.next().map(str::parse::<i64>) {
+        let tz_offset = parts.next().map(parse_tz_offset).transpose()?.unwrap_or(0);
+        return Ok((timestamp, tz_offset));
+    }
+
+    let parsed = DateTime::parse_from_rfc2822(value)
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S %z"))
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%z"));
+    if let Ok(date) = parsed {
+        return Ok((date.timestamp(), date.offset().local_minus_utc() / 60));
+    }
+
+    // Date sans fuseau : interprétée dans le fuseau local
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
+            if let Some(date) = Local.from_local_datetime(&naive).earliest() {
+                return Ok((date.timestamp(), date.offset().fix().local_minus_utc() / 60));
+            }
+        }
+    }
+
+    Err(anyhow!("Invalid date format: {}", value))
+}
+
+/// Analyse un fuseau `+hhmm` / `-hhmm` et le convertit en minutes.
+fn parse_tz_offset(value: &str) -> Result<i32> {
+    let invalid = || anyhow!("Invalid timezone offset: {}", value);
+    let (sign, digits) = match value.as_bytes().first() {
+        Some(b'+') => (1, &value[1..]),
+        Some(b'-') => (-1, &value[1..]),
+        _ => return Err(invalid()),
+    };
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let hours: i32 = digits[..2].parse()?;
+    let minutes: i32 = digits[2..].parse()?;
+    Ok(sign * (hours * 60 + minutes))
+}
+
+fn non_empty_env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Email déduit de l'utilisateur et du nom d'hôte, comme le fait git en dernier recours.
+fn default_email(name: &str) -> Option<String> {
+    let user = non_empty_env("USER").or_else(|| non_empty_env("LOGNAME")).unwrap_or_else(|| name.to_string());
+    let host = non_empty_env("HOSTNAME")
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok().map(|host| host.trim().to_string()))
+        .filter(|host| !host.is_empty())?;
+    Some(format!("{}@{}", user, host))
+}
+
+fn unknown_identity_error(role: SignatureRole) -> anyhow::Error {
+    let role = match role {
+        SignatureRole::Author => "Author",
+        SignatureRole::Committer => "Committer",
+    };
+    anyhow!(
+        "{} identity unknown\n\n*** Please tell me who you are.\n\nRun\n\n  \
+         git config --global user.email \"you@example.com\"\n  git config --global user.name \"Your Name\"",
+        role
+    )
+}
@@ -6,5 +6,6 @@ pub mod tree;
 pub mod object_kind;
 mod object_writer;
 mod objet_reader;
-mod object_transformer;
-mod commit;
+pub mod commit;
+pub mod tag;
+mod pack;
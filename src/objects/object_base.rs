@@ -1,6 +1,6 @@
-use std::path::PathBuf;
-use crate::objects::object_manager;
-use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use crate::objects::utils;
+use anyhow::Result;
 
 #[derive(Debug)]
 pub struct GitObjectBase {
@@ -13,14 +13,8 @@ pub trait GitObject {
     fn get_header(&self) -> String {
         format!("{} {}\0", self.get_header_prefix(), self.compute_size())
     }
-    fn compute_file_path(&self) -> Result<PathBuf> {
-        if self.get_hash().len() != object_manager::OBJECT_HASH_SIZE {
-            return Err(anyhow!("Invalid object identifier: expected {} characters, got {}", object_manager::OBJECT_HASH_SIZE, self.get_hash().len()));
-        }
-
-        // Sépare le hash en deux parties : le répertoire (les deux premiers caractères) et le fichier
-        let (dir, file) = self.get_hash().split_at(2);
-        Ok(PathBuf::from(object_manager::GIT_OBJECTS_DIR).join(dir).join(file))
+    fn compute_file_path(&self, objects_dir: &Path) -> Result<PathBuf> {
+        utils::hash_to_object_path(objects_dir, self.get_hash())
     }
     fn compute_size(&self) -> usize;
     fn compute_object_data(&self) -> Vec<u8>;
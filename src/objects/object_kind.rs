@@ -3,30 +3,14 @@ use crate::objects::object_base::GitObject;
 use crate::objects::tree::tree::Tree;
 use anyhow::Result;
 use crate::objects::commit::commit::Commit;
+use crate::objects::tag::tag::Tag;
 
 #[derive(Debug)]
 pub enum GitObjectKind {
     Blob(Blob),
     Tree(Tree),
     Commit(Commit),
-}
-
-impl GitObjectKind {
-    pub fn get_data(&self) -> Result<String> {
-        let data = match self {
-            GitObjectKind::Blob(blob) => blob.content.clone(),
-            GitObjectKind::Tree(tree) => {
-                let entries: Vec<String> = tree.entries.iter()
-                    .map(|entry| format!("{}", entry.name))
-                    .collect();
-                entries.join("\n")
-            },
-            GitObjectKind::Commit(commit) => {
-                todo!()
-            }
-        };
-        Ok(data)
-    }
+    Tag(Tag),
 }
 
 impl GitObject for GitObjectKind {
@@ -35,6 +19,7 @@ impl GitObject for GitObjectKind {
             GitObjectKind::Blob(blob) => blob.get_hash(),
             GitObjectKind::Tree(tree) => tree.get_hash(),
             GitObjectKind::Commit(commit) => commit.get_hash(),
+            GitObjectKind::Tag(tag) => tag.get_hash(),
         }
     }
 
@@ -43,6 +28,7 @@ impl GitObject for GitObjectKind {
             GitObjectKind::Blob(blob) => blob.get_header_prefix(),
             GitObjectKind::Tree(tree) => tree.get_header_prefix(),
             GitObjectKind::Commit(commit) => commit.get_header_prefix(),
+            GitObjectKind::Tag(tag) => tag.get_header_prefix(),
         }
     }
 
@@ -51,6 +37,7 @@ impl GitObject for GitObjectKind {
             GitObjectKind::Blob(blob) => blob.compute_size(),
             GitObjectKind::Tree(tree) => tree.compute_size(),
             GitObjectKind::Commit(commit) => commit.compute_size(),
+            GitObjectKind::Tag(tag) => tag.compute_size(),
         }
     }
 
@@ -59,6 +46,7 @@ impl GitObject for GitObjectKind {
             GitObjectKind::Blob(blob) => blob.compute_object_data(),
             GitObjectKind::Tree(tree) => tree.compute_object_data(),
             GitObjectKind::Commit(commit) => commit.compute_object_data(),
+            GitObjectKind::Tag(tag) => tag.compute_object_data(),
         }
     }
 
@@ -1,37 +1,574 @@
-use crate::fs_reader::FSReader;
+use crate::config::config_set::ConfigSet;
+use crate::dircache::index::Index;
+use crate::dircache::index_entry::{self, IndexEntry};
+use crate::fs_reader::{self, FSReader};
+use crate::objects::blob::blob::Blob;
 use crate::objects::object_kind::GitObjectKind;
-use crate::objects::object_transformer::GitObjectTransformer;
 use crate::objects::object_writer::GitObjectWriter;
 use crate::objects::objet_reader::GitObjectReader;
-use anyhow::Result;
-use std::path::Path;
+use crate::objects::pack::pack_index::PackIndex;
+use crate::objects::pack::pack_indexer::PackIndexer;
+use crate::objects::pack::pack_writer::{PackInput, PackWriter};
+use crate::objects::tree::tree::{Tree, TreeEntry};
+use crate::objects::object_base::GitObject;
+use crate::objects::utils;
+use crate::refs;
+use crate::repository;
+use anyhow::{anyhow, Result};
+use flate2::Compression;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::rc::Rc;
+use std::path::{Path, PathBuf};
 use crate::objects::commit::commit::Commit;
+use crate::objects::commit::signature::{Signature, SignatureRole};
+use crate::objects::tag::tag::Tag;
 
 pub(crate) const OBJECT_CONTENT_SEPARATOR: u8 = 0;
 pub(crate) const OBJECT_HASH_SIZE: usize = 40;
-pub(crate) const GIT_OBJECTS_DIR: &str = ".git/objects";
+/// Fichiers qui accompagnent un pack et disparaissent avec lui.
+const PACK_EXTENSIONS: [&str; 4] = ["pack", "idx", "rev", "bitmap"];
 
-pub fn read_object(hash: &str) -> Result<GitObjectKind> {
-    let reader = GitObjectReader;
-    reader.read_object(hash)
+thread_local! {
+    /// Lecteur partagé, pour ne charger les index de packs qu'une fois par processus.
+    static READER: RefCell<Option<Rc<GitObjectReader>>> = const { RefCell::new(None) };
+    /// Écrivain partagé, pour ne lire le niveau de compression dans la configuration qu'une fois.
+    static WRITER: RefCell<Option<Rc<GitObjectWriter>>> = const { RefCell::new(None) };
 }
 
-pub fn create_object(path: &Path) -> Result<GitObjectKind> {
-    let reader = FSReader;
-    let transformer = GitObjectTransformer::new(&reader);
-    let writer = GitObjectWriter;
+/// Résultat d'un `gc` : pack produit et nombre d'objets loose supprimés.
+#[derive(Debug)]
+pub struct GcSummary {
+    pub pack_name: Option<String>,
+    pub object_count: usize,
+    pub pruned_count: usize,
+}
 
-    let object = transformer.transform_fs_to_object(path)?;
-    writer.write_object(&object)?;
+/// Lecteur des objets du dépôt courant.
+fn reader() -> Result<Rc<GitObjectReader>> {
+    let objects_dir = repository::current()?.objects_dir();
+    Ok(READER.with(|reader| {
+        let mut reader = reader.borrow_mut();
+        // Le dépôt courant a pu changer depuis le chargement (clone)
+        if reader.as_ref().is_some_and(|reader| reader.objects_dir() != objects_dir) {
+            *reader = None;
+        }
+        reader.get_or_insert_with(|| Rc::new(GitObjectReader::new(&objects_dir))).clone()
+    }))
+}
 
-    Ok(object)
+/// Écrivain des objets du dépôt courant.
+fn writer() -> Result<Rc<GitObjectWriter>> {
+    let objects_dir = repository::current()?.objects_dir();
+    WRITER.with(|writer| {
+        let mut writer = writer.borrow_mut();
+        // Le dépôt courant a pu changer depuis la création (clone)
+        if writer.as_ref().is_some_and(|writer| writer.objects_dir() != objects_dir) {
+            *writer = None;
+        }
+        if let Some(writer) = writer.as_ref() {
+            return Ok(writer.clone());
+        }
+        let compression = compression_level(&["core.looseCompression", "core.compression"])?;
+        Ok(writer.insert(Rc::new(GitObjectWriter::new(&objects_dir, compression))).clone())
+    })
 }
 
-pub fn create_commit(tree_hash: &String, parent_hash: &Option<String>, message: &String) -> Result<GitObjectKind> {
-    let writer = GitObjectWriter;
+/// Niveau de compression de la première clé définie parmi `keys`, comme dans git :
+/// de 0 à 9, ou -1 pour le niveau par défaut de zlib.
+fn compression_level(keys: &[&str]) -> Result<Compression> {
+    let config = ConfigSet::load()?;
+    for key in keys {
+        if let Some(level) = config.get_int(key)? {
+            return match level {
+                -1 => Ok(Compression::default()),
+                0..=9 => Ok(Compression::new(level as u32)),
+                _ => Err(anyhow!("bad zlib compression level {}", level)),
+            };
+        }
+    }
+    Ok(Compression::default())
+}
+
+/// Oublie les packs chargés par le lecteur partagé, après en avoir ajouté ou supprimé.
+fn reset_reader() {
+    READER.with(|reader| reader.borrow_mut().take());
+}
+
+/// Indique si un objet est présent dans le dépôt.
+pub fn has_object(hash: &str) -> Result<bool> {
+    reader()?.has_object(hash)
+}
+
+/// Renvoie les hashes des objets du dépôt qui commencent par le préfixe donné.
+pub fn find_objects_by_prefix(prefix: &str) -> Result<Vec<String>> {
+    reader()?.find_objects_by_prefix(prefix)
+}
+
+/// Renvoie le type d'un objet (`blob`, `tree`, `commit` ou `tag`) sans l'analyser.
+pub fn read_object_type(hash: &str) -> Result<String> {
+    Ok(read_object_header(hash)?.0)
+}
+
+/// Lit le type et la taille d'un objet sans décompresser son contenu (sauf delta packé).
+pub fn read_object_header(hash: &str) -> Result<(String, u64)> {
+    let stream = reader()?.open_object_stream(hash)?;
+    Ok((stream.kind, stream.size))
+}
+
+/// Recopie le contenu d'un objet dans `out` par blocs, sans le charger entièrement en mémoire.
+///
+/// # Renvoie
+/// - Le type de l'objet.
+///
+/// # Erreurs
+/// - Retourne une erreur si l'objet est tronqué.
+pub fn copy_object_content<W: Write>(hash: &str, out: &mut W) -> Result<String> {
+    let mut stream = reader()?.open_object_stream(hash)?;
+    let copied = io::copy(&mut stream.content, out)?;
+    if copied != stream.size {
+        return Err(anyhow!("Object {} is truncated: expected {} bytes, got {}", hash, stream.size, copied));
+    }
+    Ok(stream.kind)
+}
+
+/// Lit le type et le contenu brut d'un objet, sans l'analyser.
+pub fn read_raw_object(hash: &str) -> Result<(String, Vec<u8>)> {
+    let object = reader()?.read_raw_object(hash)?;
+    Ok((object.kind, object.content))
+}
+
+/// Renvoie les hashes de tous les objets du dépôt, loose et packés, triés et sans doublons.
+pub fn list_objects() -> Result<Vec<String>> {
+    reader()?.list_objects()
+}
+
+/// Renvoie la taille du contenu d'un objet.
+pub fn read_object_size(hash: &str) -> Result<u64> {
+    Ok(read_object_header(hash)?.1)
+}
+
+/// Lit et analyse un arbre.
+pub fn read_tree(tree_hash: &str) -> Result<Tree> {
+    let object = reader()?.read_raw_object(tree_hash)?;
+    if object.kind != "tree" {
+        return Err(anyhow!("Object {} is a {}, not a tree", tree_hash, object.kind));
+    }
+    Tree::from_object_file(tree_hash, &object.content)
+}
 
-    let commit = GitObjectKind::Commit(Commit::new(tree_hash, parent_hash, message, None)?);
+pub fn read_object(hash: &str) -> Result<GitObjectKind> {
+    let reader = reader()?;
+    reader.read_object(hash)
+}
+
+pub fn create_commit(tree_hash: &str, parents: &[String], message: &str) -> Result<GitObjectKind> {
+    let writer = writer()?;
+
+    let author = Signature::from_environment(SignatureRole::Author)?;
+    let committer = Signature::from_environment(SignatureRole::Committer)?;
+
+    let commit = GitObjectKind::Commit(Commit::new(tree_hash, parents, message, author, committer)?);
     writer.write_object(&commit)?;
 
     Ok(commit)
-}
\ No newline at end of file
+}
+
+/// Crée un tag annoté nommé `name` pointant vers l'objet donné.
+pub fn create_tag(object_hash: &str, name: &str, message: &str) -> Result<GitObjectKind> {
+    let writer = writer()?;
+
+    let object_type = reader()?.read_raw_object(object_hash)?.kind;
+    // Comme git, le tagger est l'identité du committer
+    let tagger = Signature::from_environment(SignatureRole::Committer)?;
+
+    let tag = GitObjectKind::Tag(Tag::new(object_hash, &object_type, name, message, tagger)?);
+    writer.write_object(&tag)?;
+
+    Ok(tag)
+}
+
+/// Écrit un blob dans le dépôt s'il n'y est pas déjà.
+///
+/// # Renvoie
+/// - Le hash du blob.
+pub fn write_blob(content: Vec<u8>) -> Result<String> {
+    let blob = GitObjectKind::Blob(Blob::new(content)?);
+    writer()?.write_object(&blob)?;
+    Ok(blob.get_hash().to_string())
+}
+
+/// Écrit le contenu d'un fichier comme blob, en flux.
+///
+/// # Renvoie
+/// - Le hash du blob.
+pub fn write_blob_file(path: &Path) -> Result<String> {
+    let (file, size) = FSReader.open_file(path)?;
+    writer()?.write_blob_stream(file, size)
+}
+
+/// Calcule le hash d'un fichier vu comme blob, en flux, et l'écrit dans le dépôt si demandé.
+///
+/// # Paramètres
+/// - `path` : fichier à hacher.
+/// - `write` : écrit aussi l'objet dans le dépôt.
+pub fn hash_file(path: &Path, write: bool) -> Result<String> {
+    if write {
+        return write_blob_file(path);
+    }
+    let (file, size) = FSReader.open_file(path)?;
+    utils::hash_blob_stream(file, size, &mut io::sink())
+}
+
+/// Construit et écrit les arbres correspondant aux entrées de l'index.
+///
+/// Les blobs sont supposés déjà présents, ils ont été écrits lors de l'ajout à l'index.
+pub fn create_tree_from_index(index: &Index) -> Result<GitObjectKind> {
+    if index.entries.iter().any(|entry| entry.stage != 0) {
+        return Err(anyhow!("error building trees: index contains unmerged entries"));
+    }
+
+    let reader = reader()?;
+    let entries: Vec<(&[u8], &IndexEntry)> = index.entries.iter()
+        .map(|entry| (entry.path.as_slice(), entry))
+        .collect();
+
+    Ok(GitObjectKind::Tree(build_index_tree(&reader, &entries)?))
+}
+
+/// Construit récursivement l'arbre d'un répertoire de l'index et l'écrit s'il est absent.
+///
+/// Les entrées sont triées : celles d'un même sous-répertoire sont donc contiguës.
+fn build_index_tree(reader: &GitObjectReader, entries: &[(&[u8], &IndexEntry)]) -> Result<Tree> {
+    let mut tree_entries = vec![];
+    let mut idx = 0;
+
+    while idx < entries.len() {
+        let (path, entry) = entries[idx];
+        match path.iter().position(|&b| b == b'/') {
+            None => {
+                tree_entries.push(TreeEntry {
+                    mode: format!("{:o}", entry.mode),
+                    name: path.to_vec(),
+                    hash: entry.hash.clone(),
+                    object: None,
+                });
+                idx += 1;
+            }
+            Some(separator) => {
+                let directory = &path[..=separator];
+                let children: Vec<(&[u8], &IndexEntry)> = entries[idx..].iter()
+                    .take_while(|(child_path, _)| child_path.starts_with(directory))
+                    .map(|(child_path, child)| (&child_path[directory.len()..], *child))
+                    .collect();
+                idx += children.len();
+
+                let subtree = build_index_tree(reader, &children)?;
+                tree_entries.push(TreeEntry {
+                    mode: "40000".to_string(),
+                    name: path[..separator].to_vec(),
+                    hash: subtree.get_hash().to_string(),
+                    object: None,
+                });
+            }
+        }
+    }
+
+    let tree = Tree::new(tree_entries)?;
+    if !reader.has_object(tree.get_hash())? {
+        writer()?.write_tree(&tree)?;
+    }
+
+    Ok(tree)
+}
+
+/// Regroupe tous les objets accessibles depuis les références, HEAD et l'index dans un
+/// unique packfile, puis supprime les anciens packs et les objets loose désormais empaquetés.
+///
+/// Les packs accompagnés d'un fichier `.keep` sont conservés tels quels ; les objets non
+/// accessibles des autres packs en sont extraits en loose avant leur suppression.
+pub fn gc() -> Result<GcSummary> {
+    let objects_dir = repository::current()?.objects_dir();
+    let pack_dir = objects_dir.join("pack");
+    let reader = reader()?;
+
+    let (kept_packs, old_packs) = list_packs(&pack_dir)?;
+    let mut kept_objects = HashSet::new();
+    for idx_path in &kept_packs {
+        kept_objects.extend(PackIndex::open(idx_path)?.hashes());
+    }
+
+    // Les objets des packs conservés n'ont pas à être dupliqués dans le nouveau pack
+    let objects: Vec<PackInput> = collect_reachable_objects(&reader, collect_roots(&reader)?)?
+        .into_iter()
+        .filter(|input| !kept_objects.contains(&input.hash))
+        .collect();
+
+    let pack_name = if objects.is_empty() {
+        None
+    } else {
+        let compression = compression_level(&["pack.compression", "core.compression"])?;
+        Some(PackWriter::new(compression).write_pack(&pack_dir, &objects, &|hash| reader.read_raw_object(hash))?)
+    };
+
+    // Tout objet d'un ancien pack absent du nouveau est extrait en loose avant qu'aucun
+    // pack ne soit supprimé : une base de delta peut se trouver dans un autre ancien pack
+    let packed: HashSet<&str> = objects.iter().map(|input| input.hash.as_str()).collect();
+    let old_packs: Vec<PathBuf> = old_packs.into_iter()
+        .filter(|idx_path| !pack_name.as_ref().is_some_and(|name| idx_path.file_stem().is_some_and(|stem| stem == name.as_str())))
+        .collect();
+    let writer = writer()?;
+    for idx_path in &old_packs {
+        for hash in PackIndex::open(idx_path)?.hashes() {
+            if !packed.contains(hash.as_str()) && !kept_objects.contains(&hash) {
+                let object = reader.read_raw_object(&hash)?;
+                writer.write_loose_object(&hash, &object.kind, &object.content)?;
+            }
+        }
+    }
+
+    // Les packs existants vont être supprimés
+    drop(reader);
+    reset_reader();
+    for idx_path in &old_packs {
+        for extension in PACK_EXTENSIONS {
+            let path = idx_path.with_extension(extension);
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+    }
+
+    let mut pruned_count = 0;
+    for input in &objects {
+        let path = utils::hash_to_object_path(&objects_dir, &input.hash)?;
+        if path.exists() {
+            fs::remove_file(&path)?;
+            pruned_count += 1;
+            if let Some(dir) = path.parent() {
+                // Ne supprime le répertoire que s'il est vide
+                let _ = fs::remove_dir(dir);
+            }
+        }
+    }
+
+    Ok(GcSummary { pack_name, object_count: objects.len(), pruned_count })
+}
+
+/// Liste les index de pack de `pack_dir`, séparés entre packs conservés (accompagnés d'un
+/// fichier `.keep`) et packs remplaçables.
+fn list_packs(pack_dir: &Path) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let (mut kept, mut others) = (vec![], vec![]);
+    if !pack_dir.is_dir() {
+        return Ok((kept, others));
+    }
+
+    for entry in fs::read_dir(pack_dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "idx") {
+            if path.with_extension("keep").exists() {
+                kept.push(path);
+            } else {
+                others.push(path);
+            }
+        }
+    }
+    Ok((kept, others))
+}
+
+/// Installe un packfile reçu d'un dépôt distant dans `objects/pack` en générant son index.
+///
+/// # Renvoie
+/// - Le nom de base du pack installé et son nombre d'objets.
+pub fn index_pack(pack_path: &Path) -> Result<(String, usize)> {
+    let installed = PackIndexer.index_pack(pack_path, &repository::current()?.objects_dir().join("pack"))?;
+    reset_reader();
+    Ok(installed)
+}
+
+/// Extrait dans `path` l'arbre du commit donné.
+pub fn checkout_commit(commit_hash: &str, path: &Path) -> Result<()> {
+    let reader = reader()?;
+    let tree_hash = read_commit_tree_hash(&reader, commit_hash)?;
+    checkout_tree(&reader, &tree_hash, path)
+}
+
+/// Lit et analyse un commit.
+pub fn read_commit(commit_hash: &str) -> Result<Commit> {
+    let object = reader()?.read_raw_object(commit_hash)?;
+    if object.kind != "commit" {
+        return Err(anyhow!("Object {} is a {}, not a commit", commit_hash, object.kind));
+    }
+    Commit::from_object_file(commit_hash, &object.content)
+}
+
+/// Cherche l'entrée désignée par un chemin relatif à la racine d'un arbre.
+///
+/// # Paramètres
+/// - `tree_hash` : hash de l'arbre racine.
+/// - `path` : chemin séparé par des `/` ; un chemin vide désigne l'arbre lui-même.
+///
+/// # Renvoie
+/// - L'entrée trouvée, ou `None` si le chemin n'existe pas dans l'arbre.
+pub fn find_tree_entry(tree_hash: &str, path: &str) -> Result<Option<TreeEntry>> {
+    let reader = reader()?;
+    let mut current = TreeEntry {
+        mode: "40000".to_string(),
+        name: vec![],
+        hash: tree_hash.to_string(),
+        object: None,
+    };
+
+    for component in path.split('/').filter(|component| !component.is_empty()) {
+        // Seuls les arbres peuvent contenir la suite du chemin
+        if current.mode != "40000" {
+            return Ok(None);
+        }
+        let object = reader.read_raw_object(&current.hash)?;
+        let tree = Tree::from_object_file(&current.hash, &object.content)?;
+        match tree.entries.into_iter().find(|entry| entry.name == component.as_bytes()) {
+            Some(entry) => current = entry,
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(current))
+}
+
+/// Renvoie le hash de l'arbre racine d'un commit.
+pub fn read_commit_tree(commit_hash: &str) -> Result<String> {
+    read_commit_tree_hash(&*reader()?, commit_hash)
+}
+
+fn read_commit_tree_hash(reader: &GitObjectReader, commit_hash: &str) -> Result<String> {
+    let object = reader.read_raw_object(commit_hash)?;
+    if object.kind != "commit" {
+        return Err(anyhow!("Object {} is a {}, not a commit", commit_hash, object.kind));
+    }
+
+    let commit = Commit::from_object_file(commit_hash, &object.content)?;
+    Ok(commit.tree_hash().to_string())
+}
+
+fn checkout_tree(reader: &GitObjectReader, tree_hash: &str, path: &Path) -> Result<()> {
+    let object = reader.read_raw_object(tree_hash)?;
+    // Un arbre invalide pourrait écrire hors du répertoire de travail (`..`) ou dans `.git`
+    let tree = Tree::from_object_file_checked(tree_hash, &object.content)?;
+    fs::create_dir_all(path)?;
+
+    for entry in tree.entries {
+        let entry_path = path.join(fs_reader::path_from_bytes(&entry.name));
+        match entry.mode.as_str() {
+            "40000" => checkout_tree(reader, &entry.hash, &entry_path)?,
+            // Sous-module : seul le répertoire vide est créé
+            "160000" => fs::create_dir_all(&entry_path)?,
+            mode => {
+                let blob = reader.read_raw_object(&entry.hash)?;
+                write_checkout_file(&entry_path, mode, &blob.content)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn write_checkout_file(path: &Path, mode: &str, content: &[u8]) -> Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    match mode {
+        "120000" => symlink(std::ffi::OsStr::from_bytes(content), path)?,
+        "100755" => {
+            fs::write(path, content)?;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+        }
+        _ => fs::write(path, content)?,
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_checkout_file(path: &Path, _mode: &str, content: &[u8]) -> Result<()> {
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Liste les objets à conserver : ceux pointés par HEAD, par toutes les références, par
+/// leurs journaux et par les entrées de l'index (toutes étapes de fusion confondues).
+fn collect_roots(reader: &GitObjectReader) -> Result<Vec<String>> {
+    let mut roots: Vec<String> = refs::list_refs("refs/")?.into_iter().map(|(_, hash)| hash).collect();
+    if let Some(head) = refs::resolve_ref(refs::HEAD)? {
+        roots.push(head);
+    }
+    // Un journal peut mentionner un objet déjà supprimé par git
+    for hash in refs::list_reflog_hashes()? {
+        if reader.has_object(&hash)? {
+            roots.push(hash);
+        }
+    }
+
+    for entry in Index::load()?.entries {
+        // Un gitlink désigne un commit d'un autre dépôt ; un fichier en intention d'ajout
+        // pointe vers le blob vide, qui peut ne pas exister
+        if entry.mode != index_entry::MODE_GITLINK && reader.has_object(&entry.hash)? {
+            roots.push(entry.hash);
+        }
+    }
+    Ok(roots)
+}
+
+/// Parcourt le graphe d'objets depuis les hashes donnés et renvoie chaque objet rencontré,
+/// sans son contenu : seuls les commits, tags et arbres sont lus pour suivre leurs liens.
+fn collect_reachable_objects(reader: &GitObjectReader, tips: Vec<String>) -> Result<Vec<PackInput>> {
+    let mut seen = HashSet::new();
+    let mut pending: Vec<(String, String)> = tips.into_iter().map(|hash| (hash, String::new())).collect();
+    let mut objects = vec![];
+
+    while let Some((hash, path_hint)) = pending.pop() {
+        if !seen.insert(hash.clone()) {
+            continue;
+        }
+
+        let mut stream = reader.open_object_stream(&hash)?;
+        match stream.kind.as_str() {
+            "commit" | "tag" => {
+                let mut content = vec![];
+                stream.content.read_to_end(&mut content)?;
+                // Les liens vers les autres objets sont dans les en-têtes, avant la ligne vide
+                for line in content.split(|&b| b == b'\n').take_while(|line| !line.is_empty()) {
+                    let line = String::from_utf8_lossy(line);
+                    if let Some(("tree" | "parent" | "object", target)) = line.split_once(' ') {
+                        pending.push((target.to_string(), String::new()));
+                    }
+                }
+            }
+            "tree" => {
+                let mut content = vec![];
+                stream.content.read_to_end(&mut content)?;
+                let tree = Tree::from_object_file(&hash, &content)?;
+                for entry in tree.entries {
+                    // Les gitlinks pointent vers des commits d'un autre dépôt
+                    if entry.mode == "160000" {
+                        continue;
+                    }
+                    let name = String::from_utf8_lossy(&entry.name);
+                    let entry_path = if path_hint.is_empty() {
+                        name.into_owned()
+                    } else {
+                        format!("{}/{}", path_hint, name)
+                    };
+                    pending.push((entry.hash, entry_path));
+                }
+            }
+            "blob" => {}
+            kind => return Err(anyhow!("Unsupported object type: {}", kind)),
+        }
+
+        objects.push(PackInput { hash, kind: stream.kind, size: stream.size, path_hint });
+    }
+
+    Ok(objects)
+}
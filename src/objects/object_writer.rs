@@ -1,56 +1,124 @@
 use crate::objects::object_base::GitObject;
 use crate::objects::object_kind::GitObjectKind;
 use crate::objects::tree::tree::Tree;
-use anyhow::Result;
+use crate::objects::{object_manager, utils};
+use anyhow::{anyhow, Result};
 use flate2::write::ZlibEncoder;
 use flate2::Compression;
-use std::fs::{create_dir_all, File};
-use std::io::{BufWriter, Write};
+use std::fs::{self, create_dir_all, File, OpenOptions};
+use std::io::{BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-pub struct GitObjectWriter;
+const TEMP_OBJECT_PREFIX: &str = "tmp_obj_";
+/// Distingue les fichiers temporaires d'un même processus.
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+pub struct GitObjectWriter {
+    /// Répertoire `objects` du dépôt dans lequel écrire.
+    objects_dir: PathBuf,
+    /// Niveau de compression des objets.
+    compression: Compression,
+}
 
 impl GitObjectWriter {
+    pub fn new(objects_dir: &Path, compression: Compression) -> Self {
+        GitObjectWriter { objects_dir: objects_dir.to_path_buf(), compression }
+    }
+
+    /// Répertoire `objects` dans lequel les objets sont écrits.
+    pub fn objects_dir(&self) -> &Path {
+        &self.objects_dir
+    }
+
     pub fn write_object(&self, object: &GitObjectKind) -> Result<()> {
         match object {
             GitObjectKind::Blob(blob) => self.write_individual_object(blob),
             GitObjectKind::Tree(tree) => self.write_tree(tree),
             GitObjectKind::Commit(commit) => self.write_individual_object(commit),
+            GitObjectKind::Tag(tag) => self.write_individual_object(tag),
         }
     }
 
     fn write_individual_object<T: GitObject>(&self, object: &T) -> Result<()> {
-        let object_data = object.compute_object_data();
+        // Un objet présent (loose ou dans un pack) a forcément le même contenu : rien à écrire
+        if object_manager::has_object(object.get_hash())? {
+            return Ok(());
+        }
 
-        let path = object.compute_file_path()?;
+        let path = object.compute_file_path(&self.objects_dir)?;
+        let dir = path.parent()
+            .ok_or_else(|| anyhow!("Invalid object path: {:?}", path))?;
+        create_dir_all(dir)?;
 
-        // Si l'objet existe déjà, ne pas réécrire
-        if path.exists() {
-            return Err(anyhow::anyhow!("Object already exists with hash: {}", object.get_hash()));
-        }
+        // L'objet est écrit sous un nom temporaire puis renommé : un objet interrompu
+        // en cours d'écriture n'apparaît jamais sous son nom définitif
+        let (tmp_path, file) = create_temp_file(dir)?;
+        let result = write_compressed(file, &tmp_path, self.compression, |encoder| Ok(encoder.write_all(&object.compute_object_data())?))
+            .and_then(|_| move_into_place(&tmp_path, &path));
+        remove_leftover(&tmp_path);
+        result
+    }
 
-        // Créez les répertoires si nécessaire
-        if let Some(parent_dir) = path.parent() {
-            create_dir_all(parent_dir)?;
-        }
+    /// Écrit un blob lu en flux : il est haché et compressé en une seule passe, sans être
+    /// chargé en mémoire.
+    ///
+    /// # Paramètres
+    /// - `content` : lecteur du contenu du blob.
+    /// - `size` : taille du contenu, écrite dans l'en-tête avant celui-ci.
+    ///
+    /// # Renvoie
+    /// - Le hash du blob.
+    pub fn write_blob_stream<R: Read>(&self, content: R, size: u64) -> Result<String> {
+        // Le hash n'est connu qu'à la fin : le fichier temporaire est créé dans `objects/`
+        create_dir_all(&self.objects_dir)?;
 
-        // Ouvrez un fichier en mode écriture dans .git/objects/ et utilisez la compression Zlib
-        let writer = BufWriter::new(File::create(&path)?);
-        let mut encoder = ZlibEncoder::new(writer, Compression::default());
+        let (tmp_path, file) = create_temp_file(&self.objects_dir)?;
+        let result = write_compressed(file, &tmp_path, self.compression, |encoder| utils::hash_blob_stream(content, size, encoder))
+            .and_then(|hash| {
+                if !object_manager::has_object(&hash)? {
+                    move_into_place(&tmp_path, &utils::hash_to_object_path(&self.objects_dir, &hash)?)?;
+                }
+                Ok(hash)
+            });
+        remove_leftover(&tmp_path);
+        result
+    }
 
-        // Écrivez les données compressées dans le fichier
-        encoder.write_all(&object_data)?;
-        encoder.finish()?;
+    /// Écrit un objet en loose même s'il est déjà présent dans un pack, pour le conserver
+    /// lorsque ce pack est supprimé.
+    ///
+    /// # Paramètres
+    /// - `hash` : hash de l'objet, qui détermine son emplacement.
+    /// - `kind` : type de l'objet (`blob`, `tree`, `commit` ou `tag`).
+    /// - `content` : contenu de l'objet, sans en-tête.
+    pub fn write_loose_object(&self, hash: &str, kind: &str, content: &[u8]) -> Result<()> {
+        let path = utils::hash_to_object_path(&self.objects_dir, hash)?;
+        if path.exists() {
+            return Ok(());
+        }
+        let dir = path.parent()
+            .ok_or_else(|| anyhow!("Invalid object path: {:?}", path))?;
+        create_dir_all(dir)?;
 
-        Ok(())
+        let (tmp_path, file) = create_temp_file(dir)?;
+        let result = write_compressed(file, &tmp_path, self.compression, |encoder| {
+            write!(encoder, "{} {}\0", kind, content.len())?;
+            Ok(encoder.write_all(content)?)
+        })
+            .and_then(|_| move_into_place(&tmp_path, &path));
+        remove_leftover(&tmp_path);
+        result
     }
 
-    fn write_tree(&self, tree: &Tree) -> Result<()> {
+    pub fn write_tree(&self, tree: &Tree) -> Result<()> {
         for entry in &tree.entries {
             if let Some(object) = &entry.object {
                 match object {
                     GitObjectKind::Blob(blob) => self.write_individual_object(blob)?,
                     GitObjectKind::Tree(sub_tree) => self.write_tree(sub_tree)?,
                     GitObjectKind::Commit(commit) => self.write_individual_object(commit)?,
+                    GitObjectKind::Tag(tag) => self.write_individual_object(tag)?,
                 }
             }
         }
@@ -59,3 +127,56 @@ impl GitObjectWriter {
         self.write_individual_object(tree)
     }
 }
+
+/// Crée un fichier temporaire inexistant dans `dir`, sur le modèle `tmp_obj_<pid>_<n>` de git.
+fn create_temp_file(dir: &Path) -> Result<(PathBuf, File)> {
+    loop {
+        let counter = TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let tmp_path = dir.join(format!("{}{}_{}", TEMP_OBJECT_PREFIX, std::process::id(), counter));
+        match OpenOptions::new().write(true).create_new(true).open(&tmp_path) {
+            Ok(file) => return Ok((tmp_path, file)),
+            // Reste d'une écriture interrompue portant le même nom : on passe au suivant
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Compresse dans le fichier les données produites par `write`, les force sur le disque,
+/// puis passe le fichier en lecture seule comme git.
+///
+/// Sans `sync_all`, le renommage pourrait survivre à une coupure de courant alors que le
+/// contenu n'a pas été écrit, laissant un objet tronqué sous son nom définitif.
+fn write_compressed<T, F>(file: File, path: &Path, compression: Compression, write: F) -> Result<T>
+where
+    F: FnOnce(&mut ZlibEncoder<BufWriter<File>>) -> Result<T>,
+{
+    let mut encoder = ZlibEncoder::new(BufWriter::new(file), compression);
+    let value = write(&mut encoder)?;
+    let file = encoder.finish()?.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()?;
+
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(path, permissions)?;
+    Ok(value)
+}
+
+/// Renomme un objet temporaire complet vers son emplacement définitif.
+fn move_into_place(tmp_path: &Path, path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        create_dir_all(dir)?;
+    }
+    match fs::rename(tmp_path, path) {
+        // Écrit entre-temps par un autre processus : le contenu est identique
+        Err(_) if path.exists() => Ok(()),
+        result => Ok(result?),
+    }
+}
+
+/// Supprime le fichier temporaire s'il n'a pas été renommé (objet déjà présent ou erreur).
+fn remove_leftover(tmp_path: &Path) {
+    if tmp_path.exists() {
+        let _ = fs::remove_file(tmp_path);
+    }
+}
@@ -1,21 +1,187 @@
 use crate::objects::blob::blob::Blob;
 use crate::objects::object_base::GitObject;
 use crate::objects::object_kind::GitObjectKind;
+use crate::objects::object_manager;
+use crate::objects::pack::pack_file::PackFile;
 use crate::objects::tree::tree::Tree;
 use crate::objects::utils;
 use anyhow::{anyhow, Result};
 use flate2::read::ZlibDecoder;
+use std::cell::OnceCell;
 use std::fs::File;
 use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
 use crate::objects::commit::commit::Commit;
+use crate::objects::tag::tag::Tag;
 
-pub struct GitObjectReader;
+/// Longueur maximale de l'en-tête `<type> <taille>` d'un objet loose.
+const MAX_HEADER_LENGTH: usize = 64;
+
+/// Type et contenu d'un objet tels que stockés, avant interprétation.
+#[derive(Debug)]
+pub struct RawObject {
+    pub kind: String,
+    pub content: Vec<u8>,
+}
+
+/// Objet ouvert en lecture continue : type et taille lus dans l'en-tête, contenu à la demande.
+pub struct ObjectStream {
+    pub kind: String,
+    pub size: u64,
+    pub content: Box<dyn Read>,
+}
+
+pub struct GitObjectReader {
+    /// Répertoire `objects` du dépôt lu.
+    objects_dir: PathBuf,
+    /// Packfiles du dépôt, chargés à la première recherche d'un objet absent en loose.
+    packs: OnceCell<Vec<PackFile>>,
+}
 
 impl GitObjectReader {
+    pub fn new(objects_dir: &Path) -> Self {
+        GitObjectReader { objects_dir: objects_dir.to_path_buf(), packs: OnceCell::new() }
+    }
+
+    pub fn objects_dir(&self) -> &Path {
+        &self.objects_dir
+    }
+
     pub fn read_object(&self, hash: &str) -> Result<GitObjectKind> {
-        // Génère le chemin d'accès au fichier blob correspondant au hash
-        let path = utils::hash_to_object_path(hash)?;
+        let raw = self.read_raw_object(hash)?;
+
+        // Reconstruire l'objet en fonction de son type
+        match raw.kind.as_str() {
+            "blob" => {
+                // Crée un objet Blob à partir des données
+                let blob = Blob::from_object_file(hash, &raw.content)?;
+                Ok(GitObjectKind::Blob(blob))
+            },
+            "tree" => {
+                // Crée un objet Tree à partir des données
+                let tree = Tree::from_object_file(hash, &raw.content)?;
+                Ok(GitObjectKind::Tree(tree))
+            },
+            "commit" => {
+                let commit = Commit::from_object_file(hash, &raw.content)?;
+                Ok(GitObjectKind::Commit(commit))
+            }
+            "tag" => {
+                let tag = Tag::from_object_file(hash, &raw.content)?;
+                Ok(GitObjectKind::Tag(tag))
+            }
+            _ => Err(anyhow!("Unsupported object type: {}", raw.kind)),
+        }
+    }
+
+    /// Lit le type et le contenu d'un objet, qu'il soit stocké en loose ou dans un packfile.
+    pub fn read_raw_object(&self, hash: &str) -> Result<RawObject> {
+        // Génère le chemin d'accès au fichier objet correspondant au hash
+        let path = utils::hash_to_object_path(&self.objects_dir, hash)?;
+        if path.exists() {
+            return self.read_loose_object(&path);
+        }
+
+        let binary_hash = binary_hash(hash)?;
+        for pack in self.packs()? {
+            if let Some(object) = pack.read_object(&binary_hash, &|base| self.read_raw_object(base))? {
+                return Ok(object);
+            }
+        }
+
+        Err(anyhow!("Object not found: {}", hash))
+    }
+
+    /// Ouvre un objet en flux, sans charger son contenu en mémoire.
+    ///
+    /// Les objets loose et les entrées non deltifiées d'un pack sont décompressés au fil
+    /// de la lecture ; un objet deltifié est reconstruit en mémoire.
+    pub fn open_object_stream(&self, hash: &str) -> Result<ObjectStream> {
+        let path = utils::hash_to_object_path(&self.objects_dir, hash)?;
+        if path.exists() {
+            return self.open_loose_stream(&path);
+        }
+
+        let binary_hash = binary_hash(hash)?;
+        for pack in self.packs()? {
+            if let Some(offset) = pack.index.find_offset(&binary_hash) {
+                return pack.open_stream_at(offset, &|base| self.read_raw_object(base));
+            }
+        }
+
+        Err(anyhow!("Object not found: {}", hash))
+    }
+
+    /// Indique si l'objet est présent dans le dépôt, en loose ou dans un packfile.
+    pub fn has_object(&self, hash: &str) -> Result<bool> {
+        if utils::hash_to_object_path(&self.objects_dir, hash)?.exists() {
+            return Ok(true);
+        }
+
+        let binary_hash = binary_hash(hash)?;
+        Ok(self.packs()?.iter().any(|pack| pack.index.find_offset(&binary_hash).is_some()))
+    }
 
+    /// Renvoie les hashes des objets, loose ou packés, qui commencent par `prefix`.
+    ///
+    /// # Paramètres
+    /// - `prefix` : préfixe hexadécimal en minuscules, d'au moins deux caractères.
+    pub fn find_objects_by_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut hashes = vec![];
+        if prefix.len() < 2 {
+            return Ok(hashes);
+        }
+
+        // Les objets loose sont rangés dans un répertoire nommé d'après les deux premiers caractères
+        let (dir, rest) = prefix.split_at(2);
+        let loose_dir = self.objects_dir.join(dir);
+        if loose_dir.is_dir() {
+            for entry in std::fs::read_dir(loose_dir)? {
+                let name = entry?.file_name().to_string_lossy().into_owned();
+                if name.len() == object_manager::OBJECT_HASH_SIZE - 2 && name.starts_with(rest) {
+                    hashes.push(format!("{}{}", dir, name));
+                }
+            }
+        }
+
+        for pack in self.packs()? {
+            hashes.extend(pack.index.find_by_prefix(prefix));
+        }
+
+        hashes.sort();
+        hashes.dedup();
+        Ok(hashes)
+    }
+
+    /// Renvoie les hashes de tous les objets, loose et packés, triés et sans doublons.
+    pub fn list_objects(&self) -> Result<Vec<String>> {
+        let mut hashes = vec![];
+
+        for dir in std::fs::read_dir(&self.objects_dir)? {
+            let dir = dir?;
+            let dir_name = dir.file_name().to_string_lossy().into_owned();
+            // Seuls les répertoires nommés par deux caractères hexadécimaux contiennent des objets
+            if dir_name.len() != 2 || !dir_name.chars().all(|c| c.is_ascii_hexdigit()) {
+                continue;
+            }
+            for entry in std::fs::read_dir(dir.path())? {
+                let name = entry?.file_name().to_string_lossy().into_owned();
+                if name.len() == object_manager::OBJECT_HASH_SIZE - 2 {
+                    hashes.push(format!("{}{}", dir_name, name));
+                }
+            }
+        }
+
+        for pack in self.packs()? {
+            hashes.extend(pack.index.hashes());
+        }
+
+        hashes.sort();
+        hashes.dedup();
+        Ok(hashes)
+    }
+
+    fn read_loose_object(&self, path: &Path) -> Result<RawObject> {
         // Ouvre le fichier de l'objet Git
         let file = File::open(path)?;
         let reader = BufReader::new(file);
@@ -29,26 +195,44 @@ impl GitObjectReader {
         let (header, content) = self.split_header_and_content(&object_data)?;
 
         // Identifier le type de l'objet à partir de l'en-tête
-        let object_kind = self.identify_object_type(&header)?;
+        let kind = self.identify_object_type(header)?;
 
-        // Reconstruire l'objet en fonction de son type
-        match object_kind.as_str() {
-            "blob" => {
-                // Crée un objet Blob à partir des données
-                let blob = Blob::from_object_file(hash, content)?;
-                Ok(GitObjectKind::Blob(blob))
-            },
-            "tree" => {
-                // Crée un objet Tree à partir des données
-                let tree = Tree::from_object_file(hash, content)?;
-                Ok(GitObjectKind::Tree(tree))
-            },
-            "commit" => {
-                let commit = Commit::from_object_file(hash, content)?;
-                Ok(GitObjectKind::Commit(commit))
+        Ok(RawObject { kind, content: content.to_vec() })
+    }
+
+    fn open_loose_stream(&self, path: &Path) -> Result<ObjectStream> {
+        let mut decoder = ZlibDecoder::new(BufReader::new(File::open(path)?));
+
+        // L'en-tête est lu octet par octet pour laisser le contenu dans le flux
+        let mut header = vec![];
+        let mut byte = [0u8; 1];
+        loop {
+            decoder.read_exact(&mut byte)?;
+            if byte[0] == b'\0' {
+                break;
+            }
+            if header.len() >= MAX_HEADER_LENGTH {
+                return Err(anyhow!("Invalid object: missing header"));
             }
-            _ => Err(anyhow!("Unsupported object type: {}", object_kind)),
+            header.push(byte[0]);
+        }
+
+        let header = std::str::from_utf8(&header)
+            .map_err(|_| anyhow!("Invalid UTF-8 in object header"))?;
+        let (kind, size) = header.split_once(' ')
+            .ok_or_else(|| anyhow!("Invalid object header: {}", header))?;
+        let size = size.parse::<u64>()
+            .map_err(|_| anyhow!("Invalid object size in header: {}", header))?;
+
+        Ok(ObjectStream { kind: kind.to_string(), size, content: Box::new(decoder.take(size)) })
+    }
+
+    fn packs(&self) -> Result<&[PackFile]> {
+        if self.packs.get().is_none() {
+            let pack_dir = self.objects_dir.join("pack");
+            let _ = self.packs.set(PackFile::open_all(&pack_dir)?);
         }
+        Ok(self.packs.get().unwrap())
     }
 
     /// Sépare l'en-tête et le contenu des données d'un objet Git.
@@ -72,4 +256,12 @@ impl GitObjectReader {
         let object_type = parts.next().ok_or_else(|| anyhow!("Invalid object header: missing type"))?;
         Ok(object_type.to_string())
     }
-}
\ No newline at end of file
+}
+
+/// Convertit un hash hexadécimal en sa forme binaire, telle que stockée dans les index de pack.
+fn binary_hash(hash: &str) -> Result<[u8; 20]> {
+    hex::decode(hash)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow!("Invalid object identifier: {}", hash))
+}
@@ -0,0 +1,202 @@
+use anyhow::{anyhow, Result};
+
+/// Applique un delta git (OFS_DELTA / REF_DELTA) sur l'objet de base.
+///
+/// # Paramètres
+/// - `base`: Le contenu de l'objet de base.
+/// - `delta`: Les instructions du delta, décompressées.
+///
+/// # Renvoie
+/// - Le contenu de l'objet reconstruit.
+pub fn apply_delta(base: &[u8], delta: &[u8]) -> Result<Vec<u8>> {
+    let mut idx = 0;
+    let base_size = read_size(delta, &mut idx)?;
+    let result_size = read_size(delta, &mut idx)?;
+
+    if base_size != base.len() {
+        return Err(anyhow!("Invalid delta: expected base of {} bytes, got {}", base_size, base.len()));
+    }
+
+    let mut result = Vec::with_capacity(result_size);
+
+    while idx < delta.len() {
+        let instruction = delta[idx];
+        idx += 1;
+
+        if instruction & 0x80 != 0 {
+            // Copie d'une plage de l'objet de base : les bits 0-3 indiquent les octets
+            // d'offset présents, les bits 4-6 ceux de la taille
+            let mut offset = 0usize;
+            for i in 0..4 {
+                if instruction & (1 << i) != 0 {
+                    offset |= (*delta.get(idx).ok_or_else(|| anyhow!("Invalid delta: truncated copy"))? as usize) << (i * 8);
+                    idx += 1;
+                }
+            }
+            let mut size = 0usize;
+            for i in 0..3 {
+                if instruction & (1 << (4 + i)) != 0 {
+                    size |= (*delta.get(idx).ok_or_else(|| anyhow!("Invalid delta: truncated copy"))? as usize) << (i * 8);
+                    idx += 1;
+                }
+            }
+            if size == 0 {
+                size = 0x10000;
+            }
+            let end = offset.checked_add(size)
+                .filter(|&end| end <= base.len())
+                .ok_or_else(|| anyhow!("Invalid delta: copy out of base bounds"))?;
+            result.extend_from_slice(&base[offset..end]);
+        } else if instruction != 0 {
+            // Insertion de données littérales
+            let size = instruction as usize;
+            if idx + size > delta.len() {
+                return Err(anyhow!("Invalid delta: truncated insert"));
+            }
+            result.extend_from_slice(&delta[idx..idx + size]);
+            idx += size;
+        } else {
+            return Err(anyhow!("Invalid delta: reserved instruction 0"));
+        }
+    }
+
+    if result.len() != result_size {
+        return Err(anyhow!("Invalid delta: expected result of {} bytes, got {}", result_size, result.len()));
+    }
+
+    Ok(result)
+}
+
+/// Lit une taille encodée en varint little-endian (7 bits par octet) en tête de delta.
+fn read_size(delta: &[u8], idx: &mut usize) -> Result<usize> {
+    let mut size = 0usize;
+    let mut shift = 0;
+    loop {
+        let byte = *delta.get(*idx).ok_or_else(|| anyhow!("Invalid delta: truncated header"))?;
+        *idx += 1;
+        size |= ((byte & 0x7f) as usize) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Ok(size);
+        }
+    }
+}
+
+/// Taille des blocs de l'objet de base indexés pour rechercher des correspondances.
+const DELTA_BLOCK_SIZE: usize = 16;
+/// Taille maximale d'une copie émise en une seule instruction.
+const MAX_COPY_SIZE: usize = 0x10000;
+/// Taille maximale d'une insertion littérale en une seule instruction.
+const MAX_INSERT_SIZE: usize = 0x7f;
+/// Nombre maximal de candidats examinés pour un même bloc.
+const MAX_CANDIDATES: usize = 8;
+
+/// Calcule un delta git permettant de reconstruire `target` à partir de `base`.
+///
+/// # Paramètres
+/// - `base`: Le contenu de l'objet de base.
+/// - `target`: Le contenu de l'objet à encoder.
+///
+/// # Renvoie
+/// - Les instructions du delta, au format attendu par `apply_delta`.
+pub fn create_delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut delta = Vec::new();
+    write_size(&mut delta, base.len());
+    write_size(&mut delta, target.len());
+
+    // Indexe les blocs alignés de la base par leur contenu
+    let mut blocks: std::collections::HashMap<&[u8], Vec<usize>> = std::collections::HashMap::new();
+    for start in (0..base.len().saturating_sub(DELTA_BLOCK_SIZE - 1)).step_by(DELTA_BLOCK_SIZE) {
+        let candidates = blocks.entry(&base[start..start + DELTA_BLOCK_SIZE]).or_default();
+        if candidates.len() < MAX_CANDIDATES {
+            candidates.push(start);
+        }
+    }
+
+    let mut insert_start = 0;
+    let mut idx = 0;
+    while idx + DELTA_BLOCK_SIZE <= target.len() {
+        let best = blocks.get(&target[idx..idx + DELTA_BLOCK_SIZE]).and_then(|candidates| {
+            candidates.iter()
+                .map(|&start| {
+                    let length = base[start..].iter()
+                        .zip(&target[idx..])
+                        .take_while(|(a, b)| a == b)
+                        .count();
+                    (start, length)
+                })
+                .max_by_key(|&(_, length)| length)
+        });
+
+        match best {
+            Some((mut base_start, mut length)) => {
+                // Étend la correspondance vers l'arrière sur les octets en attente d'insertion
+                let mut target_start = idx;
+                while target_start > insert_start && base_start > 0 && base[base_start - 1] == target[target_start - 1] {
+                    base_start -= 1;
+                    target_start -= 1;
+                    length += 1;
+                }
+
+                write_insert(&mut delta, &target[insert_start..target_start]);
+                write_copy(&mut delta, base_start, length);
+                idx = target_start + length;
+                insert_start = idx;
+            }
+            None => idx += 1,
+        }
+    }
+    write_insert(&mut delta, &target[insert_start..]);
+
+    delta
+}
+
+fn write_size(delta: &mut Vec<u8>, mut size: usize) {
+    loop {
+        let byte = (size & 0x7f) as u8;
+        size >>= 7;
+        if size == 0 {
+            delta.push(byte);
+            return;
+        }
+        delta.push(byte | 0x80);
+    }
+}
+
+fn write_insert(delta: &mut Vec<u8>, data: &[u8]) {
+    for chunk in data.chunks(MAX_INSERT_SIZE) {
+        delta.push(chunk.len() as u8);
+        delta.extend_from_slice(chunk);
+    }
+}
+
+fn write_copy(delta: &mut Vec<u8>, mut offset: usize, mut length: usize) {
+    while length > 0 {
+        let size = length.min(MAX_COPY_SIZE);
+        let mut instruction = 0x80u8;
+        let mut arguments = Vec::with_capacity(7);
+
+        for i in 0..4 {
+            let byte = (offset >> (i * 8)) as u8;
+            if byte != 0 {
+                instruction |= 1 << i;
+                arguments.push(byte);
+            }
+        }
+        // Une taille de 0x10000 est encodée par l'absence d'octets de taille
+        if size != MAX_COPY_SIZE {
+            for i in 0..3 {
+                let byte = (size >> (i * 8)) as u8;
+                if byte != 0 {
+                    instruction |= 1 << (4 + i);
+                    arguments.push(byte);
+                }
+            }
+        }
+
+        delta.push(instruction);
+        delta.extend_from_slice(&arguments);
+        offset += size;
+        length -= size;
+    }
+}
@@ -0,0 +1,5 @@
+pub mod delta;
+pub mod pack_file;
+pub mod pack_index;
+pub mod pack_indexer;
+pub mod pack_writer;
@@ -0,0 +1,201 @@
+use crate::objects::blob::blob;
+use crate::objects::commit::commit;
+use crate::objects::objet_reader::{ObjectStream, RawObject};
+use crate::objects::pack::delta;
+use crate::objects::pack::pack_index::PackIndex;
+use crate::objects::tag::tag;
+use crate::objects::tree::tree;
+use anyhow::{anyhow, Result};
+use flate2::bufread::ZlibDecoder;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+pub(crate) const PACK_SIGNATURE: &[u8; 4] = b"PACK";
+pub(crate) const PACK_VERSION: u32 = 2;
+
+pub(crate) const OBJ_COMMIT: u8 = 1;
+pub(crate) const OBJ_TREE: u8 = 2;
+pub(crate) const OBJ_BLOB: u8 = 3;
+pub(crate) const OBJ_TAG: u8 = 4;
+pub(crate) const OBJ_OFS_DELTA: u8 = 6;
+pub(crate) const OBJ_REF_DELTA: u8 = 7;
+
+/// Un packfile (`.pack`) accompagné de son index (`.idx`).
+#[derive(Debug)]
+pub struct PackFile {
+    pack_path: PathBuf,
+    pub index: PackIndex,
+}
+
+impl PackFile {
+    /// Ouvre le packfile associé au fichier `.idx` donné.
+    pub fn open(idx_path: &Path) -> Result<PackFile> {
+        let index = PackIndex::open(idx_path)?;
+        let pack_path = idx_path.with_extension("pack");
+
+        let mut header = [0u8; 8];
+        File::open(&pack_path)?.read_exact(&mut header)?;
+        if &header[..4] != PACK_SIGNATURE || u32::from_be_bytes(header[4..].try_into().unwrap()) != PACK_VERSION {
+            return Err(anyhow!("Invalid packfile: {:?}", pack_path));
+        }
+
+        Ok(PackFile { pack_path, index })
+    }
+
+    /// Liste les packfiles présents dans le répertoire `objects/pack`.
+    pub fn open_all(pack_dir: &Path) -> Result<Vec<PackFile>> {
+        let mut packs = vec![];
+        if !pack_dir.is_dir() {
+            return Ok(packs);
+        }
+
+        let mut idx_paths: Vec<PathBuf> = std::fs::read_dir(pack_dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "idx"))
+            .collect();
+        idx_paths.sort();
+
+        for idx_path in idx_paths {
+            packs.push(PackFile::open(&idx_path)?);
+        }
+        Ok(packs)
+    }
+
+    /// Lit l'objet identifié par `hash` (forme binaire) s'il est présent dans ce pack.
+    ///
+    /// # Paramètres
+    /// - `hash`: Le hash binaire de l'objet recherché.
+    /// - `resolve_external`: Fonction utilisée pour retrouver la base d'un REF_DELTA absente du pack.
+    pub fn read_object(
+        &self,
+        hash: &[u8; 20],
+        resolve_external: &dyn Fn(&str) -> Result<RawObject>,
+    ) -> Result<Option<RawObject>> {
+        match self.index.find_offset(hash) {
+            Some(offset) => Ok(Some(self.read_at(offset, resolve_external)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Lit et reconstruit l'objet situé à l'offset donné dans le packfile.
+    pub fn read_at(&self, offset: u64, resolve_external: &dyn Fn(&str) -> Result<RawObject>) -> Result<RawObject> {
+        let mut reader = BufReader::new(File::open(&self.pack_path)?);
+        reader.seek(SeekFrom::Start(offset))?;
+
+        let (object_type, size) = read_entry_header(&mut reader)?;
+
+        match object_type {
+            OBJ_COMMIT | OBJ_TREE | OBJ_BLOB | OBJ_TAG => {
+                let content = inflate(&mut reader, size)?;
+                Ok(RawObject { kind: kind_name(object_type)?.to_string(), content })
+            }
+            OBJ_OFS_DELTA => {
+                let distance = read_offset_delta(&mut reader)?;
+                let base_offset = offset.checked_sub(distance)
+                    .ok_or_else(|| anyhow!("Invalid packfile: delta base before start of pack"))?;
+                let delta = inflate(&mut reader, size)?;
+
+                let base = self.read_at(base_offset, resolve_external)?;
+                let content = delta::apply_delta(&base.content, &delta)?;
+                Ok(RawObject { kind: base.kind, content })
+            }
+            OBJ_REF_DELTA => {
+                let mut base_hash = [0u8; 20];
+                reader.read_exact(&mut base_hash)?;
+                let delta = inflate(&mut reader, size)?;
+
+                let base = match self.read_object(&base_hash, resolve_external)? {
+                    Some(base) => base,
+                    None => resolve_external(&hex::encode(base_hash))?,
+                };
+                let content = delta::apply_delta(&base.content, &delta)?;
+                Ok(RawObject { kind: base.kind, content })
+            }
+            _ => Err(anyhow!("Invalid packfile: unknown object type {}", object_type)),
+        }
+    }
+
+    /// Ouvre en flux l'objet situé à l'offset donné ; seul un delta est reconstruit en mémoire.
+    pub fn open_stream_at(&self, offset: u64, resolve_external: &dyn Fn(&str) -> Result<RawObject>) -> Result<ObjectStream> {
+        let mut reader = BufReader::new(File::open(&self.pack_path)?);
+        reader.seek(SeekFrom::Start(offset))?;
+
+        let (object_type, size) = read_entry_header(&mut reader)?;
+        match object_type {
+            OBJ_COMMIT | OBJ_TREE | OBJ_BLOB | OBJ_TAG => Ok(ObjectStream {
+                kind: kind_name(object_type)?.to_string(),
+                size: size as u64,
+                content: Box::new(ZlibDecoder::new(reader).take(size as u64)),
+            }),
+            _ => {
+                let object = self.read_at(offset, resolve_external)?;
+                Ok(ObjectStream {
+                    kind: object.kind,
+                    size: object.content.len() as u64,
+                    content: Box::new(Cursor::new(object.content)),
+                })
+            }
+        }
+    }
+}
+
+/// Convertit un type d'objet de pack en nom d'objet git.
+pub(crate) fn kind_name(object_type: u8) -> Result<&'static str> {
+    match object_type {
+        OBJ_COMMIT => Ok(commit::HEADER_PREFIX),
+        OBJ_TREE => Ok(tree::HEADER_PREFIX),
+        OBJ_BLOB => Ok(blob::HEADER_PREFIX),
+        OBJ_TAG => Ok(tag::HEADER_PREFIX),
+        _ => Err(anyhow!("Invalid packfile: unknown object type {}", object_type)),
+    }
+}
+
+/// Lit l'en-tête d'une entrée : type sur 3 bits puis taille décompressée en varint.
+pub(crate) fn read_entry_header<R: Read>(reader: &mut R) -> Result<(u8, usize)> {
+    let mut byte = read_byte(reader)?;
+    let object_type = (byte >> 4) & 0x07;
+    let mut size = (byte & 0x0f) as usize;
+    let mut shift = 4;
+
+    while byte & 0x80 != 0 {
+        byte = read_byte(reader)?;
+        size |= ((byte & 0x7f) as usize) << shift;
+        shift += 7;
+    }
+
+    Ok((object_type, size))
+}
+
+/// Lit la distance négative vers la base d'un OFS_DELTA.
+pub(crate) fn read_offset_delta<R: Read>(reader: &mut R) -> Result<u64> {
+    let mut byte = read_byte(reader)?;
+    let mut distance = (byte & 0x7f) as u64;
+
+    while byte & 0x80 != 0 {
+        byte = read_byte(reader)?;
+        distance = ((distance + 1) << 7) | (byte & 0x7f) as u64;
+    }
+
+    Ok(distance)
+}
+
+fn read_byte<R: Read>(reader: &mut R) -> Result<u8> {
+    let mut byte = [0u8; 1];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+/// Décompresse le flux zlib d'une entrée et vérifie sa taille.
+///
+/// Le lecteur est laissé positionné juste après la fin du flux compressé.
+pub(crate) fn inflate<R: BufRead>(reader: &mut R, size: usize) -> Result<Vec<u8>> {
+    let mut decoder = ZlibDecoder::new(reader);
+    let mut content = Vec::with_capacity(size);
+    decoder.read_to_end(&mut content)?;
+
+    if content.len() != size {
+        return Err(anyhow!("Invalid packfile: expected {} bytes, inflated {}", size, content.len()));
+    }
+    Ok(content)
+}
@@ -0,0 +1,119 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+const IDX_SIGNATURE: &[u8; 4] = b"\xfftOc";
+const IDX_VERSION: u32 = 2;
+const FANOUT_ENTRIES: usize = 256;
+const HASH_BYTES: usize = 20;
+/// Bit de poids fort indiquant qu'un offset 32 bits renvoie vers la table des offsets 64 bits.
+const LARGE_OFFSET_FLAG: u32 = 0x8000_0000;
+
+/// Index d'un packfile (`.idx` version 2).
+///
+/// Permet de retrouver l'offset d'un objet dans le `.pack` associé à partir de son hash.
+#[derive(Debug)]
+pub struct PackIndex {
+    fanout: [u32; FANOUT_ENTRIES],
+    hashes: Vec<[u8; HASH_BYTES]>,
+    offsets: Vec<u64>,
+}
+
+impl PackIndex {
+    /// Charge et analyse un fichier `.idx` version 2.
+    pub fn open(path: &Path) -> Result<PackIndex> {
+        let data = std::fs::read(path)?;
+        Self::parse(&data).map_err(|e| anyhow!("Invalid pack index {:?}: {}", path, e))
+    }
+
+    fn parse(data: &[u8]) -> Result<PackIndex> {
+        if data.len() < 8 + FANOUT_ENTRIES * 4 || &data[..4] != IDX_SIGNATURE {
+            return Err(anyhow!("unsupported pack index format (only version 2 is supported)"));
+        }
+        let version = read_u32(data, 4);
+        if version != IDX_VERSION {
+            return Err(anyhow!("unsupported pack index version {}", version));
+        }
+
+        // Table de répartition : nombre cumulé d'objets dont le premier octet est <= i
+        let mut fanout = [0u32; FANOUT_ENTRIES];
+        for (i, entry) in fanout.iter_mut().enumerate() {
+            *entry = read_u32(data, 8 + i * 4);
+        }
+        // Une table décroissante ferait sortir les recherches de la liste des hashes
+        if fanout.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err(anyhow!("non-monotonic fanout table"));
+        }
+        let count = fanout[FANOUT_ENTRIES - 1] as usize;
+
+        let hashes_start = 8 + FANOUT_ENTRIES * 4;
+        let crcs_start = hashes_start + count * HASH_BYTES;
+        let offsets_start = crcs_start + count * 4;
+        let large_offsets_start = offsets_start + count * 4;
+        if data.len() < large_offsets_start + 2 * HASH_BYTES {
+            return Err(anyhow!("truncated pack index"));
+        }
+
+        let hashes = (0..count)
+            .map(|i| {
+                let start = hashes_start + i * HASH_BYTES;
+                data[start..start + HASH_BYTES].try_into().unwrap()
+            })
+            .collect();
+
+        let mut offsets = Vec::with_capacity(count);
+        for i in 0..count {
+            let offset = read_u32(data, offsets_start + i * 4);
+            if offset & LARGE_OFFSET_FLAG == 0 {
+                offsets.push(offset as u64);
+            } else {
+                // L'offset réel est stocké dans la table des offsets 64 bits
+                let position = large_offsets_start + (offset & !LARGE_OFFSET_FLAG) as usize * 8;
+                if position + 8 > data.len() {
+                    return Err(anyhow!("truncated large offset table"));
+                }
+                offsets.push(u64::from_be_bytes(data[position..position + 8].try_into().unwrap()));
+            }
+        }
+
+        Ok(PackIndex { fanout, hashes, offsets })
+    }
+
+    /// Recherche l'offset d'un objet dans le packfile à partir de son hash binaire.
+    pub fn find_offset(&self, hash: &[u8; HASH_BYTES]) -> Option<u64> {
+        let first = hash[0] as usize;
+        let start = if first == 0 { 0 } else { self.fanout[first - 1] as usize };
+        let end = self.fanout[first] as usize;
+
+        self.hashes[start..end]
+            .binary_search(hash)
+            .ok()
+            .map(|position| self.offsets[start + position])
+    }
+
+    /// Renvoie, en hexadécimal, tous les hashes indexés.
+    pub fn hashes(&self) -> impl Iterator<Item = String> + '_ {
+        self.hashes.iter().map(hex::encode)
+    }
+
+    /// Renvoie, en hexadécimal, les hashes indexés qui commencent par `prefix`.
+    ///
+    /// # Paramètres
+    /// - `prefix` : préfixe hexadécimal d'au moins deux caractères.
+    pub fn find_by_prefix(&self, prefix: &str) -> Vec<String> {
+        let Some(first) = prefix.get(..2).and_then(|first| u8::from_str_radix(first, 16).ok()) else {
+            return vec![];
+        };
+        let first = first as usize;
+        let start = if first == 0 { 0 } else { self.fanout[first - 1] as usize };
+        let end = self.fanout[first] as usize;
+
+        self.hashes[start..end].iter()
+            .map(hex::encode)
+            .filter(|hash| hash.starts_with(prefix))
+            .collect()
+    }
+}
+
+fn read_u32(data: &[u8], position: usize) -> u32 {
+    u32::from_be_bytes(data[position..position + 4].try_into().unwrap())
+}
@@ -0,0 +1,217 @@
+use crate::objects::pack::delta;
+use crate::objects::pack::pack_file::{self, OBJ_OFS_DELTA, OBJ_REF_DELTA, PACK_SIGNATURE, PACK_VERSION};
+use crate::objects::pack::pack_writer::{self, IndexEntry};
+use anyhow::{anyhow, Result};
+use flate2::Crc;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+/// Base d'une entrée delta du pack.
+enum DeltaBase {
+    None,
+    Offset(u64),
+    Hash([u8; 20]),
+}
+
+/// Entrée lue séquentiellement dans le pack, avant résolution des deltas.
+///
+/// Seule la position des données compressées est conservée : elles sont décompressées à
+/// nouveau au moment de la résolution.
+struct ParsedEntry {
+    offset: u64,
+    object_type: u8,
+    base: DeltaBase,
+    data_offset: u64,
+    size: usize,
+    crc: u32,
+}
+
+/// Deltas en attente de leur base, indexés par offset ou par hash de la base.
+#[derive(Default)]
+struct DeltaChildren {
+    by_offset: HashMap<u64, Vec<usize>>,
+    by_hash: HashMap<[u8; 20], Vec<usize>>,
+}
+
+impl DeltaChildren {
+    /// Retire et renvoie les deltas dont la base est l'objet situé à `offset` ou de hash `hash`.
+    ///
+    /// Un objet présent deux fois dans le pack ne sert ainsi de base qu'une fois.
+    fn take(&mut self, offset: u64, hash: &[u8; 20]) -> Vec<usize> {
+        let mut children = self.by_offset.remove(&offset).unwrap_or_default();
+        children.extend(self.by_hash.remove(hash).unwrap_or_default());
+        children
+    }
+}
+
+/// Objet reconstruit dont les deltas enfants restent à résoudre.
+struct ResolvedBase {
+    kind: &'static str,
+    content: Vec<u8>,
+    children: Vec<usize>,
+    next_child: usize,
+}
+
+/// Construit l'index `.idx` d'un packfile reçu (équivalent de `git index-pack`).
+pub struct PackIndexer;
+
+impl PackIndexer {
+    /// Vérifie le pack `pack_path`, résout ses deltas puis l'installe dans `pack_dir`
+    /// sous le nom `pack-<checksum>` accompagné de son index.
+    ///
+    /// # Renvoie
+    /// - Le nom de base du pack installé et son nombre d'objets.
+    pub fn index_pack(&self, pack_path: &Path, pack_dir: &Path) -> Result<(String, usize)> {
+        let data = fs::read(pack_path)?;
+        if data.len() < 32 || &data[..4] != PACK_SIGNATURE || u32::from_be_bytes(data[4..8].try_into().unwrap()) != PACK_VERSION {
+            return Err(anyhow!("Invalid packfile: bad header"));
+        }
+
+        let (content, checksum) = data.split_at(data.len() - 20);
+        if Sha1::digest(content).as_slice() != checksum {
+            return Err(anyhow!("Invalid packfile: checksum mismatch"));
+        }
+
+        let count = u32::from_be_bytes(data[8..12].try_into().unwrap()) as usize;
+        let entries = self.parse_entries(content, count)?;
+        let hashes = self.resolve_entries(content, &entries)?;
+
+        let index_entries = entries.iter()
+            .zip(hashes)
+            .map(|(entry, hash)| IndexEntry { hash, offset: entry.offset, crc: entry.crc })
+            .collect();
+
+        fs::create_dir_all(pack_dir)?;
+        let name = format!("pack-{}", hex::encode(checksum));
+        fs::rename(pack_path, pack_dir.join(format!("{}.pack", name)))?;
+        pack_writer::write_index(&pack_dir.join(format!("{}.idx", name)), index_entries, checksum)?;
+
+        Ok((name, count))
+    }
+
+    /// Lit les en-têtes et décompresse les données de chaque entrée du pack.
+    fn parse_entries(&self, content: &[u8], count: usize) -> Result<Vec<ParsedEntry>> {
+        let mut cursor = Cursor::new(content);
+        cursor.set_position(12);
+
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            let offset = cursor.position();
+            let (object_type, size) = pack_file::read_entry_header(&mut cursor)?;
+
+            let base = match object_type {
+                OBJ_OFS_DELTA => {
+                    let distance = pack_file::read_offset_delta(&mut cursor)?;
+                    DeltaBase::Offset(offset.checked_sub(distance)
+                        .ok_or_else(|| anyhow!("Invalid packfile: delta base before start of pack"))?)
+                }
+                OBJ_REF_DELTA => {
+                    let mut hash = [0u8; 20];
+                    cursor.read_exact(&mut hash)?;
+                    DeltaBase::Hash(hash)
+                }
+                _ => DeltaBase::None,
+            };
+
+            // Décompressé une première fois pour vérifier la taille et trouver l'entrée suivante
+            let data_offset = cursor.position();
+            pack_file::inflate(&mut cursor, size)?;
+
+            let mut crc = Crc::new();
+            crc.update(&content[offset as usize..cursor.position() as usize]);
+
+            entries.push(ParsedEntry { offset, object_type, base, data_offset, size, crc: crc.sum() });
+        }
+
+        if cursor.position() as usize != content.len() {
+            return Err(anyhow!("Invalid packfile: trailing data after last object"));
+        }
+
+        Ok(entries)
+    }
+
+    /// Reconstruit chaque objet (en appliquant les deltas) et calcule son hash.
+    ///
+    /// Les deltas sont résolus en profondeur depuis chaque objet non deltifié : seule la
+    /// chaîne de bases en cours de résolution est gardée en mémoire, et le contenu d'une base
+    /// est libéré dès que tous ses deltas enfants ont été résolus.
+    fn resolve_entries(&self, content: &[u8], entries: &[ParsedEntry]) -> Result<Vec<[u8; 20]>> {
+        let positions_by_offset: HashMap<u64, usize> = entries.iter()
+            .enumerate()
+            .map(|(position, entry)| (entry.offset, position))
+            .collect();
+
+        let mut children = DeltaChildren::default();
+        for (position, entry) in entries.iter().enumerate() {
+            match entry.base {
+                DeltaBase::None => {}
+                DeltaBase::Offset(base_offset) => {
+                    if !positions_by_offset.contains_key(&base_offset) {
+                        return Err(anyhow!("Invalid packfile: no object at offset {}", base_offset));
+                    }
+                    children.by_offset.entry(base_offset).or_default().push(position);
+                }
+                DeltaBase::Hash(base_hash) => children.by_hash.entry(base_hash).or_default().push(position),
+            }
+        }
+
+        let mut hashes: Vec<Option<[u8; 20]>> = vec![None; entries.len()];
+        for (position, entry) in entries.iter().enumerate() {
+            if !matches!(entry.base, DeltaBase::None) {
+                continue;
+            }
+
+            let kind = pack_file::kind_name(entry.object_type)?;
+            let data = self.inflate_entry(content, entry)?;
+            let (hash, base) = self.resolve_base(kind, data, entry.offset, &mut children);
+            hashes[position] = Some(hash);
+            let mut stack = vec![base];
+
+            while let Some(base) = stack.last_mut() {
+                let Some(&child) = base.children.get(base.next_child) else {
+                    // Tous les enfants sont résolus : le contenu de la base n'est plus utile
+                    stack.pop();
+                    continue;
+                };
+                base.next_child += 1;
+
+                let kind = base.kind;
+                let delta = self.inflate_entry(content, &entries[child])?;
+                let child_content = delta::apply_delta(&base.content, &delta)?;
+                let (hash, resolved) = self.resolve_base(kind, child_content, entries[child].offset, &mut children);
+                hashes[child] = Some(hash);
+                stack.push(resolved);
+            }
+        }
+
+        let unresolved = hashes.iter().filter(|hash| hash.is_none()).count();
+        if unresolved > 0 {
+            return Err(anyhow!("Invalid packfile: {} deltas reference missing base objects", unresolved));
+        }
+        Ok(hashes.into_iter().map(Option::unwrap).collect())
+    }
+
+    /// Calcule le hash d'un objet reconstruit et rassemble les deltas qui en dépendent.
+    ///
+    /// # Paramètres
+    /// - `offset` : position de l'entrée de l'objet dans le pack.
+    fn resolve_base(&self, kind: &'static str, content: Vec<u8>, offset: u64, children: &mut DeltaChildren) -> ([u8; 20], ResolvedBase) {
+        let mut hasher = Sha1::new();
+        hasher.update(format!("{} {}\0", kind, content.len()));
+        hasher.update(&content);
+        let hash: [u8; 20] = hasher.finalize().into();
+
+        let children = children.take(offset, &hash);
+        (hash, ResolvedBase { kind, content, children, next_child: 0 })
+    }
+
+    /// Décompresse à nouveau les données d'une entrée.
+    fn inflate_entry(&self, content: &[u8], entry: &ParsedEntry) -> Result<Vec<u8>> {
+        let mut cursor = Cursor::new(content);
+        cursor.set_position(entry.data_offset);
+        pack_file::inflate(&mut cursor, entry.size)
+    }
+}
@@ -0,0 +1,298 @@
+use crate::objects::objet_reader::RawObject;
+use crate::objects::pack::delta;
+use crate::objects::pack::pack_file::{self, OBJ_OFS_DELTA, PACK_SIGNATURE, PACK_VERSION};
+use anyhow::{anyhow, Result};
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Crc};
+use sha1::{Digest, Sha1};
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const IDX_SIGNATURE: &[u8; 4] = b"\xfftOc";
+const IDX_VERSION: u32 = 2;
+/// Nombre d'objets précédents examinés comme base potentielle d'un delta.
+const DELTA_WINDOW: usize = 10;
+/// Longueur maximale d'une chaîne de deltas.
+const MAX_DELTA_DEPTH: usize = 50;
+/// Taille minimale pour qu'un objet soit candidat à la compression delta.
+const MIN_DELTA_SIZE: usize = 50;
+
+/// Objet à inclure dans un packfile, décrit sans son contenu, lu au moment de l'écriture.
+pub struct PackInput {
+    pub hash: String,
+    pub kind: String,
+    pub size: u64,
+    /// Chemin sous lequel l'objet a été rencontré, utilisé pour regrouper les versions d'un même fichier.
+    pub path_hint: String,
+}
+
+/// Objet récemment écrit, conservé comme base potentielle des deltas suivants.
+struct WindowEntry {
+    position: usize,
+    object: RawObject,
+    depth: usize,
+}
+
+/// Entrée écrite dans le packfile, conservée pour produire l'index.
+pub(crate) struct IndexEntry {
+    pub hash: [u8; 20],
+    pub offset: u64,
+    pub crc: u32,
+}
+
+pub struct PackWriter {
+    /// Niveau de compression des entrées.
+    compression: Compression,
+}
+
+impl PackWriter {
+    pub fn new(compression: Compression) -> Self {
+        PackWriter { compression }
+    }
+
+    /// Écrit un packfile et son index v2 dans `pack_dir`.
+    ///
+    /// Le contenu des objets est lu un à un au fil de l'écriture : seuls ceux de la fenêtre
+    /// de recherche des deltas sont gardés en mémoire.
+    ///
+    /// # Paramètres
+    /// - `objects` : objets à écrire.
+    /// - `read_object` : fonction qui lit un objet à partir de son hash.
+    ///
+    /// # Renvoie
+    /// - Le nom de base du pack (`pack-<checksum>`).
+    pub fn write_pack(
+        &self,
+        pack_dir: &Path,
+        objects: &[PackInput],
+        read_object: &dyn Fn(&str) -> Result<RawObject>,
+    ) -> Result<String> {
+        fs::create_dir_all(pack_dir)?;
+
+        let order = self.sort_for_deltas(objects);
+
+        let tmp_pack_path = pack_dir.join(format!("tmp_pack_{}", std::process::id()));
+        let mut writer = HashingWriter::new(BufWriter::new(File::create(&tmp_pack_path)?));
+
+        writer.write_all(PACK_SIGNATURE)?;
+        writer.write_all(&PACK_VERSION.to_be_bytes())?;
+        writer.write_all(&(objects.len() as u32).to_be_bytes())?;
+
+        let mut offsets = vec![0u64; objects.len()];
+        let mut index_entries = Vec::with_capacity(objects.len());
+        let mut window: VecDeque<WindowEntry> = VecDeque::with_capacity(DELTA_WINDOW + 1);
+
+        for &position in &order {
+            let input = &objects[position];
+            let object = read_object(&input.hash)?;
+            let offset = writer.count;
+            offsets[position] = offset;
+
+            let mut entry = Vec::new();
+            let mut depth = 0;
+            match self.find_best_delta(&window, &object) {
+                Some((base, delta)) => {
+                    depth = base.depth + 1;
+                    write_entry_header(&mut entry, OBJ_OFS_DELTA, delta.len());
+                    write_offset_delta(&mut entry, offset - offsets[base.position]);
+                    entry.extend_from_slice(&deflate(&delta, self.compression)?);
+                }
+                None => {
+                    write_entry_header(&mut entry, kind_code(&object.kind)?, object.content.len());
+                    entry.extend_from_slice(&deflate(&object.content, self.compression)?);
+                }
+            }
+
+            let mut crc = Crc::new();
+            crc.update(&entry);
+            writer.write_all(&entry)?;
+
+            index_entries.push(IndexEntry {
+                hash: hex::decode(&input.hash)?
+                    .try_into()
+                    .map_err(|_| anyhow!("Invalid object identifier: {}", input.hash))?,
+                offset,
+                crc: crc.sum(),
+            });
+
+            window.push_back(WindowEntry { position, object, depth });
+            if window.len() > DELTA_WINDOW {
+                window.pop_front();
+            }
+        }
+
+        // Le pack se termine par le SHA-1 de tout son contenu
+        let checksum = writer.hasher.clone().finalize();
+        writer.inner.write_all(&checksum)?;
+        writer.inner.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+
+        // Le pack est mis en place avant son index : un `.idx` sans `.pack` rendrait
+        // le répertoire illisible
+        let name = format!("pack-{}", hex::encode(checksum));
+        fs::rename(&tmp_pack_path, pack_dir.join(format!("{}.pack", name)))?;
+        write_index(&pack_dir.join(format!("{}.idx", name)), index_entries, &checksum)?;
+
+        Ok(name)
+    }
+
+    /// Trie les objets par type, nom de fichier puis taille décroissante, pour que les
+    /// versions successives d'un même fichier se retrouvent dans la même fenêtre.
+    fn sort_for_deltas(&self, objects: &[PackInput]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..objects.len()).collect();
+        order.sort_by_key(|&position| {
+            let input = &objects[position];
+            let file_name = input.path_hint.rsplit('/').next().unwrap_or_default();
+            (input.kind.as_str(), file_name, Reverse(input.size))
+        });
+        order
+    }
+
+    /// Cherche le meilleur delta de l'objet parmi les objets de la fenêtre.
+    ///
+    /// # Renvoie
+    /// - La base retenue et le delta, ou `None` si aucun delta ne fait gagner au moins
+    ///   la moitié de la taille de l'objet.
+    fn find_best_delta<'a>(&self, window: &'a VecDeque<WindowEntry>, target: &RawObject) -> Option<(&'a WindowEntry, Vec<u8>)> {
+        if target.content.len() < MIN_DELTA_SIZE {
+            return None;
+        }
+
+        let mut best: Option<(&WindowEntry, Vec<u8>)> = None;
+        let mut best_size = target.content.len() / 2;
+        for base in window.iter().rev() {
+            if base.object.kind != target.kind || base.depth >= MAX_DELTA_DEPTH {
+                continue;
+            }
+
+            let delta = delta::create_delta(&base.object.content, &target.content);
+            if delta.len() < best_size {
+                best_size = delta.len();
+                best = Some((base, delta));
+            }
+        }
+        best
+    }
+}
+
+/// Écrit l'index `.idx` version 2 correspondant au pack, une fois le pack en place.
+pub(crate) fn write_index(path: &Path, mut entries: Vec<IndexEntry>, pack_checksum: &[u8]) -> Result<()> {
+    entries.sort_by_key(|entry| entry.hash);
+
+    let mut data = Vec::new();
+    data.extend_from_slice(IDX_SIGNATURE);
+    data.extend_from_slice(&IDX_VERSION.to_be_bytes());
+
+    // Table de répartition par premier octet du hash
+    let mut fanout = [0u32; 256];
+    for entry in &entries {
+        fanout[entry.hash[0] as usize] += 1;
+    }
+    let mut total = 0;
+    for count in fanout.iter_mut() {
+        total += *count;
+        *count = total;
+        data.extend_from_slice(&count.to_be_bytes());
+    }
+
+    for entry in &entries {
+        data.extend_from_slice(&entry.hash);
+    }
+    for entry in &entries {
+        data.extend_from_slice(&entry.crc.to_be_bytes());
+    }
+
+    // Les offsets au-delà de 2 Gio sont déportés dans une table 64 bits
+    let mut large_offsets = Vec::new();
+    for entry in &entries {
+        if entry.offset < 0x8000_0000 {
+            data.extend_from_slice(&(entry.offset as u32).to_be_bytes());
+        } else {
+            data.extend_from_slice(&(0x8000_0000 | large_offsets.len() as u32).to_be_bytes());
+            large_offsets.push(entry.offset);
+        }
+    }
+    for offset in large_offsets {
+        data.extend_from_slice(&offset.to_be_bytes());
+    }
+
+    data.extend_from_slice(pack_checksum);
+    let checksum = Sha1::digest(&data);
+    data.extend_from_slice(&checksum);
+
+    // Écrit sous un nom temporaire puis renommé, comme les objets loose
+    let tmp_path = path.with_file_name(format!("tmp_idx_{}", std::process::id()));
+    let result = File::create(&tmp_path)
+        .and_then(|mut file| file.write_all(&data).and_then(|_| file.sync_all()))
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    Ok(result?)
+}
+
+/// Convertit un nom de type d'objet en code de type de pack.
+fn kind_code(kind: &str) -> Result<u8> {
+    (1..=4)
+        .find(|&code| pack_file::kind_name(code).is_ok_and(|name| name == kind))
+        .ok_or_else(|| anyhow!("Unsupported object type: {}", kind))
+}
+
+/// Encode l'en-tête d'une entrée : type sur 3 bits puis taille en varint.
+fn write_entry_header(out: &mut Vec<u8>, object_type: u8, size: usize) {
+    let mut byte = (object_type << 4) | (size & 0x0f) as u8;
+    let mut size = size >> 4;
+    while size > 0 {
+        out.push(byte | 0x80);
+        byte = (size & 0x7f) as u8;
+        size >>= 7;
+    }
+    out.push(byte);
+}
+
+/// Encode la distance vers la base d'un OFS_DELTA.
+fn write_offset_delta(out: &mut Vec<u8>, mut distance: u64) {
+    let mut bytes = vec![(distance & 0x7f) as u8];
+    distance >>= 7;
+    while distance > 0 {
+        distance -= 1;
+        bytes.push(0x80 | (distance & 0x7f) as u8);
+        distance >>= 7;
+    }
+    bytes.reverse();
+    out.extend_from_slice(&bytes);
+}
+
+fn deflate(data: &[u8], compression: Compression) -> Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), compression);
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Écrivain qui calcule le SHA-1 et compte les octets au fil de l'écriture.
+struct HashingWriter<W: Write> {
+    inner: W,
+    hasher: Sha1,
+    count: u64,
+}
+
+impl<W: Write> HashingWriter<W> {
+    fn new(inner: W) -> Self {
+        HashingWriter { inner, hasher: Sha1::new(), count: 0 }
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
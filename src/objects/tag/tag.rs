@@ -0,0 +1,167 @@
+use crate::objects::commit::signature::Signature;
+use crate::objects::object_base::{GitObject, GitObjectBase};
+use crate::objects::utils;
+use anyhow::{anyhow, Result};
+
+pub(crate) const HEADER_PREFIX: &str = "tag";
+const OBJECT_PREFIX: &str = "object";
+const TYPE_PREFIX: &str = "type";
+const TAGGER_PREFIX: &str = "tagger";
+
+/// Débuts de bloc qui marquent la signature ajoutée à la fin du message (GPG, SSH, X.509).
+const SIGNATURE_MARKERS: [&[u8]; 3] = [
+    b"-----BEGIN PGP SIGNATURE-----",
+    b"-----BEGIN SSH SIGNATURE-----",
+    b"-----BEGIN SIGNED MESSAGE-----",
+];
+
+#[derive(Debug)]
+pub struct Tag {
+    pub base: GitObjectBase,
+    /// Hash de l'objet étiqueté.
+    pub object_hash: String,
+    /// Type de l'objet étiqueté (`commit`, `tree`, `blob` ou `tag`).
+    pub object_type: String,
+    /// Nom du tag, sans le préfixe `refs/tags/`.
+    pub name: String,
+    /// Absent sur certains tags très anciens.
+    pub tagger: Option<Signature>,
+    /// Message brut, saut de ligne final compris.
+    pub message: Vec<u8>,
+    /// Signature éventuelle, stockée par git à la suite du message.
+    pub signature: Option<Vec<u8>>,
+}
+
+impl Tag {
+    pub(crate) fn new(object_hash: &str, object_type: &str, name: &str, message: &str, tagger: Signature) -> Result<Tag> {
+        // Comme git, le message se termine toujours par un saut de ligne
+        let mut message = message.as_bytes().to_vec();
+        if !message.ends_with(b"\n") {
+            message.push(b'\n');
+        }
+
+        let mut tag = Tag {
+            base: GitObjectBase { hash: String::new() },
+            object_hash: object_hash.to_owned(),
+            object_type: object_type.to_owned(),
+            name: name.to_owned(),
+            tagger: Some(tagger),
+            message,
+            signature: None,
+        };
+
+        tag.base.hash = utils::compute_sha1_from_bytes(&tag.compute_object_data());
+
+        Ok(tag)
+    }
+
+    /// Sérialise le contenu de l'objet tag, sans l'en-tête.
+    pub(crate) fn compute_content(&self) -> Vec<u8> {
+        let mut content = Vec::new();
+
+        content.extend_from_slice(format!("{} {}\n", OBJECT_PREFIX, self.object_hash).as_bytes());
+        content.extend_from_slice(format!("{} {}\n", TYPE_PREFIX, self.object_type).as_bytes());
+        content.extend_from_slice(format!("{} {}\n", HEADER_PREFIX, self.name).as_bytes());
+        if let Some(tagger) = &self.tagger {
+            content.extend_from_slice(format!("{} {}\n", TAGGER_PREFIX, tagger).as_bytes());
+        }
+
+        // Ligne vide entre les en-têtes et le message
+        content.push(b'\n');
+        content.extend_from_slice(&self.message);
+        if let Some(signature) = &self.signature {
+            content.extend_from_slice(signature);
+        }
+
+        content
+    }
+}
+
+impl GitObject for Tag {
+    fn get_hash(&self) -> &str {
+        &self.base.hash
+    }
+
+    fn get_header_prefix(&self) -> &'static str {
+        HEADER_PREFIX
+    }
+
+    fn compute_size(&self) -> usize {
+        self.compute_content().len()
+    }
+
+    fn compute_object_data(&self) -> Vec<u8> {
+        let content = self.compute_content();
+        let header = format!("{} {}\0", HEADER_PREFIX, content.len());
+
+        let mut result = Vec::with_capacity(header.len() + content.len());
+        result.extend_from_slice(header.as_bytes());
+        result.extend_from_slice(&content);
+
+        result
+    }
+
+    fn from_object_file(hash: &str, content: &[u8]) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut object_hash = None;
+        let mut object_type = None;
+        let mut name = None;
+        let mut tagger = None;
+        let mut body: &[u8] = &[];
+        let mut idx = 0;
+
+        while idx < content.len() {
+            let line_end = content[idx..].iter()
+                .position(|&c| c == b'\n')
+                .map(|position| idx + position)
+                .unwrap_or(content.len());
+            let line = String::from_utf8_lossy(&content[idx..line_end]);
+            idx = line_end + 1; // Avancer à la ligne suivante
+
+            if line.is_empty() {
+                // Passage aux données du message après la première ligne vide
+                body = content.get(idx..).unwrap_or_default();
+                break;
+            }
+
+            let (key, value) = line.split_once(' ')
+                .ok_or_else(|| anyhow!("Invalid tag object {}: malformed header '{}'", hash, line))?;
+            match key {
+                OBJECT_PREFIX => object_hash = Some(value.to_string()),
+                TYPE_PREFIX => object_type = Some(value.to_string()),
+                HEADER_PREFIX => name = Some(value.to_string()),
+                TAGGER_PREFIX => tagger = Some(Signature::parse(value)?),
+                _ => return Err(anyhow!("Unknown tag object field: {}", key)),
+            }
+        }
+
+        // La signature commence à la dernière ligne marquant un bloc signé
+        let signature_start = body.split_inclusive(|&b| b == b'\n')
+            .scan(0, |offset, line| {
+                let start = *offset;
+                *offset += line.len();
+                Some((start, line))
+            })
+            .filter(|(_, line)| SIGNATURE_MARKERS.iter().any(|marker| line.starts_with(marker)))
+            .map(|(start, _)| start)
+            .last();
+        let (message, signature) = match signature_start {
+            Some(start) => (body[..start].to_vec(), Some(body[start..].to_vec())),
+            None => (body.to_vec(), None),
+        };
+
+        Ok(Tag {
+            base: GitObjectBase {
+                hash: hash.to_string(),
+            },
+            object_hash: object_hash.ok_or_else(|| anyhow!("Invalid tag object {}: missing object", hash))?,
+            object_type: object_type.ok_or_else(|| anyhow!("Invalid tag object {}: missing type", hash))?,
+            name: name.ok_or_else(|| anyhow!("Invalid tag object {}: missing tag name", hash))?,
+            tagger,
+            message,
+            signature,
+        })
+    }
+}
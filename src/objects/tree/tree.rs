@@ -2,6 +2,8 @@ use crate::objects::object_base::{GitObject, GitObjectBase};
 use crate::objects::object_kind::GitObjectKind;
 use crate::objects::{object_manager, utils};
 use anyhow::{anyhow, Result};
+use std::cmp::Ordering;
+use std::collections::HashSet;
 
 pub(crate) const HEADER_PREFIX: &str = "tree";
 
@@ -13,15 +15,43 @@ pub struct Tree {
 #[derive(Debug)]
 pub struct TreeEntry {
     pub mode: String,
-    pub name: String,
+    /// Nom brut : git n'impose aucun encodage aux noms de fichiers.
+    pub name: Vec<u8>,
     pub hash: String,
     pub object: Option<GitObjectKind>,
 }
 
+impl TreeEntry {
+    /// Type de l'objet désigné, déduit du mode : `tree`, `commit` (sous-module) ou `blob`.
+    pub fn kind(&self) -> &'static str {
+        match self.mode.as_str() {
+            "40000" => HEADER_PREFIX,
+            "160000" => "commit",
+            _ => "blob",
+        }
+    }
+
+    /// Mode sur six chiffres, tel qu'affiché par git (`040000` pour un arbre).
+    pub fn mode_padded(&self) -> String {
+        format!("{:0>6}", self.mode)
+    }
+
+    /// Compare deux entrées selon l'ordre canonique de git : le nom d'un sous-arbre est
+    /// comparé comme s'il se terminait par `/` (`foo.txt` précède donc le répertoire `foo`).
+    pub fn canonical_cmp(&self, other: &TreeEntry) -> Ordering {
+        self.sort_key().cmp(other.sort_key())
+    }
+
+    fn sort_key(&self) -> impl Iterator<Item = u8> + '_ {
+        let suffix = if self.mode == "40000" { Some(b'/') } else { None };
+        self.name.iter().copied().chain(suffix)
+    }
+}
+
 impl Tree {
     pub(crate) fn new(mut entries: Vec<TreeEntry>) -> Result<Self> {
-        // Trier les entrées par ordre alphabétique du nom
-        entries.sort_by_key(|entry| entry.name.clone());
+        // Trier les entrées dans l'ordre canonique de git
+        entries.sort_by(|a, b| a.canonical_cmp(b));
 
         // Créer un buffer pour stocker les données de l'objet tree
         let mut entries_data = Vec::new();
@@ -32,7 +62,7 @@ impl Tree {
             entries_data.push(b' ');
 
             // Ajouter le nom du fichier, suivi d'un byte nul
-            entries_data.extend_from_slice(entry.name.as_bytes());
+            entries_data.extend_from_slice(&entry.name);
             entries_data.push(b'\0');
 
             // Ajouter le hash décodé (sous forme de bytes)
@@ -62,6 +92,43 @@ impl Tree {
         })
     }
 
+    /// Lit un arbre et vérifie sa validité (voir [`Tree::validate`]).
+    pub(crate) fn from_object_file_checked(hash: &str, content: &[u8]) -> Result<Tree> {
+        let tree = Tree::from_object_file(hash, content)?;
+        tree.validate()?;
+        Ok(tree)
+    }
+
+    /// Vérifie qu'un arbre lu depuis le dépôt est bien formé, comme `git fsck` :
+    /// entrées dans l'ordre canonique, sans doublon, et sans nom vide, `.`, `..`, `.git`
+    /// (quelle que soit la casse) ni `/`.
+    pub fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            let problem = match entry.name.as_slice() {
+                b"" => Some("contains empty pathname"),
+                b"." => Some("contains '.'"),
+                b".." => Some("contains '..'"),
+                name if name.eq_ignore_ascii_case(b".git") => Some("contains '.git'"),
+                name if name.contains(&b'/') => Some("contains full pathnames"),
+                _ => None,
+            };
+            if let Some(problem) = problem {
+                return Err(anyhow!("Invalid tree {}: {}", self.base.hash, problem));
+            }
+
+            // Un fichier et un répertoire du même nom sont aussi des doublons
+            if !names.insert(entry.name.as_slice()) {
+                return Err(anyhow!("Invalid tree {}: contains duplicate file entries", self.base.hash));
+            }
+            if i > 0 && self.entries[i - 1].canonical_cmp(entry) != Ordering::Less {
+                return Err(anyhow!("Invalid tree {}: not properly sorted", self.base.hash));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl GitObject for Tree {
@@ -75,9 +142,9 @@ impl GitObject for Tree {
 
     fn compute_size(&self) -> usize {
         self.entries.iter().map(|entry| {
-            entry.mode.as_bytes().len()          // Taille du mode (en ASCII)
+            entry.mode.len()          // Taille du mode (en ASCII)
                 + 1                       // Espace séparateur entre le mode et le nom
-                + entry.name.as_bytes().len()        // Taille du nom
+                + entry.name.len()        // Taille du nom
                 + 1                       // Séparateur NUL '\0'
                 + 20                      // Taille du hash en binaire (SHA-1 est 20 octets)
         }).sum::<usize>()
@@ -89,17 +156,17 @@ impl GitObject for Tree {
 
         let mut result = Vec::with_capacity(header.len() + self.compute_size());
 
-        result.extend_from_slice(&header);
+        result.extend_from_slice(header);
 
         // Ajouter chaque entrée du tree
         self.entries.iter().for_each(|entry| {
             // Ajouter le mode (comme "100644" ou "040000")
-            result.extend_from_slice(&entry.mode.as_bytes());
+            result.extend_from_slice(entry.mode.as_bytes());
             // Ajouter un espace séparateur
             result.push(b' ');
 
             // Ajouter le nom du fichier ou du répertoire
-            result.extend_from_slice(entry.name.as_bytes());
+            result.extend_from_slice(&entry.name);
 
             // Ajouter le séparateur NUL '\0'
             result.push(0);
@@ -127,7 +194,7 @@ impl GitObject for Tree {
             let name_end = content[idx..].iter()
                 .position(|&c| c == object_manager::OBJECT_CONTENT_SEPARATOR)
                 .ok_or_else(|| anyhow!("Invalid tree object: missing NUL after name"))?;
-            let name = std::str::from_utf8(&content[idx..idx + name_end])?.to_string();
+            let name = content[idx..idx + name_end].to_vec();
             idx += name_end+1; // Passer le séparateur NUL
 
             // Étape 3 : Lire le hash (20 octets binaires)
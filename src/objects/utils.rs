@@ -1,11 +1,17 @@
-use std::path::PathBuf;
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
 use anyhow::anyhow;
 use sha1::{Digest, Sha1};
+use crate::objects::blob::blob;
 use crate::objects::object_manager;
 
-/// Convertit un hash Git en un chemin vers le fichier objet dans `.git/objects`.
+/// Taille des blocs lus lors du hachage d'un flux.
+const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Convertit un hash Git en un chemin vers le fichier objet dans le répertoire `objects`.
 ///
 /// # Paramètres
+/// - `objects_dir`: Le répertoire `objects` du dépôt.
 /// - `hash`: L'identifiant SHA-1 de l'objet.
 ///
 /// # Renvoie
@@ -13,14 +19,14 @@ use crate::objects::object_manager;
 ///
 /// # Erreurs
 /// - Retourne une erreur si le hash est de longueur incorrecte.
-pub(crate) fn hash_to_object_path(hash: &str) -> anyhow::Result<PathBuf> {
+pub(crate) fn hash_to_object_path(objects_dir: &Path, hash: &str) -> anyhow::Result<PathBuf> {
     if hash.len() != object_manager::OBJECT_HASH_SIZE {
         return Err(anyhow!("Invalid object identifier: expected {} characters, got {}", object_manager::OBJECT_HASH_SIZE, hash.len()));
     }
 
     // Sépare le hash en deux parties : le répertoire (les deux premiers caractères) et le fichier
     let (dir, file) = hash.split_at(2);
-    Ok(PathBuf::from(object_manager::GIT_OBJECTS_DIR).join(dir).join(file))
+    Ok(objects_dir.join(dir).join(file))
 }
 
 /// Calcule le hash SHA-1 pour les données spécifiées.
@@ -30,12 +36,44 @@ pub(crate) fn hash_to_object_path(hash: &str) -> anyhow::Result<PathBuf> {
 ///
 /// # Renvoie
 /// - Un hash SHA-1 sous forme de chaîne hexadécimale.
-pub(crate) fn compute_sha1(data: &str) -> String {
-    compute_sha1_from_bytes(data.as_bytes())
-}
-
 pub(crate) fn compute_sha1_from_bytes(data: &[u8]) -> String {
     let mut hasher = Sha1::new();
     hasher.update(data);
     hex::encode(hasher.finalize())
 }
+
+/// Calcule le hash d'un blob lu en flux, en recopiant au passage l'objet (en-tête compris) dans `out`.
+///
+/// # Paramètres
+/// - `content` : lecteur du contenu du blob.
+/// - `size` : taille annoncée dans l'en-tête ; le contenu lu doit avoir exactement cette taille.
+/// - `out` : destination de l'objet non compressé (`io::sink()` pour un simple calcul de hash).
+///
+/// # Erreurs
+/// - Retourne une erreur si le contenu n'a pas la taille annoncée, par exemple si le fichier
+///   a été modifié pendant la lecture.
+pub(crate) fn hash_blob_stream<R: Read, W: Write>(mut content: R, size: u64, out: &mut W) -> anyhow::Result<String> {
+    let header = format!("{} {}\0", blob::HEADER_PREFIX, size);
+    let mut hasher = Sha1::new();
+    hasher.update(header.as_bytes());
+    out.write_all(header.as_bytes())?;
+
+    let mut buffer = vec![0u8; STREAM_BUFFER_SIZE];
+    let mut total = 0u64;
+    loop {
+        let read = match content.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        hasher.update(&buffer[..read]);
+        out.write_all(&buffer[..read])?;
+        total += read as u64;
+    }
+
+    if total != size {
+        return Err(anyhow!("Content size changed while reading: expected {} bytes, read {}", size, total));
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
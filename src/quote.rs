@@ -0,0 +1,31 @@
+/// Met un chemin entre guillemets à la manière de git (`core.quotePath`) s'il contient
+/// des caractères de contrôle, des guillemets, des barres obliques inverses ou des octets non ASCII.
+///
+/// # Renvoie
+/// - Le chemin tel quel s'il ne nécessite pas d'échappement, sinon `"..."` avec des
+///   séquences d'échappement C et des octets en octal.
+pub(crate) fn quote_path(path: &[u8]) -> String {
+    let needs_quoting = path.iter().any(|&b| b < 0x20 || b == b'"' || b == b'\\' || b >= 0x7f);
+    if !needs_quoting {
+        return String::from_utf8_lossy(path).into_owned();
+    }
+
+    let mut quoted = String::from("\"");
+    for &b in path {
+        match b {
+            b'"' => quoted.push_str("\\\""),
+            b'\\' => quoted.push_str("\\\\"),
+            b'\t' => quoted.push_str("\\t"),
+            b'\n' => quoted.push_str("\\n"),
+            b'\r' => quoted.push_str("\\r"),
+            0x07 => quoted.push_str("\\a"),
+            0x08 => quoted.push_str("\\b"),
+            0x0b => quoted.push_str("\\v"),
+            0x0c => quoted.push_str("\\f"),
+            b if !(0x20..0x7f).contains(&b) => quoted.push_str(&format!("\\{:03o}", b)),
+            b => quoted.push(b as char),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,476 @@
+use crate::config::config_set::ConfigSet;
+use crate::objects::commit::signature::{Signature, SignatureRole};
+use crate::objects::object_manager;
+use crate::repository;
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+pub const HEAD: &str = "HEAD";
+const SYMBOLIC_REF_PREFIX: &str = "ref: ";
+const PACKED_REFS_FILE: &str = "packed-refs";
+const LOCK_EXTENSION: &str = ".lock";
+/// Répertoire des journaux de références (reflogs), relatif au répertoire git.
+const LOGS_DIR: &str = "logs";
+/// Références dont le journal est créé automatiquement avec `core.logAllRefUpdates=true`.
+const LOGGED_REF_PREFIXES: [&str; 3] = ["refs/heads/", "refs/remotes/", "refs/notes/"];
+/// Profondeur maximale d'une chaîne de références symboliques.
+const MAX_SYMBOLIC_DEPTH: usize = 5;
+/// Valeur attendue d'une référence qui ne doit pas encore exister.
+pub const NULL_HASH: &str = "0000000000000000000000000000000000000000";
+
+/// Contenu d'une référence : un hash ou le nom d'une autre référence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefValue {
+    Direct(String),
+    Symbolic(String),
+}
+
+/// Lit une référence sans suivre les références symboliques, en loose puis dans `packed-refs`.
+pub fn read_ref(name: &str) -> Result<Option<RefValue>> {
+    read_ref_in(repository::current()?.git_dir(), name)
+}
+
+fn read_ref_in(git_dir: &Path, name: &str) -> Result<Option<RefValue>> {
+    // Un nom invalide (`refs/../../x`) pourrait désigner un fichier hors du répertoire git
+    check_ref_format(name)?;
+    let path = git_dir.join(name);
+    if path.is_file() {
+        let content = fs::read_to_string(path)?;
+        let content = content.trim_end();
+        return match content.strip_prefix(SYMBOLIC_REF_PREFIX) {
+            Some(target) => Ok(Some(RefValue::Symbolic(target.to_string()))),
+            None if is_hash(content) => Ok(Some(RefValue::Direct(content.to_string()))),
+            None => Err(anyhow!("Invalid ref {}: {:?}", name, content)),
+        };
+    }
+
+    Ok(read_packed_refs_in(git_dir)?.remove(name).map(RefValue::Direct))
+}
+
+/// Résout une référence jusqu'au hash pointé, en suivant les références symboliques.
+///
+/// # Renvoie
+/// - `None` si la référence (ou sa cible) n'existe pas, comme une branche sans commit.
+pub fn resolve_ref(name: &str) -> Result<Option<String>> {
+    let (_, value) = follow_symbolic_refs(name)?;
+    Ok(value)
+}
+
+/// Nom de la référence finale atteinte en suivant les références symboliques
+/// (par exemple `refs/heads/main` pour `HEAD`).
+pub fn resolve_ref_name(name: &str) -> Result<String> {
+    Ok(follow_symbolic_refs(name)?.0)
+}
+
+/// Résout le HEAD du dépôt imbriqué (sous-module) dont `work_tree` est le répertoire de travail.
+///
+/// Le `.git` du dépôt imbriqué peut être un répertoire ou un fichier `gitdir: <chemin>`.
+///
+/// # Renvoie
+/// - `None` si `work_tree` ne contient pas de dépôt ou si son HEAD ne mène à aucun commit.
+pub fn resolve_nested_head(work_tree: &Path) -> Result<Option<String>> {
+    let Some(git_dir) = repository::resolve_dot_git(&work_tree.join(repository::DOT_GIT))? else {
+        return Ok(None);
+    };
+
+    Ok(follow_symbolic_refs_in(&git_dir, HEAD)?.1)
+}
+
+fn follow_symbolic_refs(name: &str) -> Result<(String, Option<String>)> {
+    follow_symbolic_refs_in(repository::current()?.git_dir(), name)
+}
+
+fn follow_symbolic_refs_in(git_dir: &Path, name: &str) -> Result<(String, Option<String>)> {
+    let mut current = name.to_string();
+    for _ in 0..=MAX_SYMBOLIC_DEPTH {
+        match read_ref_in(git_dir, &current)? {
+            Some(RefValue::Symbolic(target)) => current = target,
+            Some(RefValue::Direct(hash)) => return Ok((current, Some(hash))),
+            None => return Ok((current, None)),
+        }
+    }
+    Err(anyhow!("Too many levels of symbolic refs starting at {}", name))
+}
+
+/// Complète un nom abrégé (`main`, `v1.0`, `origin/main`...) en nom de référence complet,
+/// selon l'ordre de recherche de git.
+pub fn expand_ref_name(name: &str) -> Result<Option<String>> {
+    let candidates = [
+        name.to_string(),
+        format!("refs/{}", name),
+        format!("refs/tags/{}", name),
+        format!("refs/heads/{}", name),
+        format!("refs/remotes/{}", name),
+        format!("refs/remotes/{}/HEAD", name),
+    ];
+
+    for candidate in candidates {
+        if check_ref_format(&candidate).is_ok() && read_ref(&candidate)?.is_some() {
+            return Ok(Some(candidate));
+        }
+    }
+    Ok(None)
+}
+
+/// Lit la cible d'une référence symbolique.
+pub fn read_symbolic_ref(name: &str) -> Result<Option<String>> {
+    match read_ref(name)? {
+        Some(RefValue::Symbolic(target)) => Ok(Some(target)),
+        _ => Ok(None),
+    }
+}
+
+/// Liste les références (loose et `packed-refs`) commençant par `prefix`, triées par nom.
+///
+/// Les références symboliques sont listées avec le hash de leur cible ; celles qui ne
+/// mènent à aucun commit sont ignorées.
+pub fn list_refs(prefix: &str) -> Result<Vec<(String, String)>> {
+    let mut refs = read_packed_refs()?;
+
+    // Les références loose ont priorité sur celles de packed-refs
+    let git_dir = repository::current()?.git_dir().to_path_buf();
+    let mut pending = vec![git_dir.join("refs")];
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+
+            let name = path.strip_prefix(&git_dir)?
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            // Verrous en cours et fichiers dont le nom n'est pas celui d'une référence
+            if check_ref_format(&name).is_err() {
+                continue;
+            }
+            if let Some(hash) = resolve_ref(&name)? {
+                refs.insert(name, hash);
+            }
+        }
+    }
+
+    Ok(refs.into_iter().filter(|(name, _)| name.starts_with(prefix)).collect())
+}
+
+/// Met à jour une référence de façon atomique, en suivant les références symboliques.
+///
+/// # Paramètres
+/// - `name`: La référence à mettre à jour.
+/// - `new_hash`: Le nouveau hash.
+/// - `old_hash`: Si fourni, la valeur que la référence doit avoir avant la mise à jour
+///   (`NULL_HASH` si elle ne doit pas exister).
+/// - `message`: Motif inscrit dans le journal de la référence, éventuellement vide.
+pub fn update_ref(name: &str, new_hash: &str, old_hash: Option<&str>, message: &str) -> Result<()> {
+    let target = resolve_ref_name(name)?;
+    write_hash(&target, new_hash, old_hash, message)
+}
+
+/// Met à jour une référence elle-même, sans suivre une éventuelle référence symbolique.
+pub fn update_ref_no_deref(name: &str, new_hash: &str, old_hash: Option<&str>, message: &str) -> Result<()> {
+    write_hash(name, new_hash, old_hash, message)
+}
+
+/// Fait pointer la référence symbolique `name` vers la référence `target`.
+pub fn set_symbolic_ref(name: &str, target: &str) -> Result<()> {
+    if !target.starts_with("refs/") {
+        return Err(anyhow!("Refusing to point {} outside of refs/: {}", name, target));
+    }
+    check_ref_format(target)?;
+    write_ref(name, &format!("{}{}\n", SYMBOLIC_REF_PREFIX, target), None)
+}
+
+/// Supprime une référence (loose et dans `packed-refs`).
+///
+/// Une référence symbolique est supprimée elle-même, sans toucher à sa cible.
+pub fn delete_ref(name: &str, old_hash: Option<&str>) -> Result<()> {
+    check_ref_format(name)?;
+    let lock = RefLock::acquire(name)?;
+    check_old_value(name, old_hash)?;
+
+    if read_ref(name)?.is_none() {
+        return Err(anyhow!("Unable to delete {}: ref does not exist", name));
+    }
+
+    match fs::remove_file(ref_path(name)?) {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+
+    remove_packed_ref(name)?;
+
+    // Le journal disparaît avec la référence
+    match fs::remove_file(reflog_path(name)?) {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+
+    drop(lock);
+    Ok(())
+}
+
+/// Vérifie qu'un nom de référence respecte les règles de `git check-ref-format`.
+pub fn check_ref_format(name: &str) -> Result<()> {
+    let invalid = name.is_empty()
+        || name == "@"
+        || name.ends_with('/')
+        || name.ends_with('.')
+        || name.contains("..")
+        || name.contains("//")
+        || name.contains("@{")
+        || name.chars().any(|c| c.is_control() || " ~^:?*[\\".contains(c))
+        || name.split('/').any(|component| component.starts_with('.') || component.ends_with(LOCK_EXTENSION));
+
+    // Hors de refs/, seuls les pseudo-refs en majuscules (HEAD, ORIG_HEAD...) sont acceptés
+    let pseudo_ref = !name.contains('/') && name.chars().all(|c| c.is_ascii_uppercase() || c == '_');
+
+    if invalid || !(name.starts_with("refs/") || pseudo_ref) {
+        return Err(anyhow!("Invalid ref name: {}", name));
+    }
+    Ok(())
+}
+
+fn write_ref(name: &str, content: &str, old_hash: Option<&str>) -> Result<()> {
+    check_ref_format(name)?;
+    let mut lock = RefLock::acquire(name)?;
+    check_old_value(name, old_hash)?;
+
+    lock.file.write_all(content.as_bytes())?;
+    lock.commit()
+}
+
+/// Écrit un hash dans une référence puis l'inscrit dans son journal, et dans celui de HEAD
+/// si HEAD pointe sur cette référence.
+fn write_hash(name: &str, new_hash: &str, old_hash: Option<&str>, message: &str) -> Result<()> {
+    check_ref_format(name)?;
+    let mut lock = RefLock::acquire(name)?;
+    check_old_value(name, old_hash)?;
+    let previous = resolve_ref(name)?.unwrap_or_else(|| NULL_HASH.to_string());
+
+    writeln!(lock.file, "{}", new_hash)?;
+    lock.commit()?;
+
+    append_reflog(name, &previous, new_hash, message)?;
+    if name != HEAD && read_symbolic_ref(HEAD)?.as_deref() == Some(name) {
+        append_reflog(HEAD, &previous, new_hash, message)?;
+    }
+    Ok(())
+}
+
+/// Ajoute une ligne `<ancien> <nouveau> <committer>\t<message>` au journal d'une référence.
+///
+/// Comme dans git, un journal existant est toujours complété ; sinon il n'est créé que si
+/// `core.logAllRefUpdates` le demande pour cette référence (voir [`should_create_reflog`]).
+fn append_reflog(name: &str, old_hash: &str, new_hash: &str, message: &str) -> Result<()> {
+    let path = reflog_path(name)?;
+    if !path.exists() && !should_create_reflog(name)? {
+        return Ok(());
+    }
+
+    let committer = Signature::from_environment(SignatureRole::Committer)?;
+    let mut line = format!("{} {} {}", old_hash, new_hash, committer);
+    if !message.is_empty() {
+        line.push('\t');
+        line.push_str(message);
+    }
+    line.push('\n');
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    OpenOptions::new().create(true).append(true).open(&path)?.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Indique si le journal d'une référence doit être créé d'après `core.logAllRefUpdates` :
+/// `always` pour toutes les références, `true` (défaut hors dépôt nu) pour HEAD, les
+/// branches, les branches distantes et les notes.
+fn should_create_reflog(name: &str) -> Result<bool> {
+    let config = ConfigSet::load()?;
+    if config.get_string("core.logallrefupdates")?.as_deref() == Some("always") {
+        return Ok(true);
+    }
+
+    let enabled = config.get_bool("core.logallrefupdates")?
+        .unwrap_or(!repository::current()?.is_bare());
+    Ok(enabled && (name == HEAD || LOGGED_REF_PREFIXES.iter().any(|prefix| name.starts_with(prefix))))
+}
+
+/// Liste les hashes mentionnés par les journaux de toutes les références, anciens comme
+/// nouveaux, pour que `gc` conserve les objets encore accessibles depuis un journal.
+pub fn list_reflog_hashes() -> Result<Vec<String>> {
+    let mut hashes = vec![];
+    let mut pending = vec![repository::current()?.path(LOGS_DIR)];
+
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            for line in fs::read_to_string(&path)?.lines() {
+                let mut fields = line.splitn(3, ' ');
+                for hash in fields.by_ref().take(2) {
+                    if is_hash(hash) && hash != NULL_HASH {
+                        hashes.push(hash.to_string());
+                    }
+                }
+            }
+        }
+    }
+    Ok(hashes)
+}
+
+fn check_old_value(name: &str, old_hash: Option<&str>) -> Result<()> {
+    let Some(expected) = old_hash else { return Ok(()) };
+
+    let current = match read_ref(name)? {
+        Some(RefValue::Direct(hash)) => Some(hash),
+        Some(RefValue::Symbolic(_)) => resolve_ref(name)?,
+        None => None,
+    };
+
+    let matches = match &current {
+        Some(hash) => hash == expected,
+        None => expected == NULL_HASH,
+    };
+    if !matches {
+        return Err(anyhow!(
+            "Cannot lock ref '{}': is at {} but expected {}",
+            name,
+            current.as_deref().unwrap_or(NULL_HASH),
+            expected
+        ));
+    }
+    Ok(())
+}
+
+fn read_packed_refs() -> Result<BTreeMap<String, String>> {
+    read_packed_refs_in(repository::current()?.git_dir())
+}
+
+fn read_packed_refs_in(git_dir: &Path) -> Result<BTreeMap<String, String>> {
+    let content = match fs::read_to_string(git_dir.join(PACKED_REFS_FILE)) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut refs = BTreeMap::new();
+    for line in content.lines() {
+        // Commentaires d'en-tête et lignes "^<hash>" des tags pelés
+        if line.starts_with('#') || line.starts_with('^') || line.is_empty() {
+            continue;
+        }
+        let (hash, name) = line.split_once(' ')
+            .ok_or_else(|| anyhow!("Invalid packed-refs line: {}", line))?;
+        refs.insert(name.to_string(), hash.to_string());
+    }
+    Ok(refs)
+}
+
+/// Retire une référence de `packed-refs` en conservant les autres lignes telles quelles,
+/// y compris l'en-tête et les lignes `^<hash>` qui suivent les tags pelés.
+fn remove_packed_ref(name: &str) -> Result<()> {
+    let mut lock = RefLock::acquire(PACKED_REFS_FILE)?;
+    let content = match fs::read_to_string(ref_path(PACKED_REFS_FILE)?) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut kept = String::with_capacity(content.len());
+    let mut removed = false;
+    let mut in_removed_ref = false;
+    for line in content.lines() {
+        // Une ligne "^<hash>" appartient à la référence qui la précède
+        if !line.starts_with('^') {
+            in_removed_ref = !line.starts_with('#')
+                && line.split_once(' ').is_some_and(|(_, ref_name)| ref_name == name);
+            removed |= in_removed_ref;
+        }
+        if !in_removed_ref {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+    }
+
+    // Sans la référence, le fichier est laissé intact et le verrou abandonné
+    if removed {
+        lock.file.write_all(kept.as_bytes())?;
+        lock.commit()?;
+    }
+    Ok(())
+}
+
+fn ref_path(name: &str) -> Result<PathBuf> {
+    Ok(repository::current()?.path(name))
+}
+
+fn reflog_path(name: &str) -> Result<PathBuf> {
+    Ok(repository::current()?.path(format!("{}/{}", LOGS_DIR, name)))
+}
+
+fn is_hash(value: &str) -> bool {
+    value.len() == object_manager::OBJECT_HASH_SIZE && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Verrou `<ref>.lock` : le nouveau contenu y est écrit puis renommé sur la référence.
+///
+/// Le fichier de verrou est supprimé si la mise à jour n'est pas validée.
+struct RefLock {
+    path: PathBuf,
+    lock_path: PathBuf,
+    file: File,
+    committed: bool,
+}
+
+impl RefLock {
+    fn acquire(name: &str) -> Result<RefLock> {
+        let path = ref_path(name)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let lock_path = ref_path(&format!("{}{}", name, LOCK_EXTENSION))?;
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+            .map_err(|e| anyhow!("Unable to create '{}': {}", lock_path.display(), e))?;
+
+        Ok(RefLock { path, lock_path, file, committed: false })
+    }
+
+    fn commit(mut self) -> Result<()> {
+        self.file.sync_all()?;
+        fs::rename(&self.lock_path, &self.path)?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for RefLock {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.lock_path);
+        }
+    }
+}
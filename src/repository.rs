@@ -1,10 +1,372 @@
+use crate::config::config_file::ConfigFile;
+use crate::config::config_set::{ConfigScope, ConfigSet};
+use crate::refs;
+use anyhow::{anyhow, Result};
+use std::cell::RefCell;
+use std::env;
 use std::fs;
-use anyhow::Result;
-
-pub fn init_repository() -> Result<()> {
-    fs::create_dir(".git")?;
-    fs::create_dir(".git/objects")?;
-    fs::create_dir(".git/refs")?;
-    fs::write(".git/HEAD", "ref: refs/heads/main\n")?;
-    Ok(println!("Initialized git directory"))
-}
\ No newline at end of file
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+/// Nom du répertoire (ou du fichier `gitdir:`) qui signale la racine d'un dépôt.
+pub const DOT_GIT: &str = ".git";
+/// Préfixe du fichier `.git` qui renvoie vers le vrai répertoire git (sous-modules, worktrees).
+const GITDIR_FILE_PREFIX: &str = "gitdir: ";
+
+const GIT_DIR_ENVIRONMENT: &str = "GIT_DIR";
+const GIT_WORK_TREE_ENVIRONMENT: &str = "GIT_WORK_TREE";
+const GIT_CEILING_DIRECTORIES_ENVIRONMENT: &str = "GIT_CEILING_DIRECTORIES";
+const GIT_TEMPLATE_DIR_ENVIRONMENT: &str = "GIT_TEMPLATE_DIR";
+/// Branche initiale quand ni `-b` ni `init.defaultBranch` ne sont donnés.
+const DEFAULT_BRANCH: &str = "main";
+
+thread_local! {
+    /// Dépôt sur lequel opèrent les commandes, découvert une fois par processus.
+    static CURRENT: RefCell<Option<Rc<Repository>>> = const { RefCell::new(None) };
+}
+
+/// Un dépôt ouvert : son répertoire git et, sauf dépôt nu, son répertoire de travail.
+#[derive(Debug)]
+pub struct Repository {
+    /// Répertoire git absolu (`<racine>/.git`, ou le dépôt lui-même s'il est nu).
+    git_dir: PathBuf,
+    /// Racine absolue du répertoire de travail, `None` pour un dépôt nu.
+    work_tree: Option<PathBuf>,
+    /// Répertoire courant au moment de la découverte, relatif à la racine du répertoire de travail.
+    prefix: PathBuf,
+}
+
+impl Repository {
+    /// Ouvre un dépôt dont le répertoire git et le répertoire de travail sont connus.
+    pub fn open(git_dir: &Path, work_tree: Option<&Path>) -> Result<Repository> {
+        let cwd = env::current_dir()?;
+        let git_dir = fs::canonicalize(cwd.join(git_dir))
+            .map_err(|_| anyhow!("not a git repository: '{}'", git_dir.display()))?;
+        if !is_git_directory(&git_dir) {
+            return Err(anyhow!("not a git repository: '{}'", git_dir.display()));
+        }
+
+        let work_tree = match work_tree {
+            Some(work_tree) => Some(fs::canonicalize(cwd.join(work_tree))
+                .map_err(|e| anyhow!("cannot use '{}' as a work tree: {}", work_tree.display(), e))?),
+            None => None,
+        };
+        let prefix = match &work_tree {
+            Some(work_tree) => fs::canonicalize(&cwd)?.strip_prefix(work_tree).map(Path::to_path_buf).unwrap_or_default(),
+            None => PathBuf::new(),
+        };
+
+        Ok(Repository { git_dir, work_tree, prefix })
+    }
+
+    /// Découvre le dépôt qui contient le répertoire courant.
+    ///
+    /// `GIT_DIR` désigne directement le répertoire git ; sinon, chaque répertoire parent est
+    /// examiné jusqu'à trouver un `.git` ou un dépôt nu, sans remonter dans les répertoires
+    /// de `GIT_CEILING_DIRECTORIES`. `GIT_WORK_TREE` (ou `core.worktree`) remplace le
+    /// répertoire de travail déduit.
+    ///
+    /// # Erreurs
+    /// - Retourne une erreur si aucun dépôt n'est trouvé.
+    pub fn discover() -> Result<Repository> {
+        let cwd = env::current_dir()?;
+        if let Some(git_dir) = env::var_os(GIT_DIR_ENVIRONMENT) {
+            let git_dir = cwd.join(git_dir);
+            // Sans autre indication, le répertoire courant est la racine du répertoire de travail
+            let work_tree = configured_work_tree(&git_dir, Some(&cwd))?;
+            return Repository::open(&git_dir, work_tree.as_deref());
+        }
+
+        let ceilings = ceiling_directories();
+        let mut dir = fs::canonicalize(&cwd)?;
+        loop {
+            if let Some(git_dir) = resolve_dot_git(&dir.join(DOT_GIT))? {
+                if is_git_directory(&git_dir) {
+                    let work_tree = configured_work_tree(&git_dir, Some(&dir))?;
+                    return Repository::open(&git_dir, work_tree.as_deref());
+                }
+            }
+            if is_git_directory(&dir) {
+                let work_tree = configured_work_tree(&dir, None)?;
+                return Repository::open(&dir, work_tree.as_deref());
+            }
+
+            match dir.parent() {
+                Some(parent) if !ceilings.iter().any(|ceiling| ceiling == parent) => dir = parent.to_path_buf(),
+                _ => return Err(anyhow!("not a git repository (or any of the parent directories): {}", DOT_GIT)),
+            }
+        }
+    }
+
+    pub fn git_dir(&self) -> &Path {
+        &self.git_dir
+    }
+
+    /// Chemin d'un fichier du répertoire git (`HEAD`, `index`, `refs/heads/main`...).
+    pub fn path<P: AsRef<Path>>(&self, name: P) -> PathBuf {
+        self.git_dir.join(name)
+    }
+
+    pub fn objects_dir(&self) -> PathBuf {
+        self.path("objects")
+    }
+
+    pub fn is_bare(&self) -> bool {
+        self.work_tree.is_none()
+    }
+
+    /// Racine du répertoire de travail.
+    ///
+    /// # Erreurs
+    /// - Retourne une erreur pour un dépôt nu.
+    pub fn work_tree(&self) -> Result<&Path> {
+        self.work_tree.as_deref()
+            .ok_or_else(|| anyhow!("this operation must be run in a work tree"))
+    }
+
+    /// Répertoire courant de l'utilisateur, relatif à la racine du répertoire de travail.
+    pub fn prefix(&self) -> &Path {
+        &self.prefix
+    }
+
+    /// Convertit un chemin donné par l'utilisateur, relatif à son répertoire courant,
+    /// en chemin relatif à la racine du répertoire de travail (`.` pour la racine).
+    ///
+    /// # Erreurs
+    /// - Retourne une erreur si le chemin sort du répertoire de travail.
+    pub fn work_tree_path(&self, path: &Path) -> Result<PathBuf> {
+        let work_tree = self.work_tree()?;
+        let joined = if path.is_absolute() {
+            path.strip_prefix(work_tree)
+                .map_err(|_| anyhow!("'{}' is outside repository at '{}'", path.display(), work_tree.display()))?
+                .to_path_buf()
+        } else {
+            self.prefix.join(path)
+        };
+
+        // Normalisation purement lexicale, comme les pathspecs de git
+        let mut normalized = PathBuf::new();
+        for component in joined.components() {
+            match component {
+                Component::Normal(name) => normalized.push(name),
+                Component::ParentDir if !normalized.pop() => {
+                    return Err(anyhow!("'{}' is outside repository at '{}'", path.display(), work_tree.display()));
+                }
+                _ => {}
+            }
+        }
+
+        if normalized.as_os_str().is_empty() {
+            normalized.push(".");
+        }
+        Ok(normalized)
+    }
+}
+
+/// Renvoie le dépôt courant, découvert à partir du répertoire courant au premier appel.
+pub fn current() -> Result<Rc<Repository>> {
+    if let Some(repository) = CURRENT.with(|current| current.borrow().clone()) {
+        return Ok(repository);
+    }
+    Ok(set_current(Repository::discover()?))
+}
+
+/// Fait du dépôt donné le dépôt courant des commandes.
+pub fn set_current(repository: Repository) -> Rc<Repository> {
+    let repository = Rc::new(repository);
+    CURRENT.with(|current| current.replace(Some(repository.clone())));
+    repository
+}
+
+/// Découvre le dépôt courant et, comme git, se place à la racine de son répertoire de travail.
+///
+/// Les chemins donnés par l'utilisateur se convertissent ensuite avec `Repository::work_tree_path`.
+pub fn setup() -> Result<Rc<Repository>> {
+    let repository = Repository::discover()?;
+    if let Some(work_tree) = &repository.work_tree {
+        env::set_current_dir(work_tree)?;
+    }
+    Ok(set_current(repository))
+}
+
+/// Résout l'entrée `.git` d'un répertoire de travail : un répertoire, ou un fichier
+/// `gitdir: <chemin>` qui renvoie vers le vrai répertoire git.
+///
+/// # Renvoie
+/// - `None` si l'entrée n'existe pas.
+pub(crate) fn resolve_dot_git(dot_git: &Path) -> Result<Option<PathBuf>> {
+    if dot_git.is_dir() {
+        return Ok(Some(dot_git.to_path_buf()));
+    }
+    if !dot_git.is_file() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(dot_git)?;
+    match content.trim_end().strip_prefix(GITDIR_FILE_PREFIX) {
+        // Un chemin relatif l'est au répertoire qui contient le fichier
+        Some(target) => Ok(Some(dot_git.parent().unwrap_or(Path::new("")).join(target))),
+        None => Err(anyhow!("Invalid gitfile format: {}", dot_git.display())),
+    }
+}
+
+/// Un répertoire git contient au moins `HEAD`, `objects/` et `refs/`.
+fn is_git_directory(path: &Path) -> bool {
+    path.join("HEAD").is_file() && path.join("objects").is_dir() && path.join("refs").is_dir()
+}
+
+/// Répertoire de travail d'un dépôt : `GIT_WORK_TREE`, puis `core.worktree`, puis celui
+/// déduit de la découverte, sauf si `core.bare` le déclare nu.
+fn configured_work_tree(git_dir: &Path, discovered: Option<&Path>) -> Result<Option<PathBuf>> {
+    if let Some(work_tree) = env::var_os(GIT_WORK_TREE_ENVIRONMENT) {
+        return Ok(Some(PathBuf::from(work_tree)));
+    }
+
+    let config = ConfigSet::load_file(&git_dir.join("config"), ConfigScope::Local, false)?;
+    if let Some(work_tree) = config.get_path("core.worktree")? {
+        // Relatif au répertoire git, comme dans git
+        return Ok(Some(git_dir.join(work_tree)));
+    }
+    if config.get_bool("core.bare")? == Some(true) {
+        return Ok(None);
+    }
+    Ok(discovered.map(Path::to_path_buf))
+}
+
+/// Répertoires de `GIT_CEILING_DIRECTORIES` au-dessus desquels la découverte ne remonte pas.
+///
+/// Comme dans git, les chemins relatifs sont ignorés.
+fn ceiling_directories() -> Vec<PathBuf> {
+    let Some(value) = env::var_os(GIT_CEILING_DIRECTORIES_ENVIRONMENT) else {
+        return vec![];
+    };
+    env::split_paths(&value)
+        .filter(|path| path.is_absolute())
+        .map(|path| fs::canonicalize(&path).unwrap_or(path))
+        .collect()
+}
+
+/// Options de la commande `init`.
+#[derive(Debug, Default)]
+pub struct InitOptions {
+    /// Crée un dépôt nu : le répertoire donné est lui-même le répertoire git.
+    pub bare: bool,
+    /// Branche pointée par `HEAD`, à la place de `init.defaultBranch`.
+    pub initial_branch: Option<String>,
+    /// Répertoire dont le contenu est copié dans le répertoire git ; vide pour n'en copier aucun.
+    pub template: Option<PathBuf>,
+    /// N'affiche pas le message final.
+    pub quiet: bool,
+}
+
+/// Crée un dépôt, ou réinitialise un dépôt existant, et l'annonce comme git.
+///
+/// # Paramètres
+/// - `directory` : répertoire du dépôt, créé au besoin ; le répertoire courant par défaut.
+/// - `options` : dépôt nu, branche initiale, modèles.
+pub fn init_repository(directory: Option<&Path>, options: &InitOptions) -> Result<()> {
+    let directory = directory.unwrap_or(Path::new("."));
+    fs::create_dir_all(directory)
+        .map_err(|e| anyhow!("cannot mkdir {}: {}", directory.display(), e))?;
+
+    let git_dir = if options.bare { directory.to_path_buf() } else { directory.join(DOT_GIT) };
+    let reinitialized = create_git_directory(&git_dir, options)?;
+
+    if !options.quiet {
+        let verb = if reinitialized { "Reinitialized existing" } else { "Initialized empty" };
+        let git_dir = fs::canonicalize(&git_dir)?;
+        println!("{} Git repository in {}/", verb, git_dir.display());
+    }
+    Ok(())
+}
+
+/// Crée l'arborescence complète d'un répertoire git, en complétant sans rien écraser
+/// un dépôt existant.
+///
+/// # Renvoie
+/// - `true` si le répertoire contenait déjà un dépôt.
+pub fn create_git_directory(git_dir: &Path, options: &InitOptions) -> Result<bool> {
+    let reinitialized = is_git_directory(git_dir);
+    // La configuration d'un dépôt englobant ne s'applique pas au nouveau dépôt
+    let global_config = ConfigSet::load_global()?;
+    let branch = match &options.initial_branch {
+        Some(branch) => branch.clone(),
+        None => global_config.get_string("init.defaultBranch")?.unwrap_or_else(|| DEFAULT_BRANCH.to_string()),
+    };
+    refs::check_ref_format(&format!("refs/heads/{}", branch))
+        .map_err(|_| anyhow!("invalid initial branch name: '{}'", branch))?;
+
+    // Les fichiers des modèles viennent en premier : ceux créés ensuite les complètent
+    if let Some(template) = template_directory(options, &global_config)? {
+        copy_template(&template, git_dir)?;
+    }
+    for dir in ["objects/info", "objects/pack", "refs/heads", "refs/tags", "info", "hooks"] {
+        fs::create_dir_all(git_dir.join(dir))?;
+    }
+
+    let head = git_dir.join("HEAD");
+    if reinitialized {
+        if let Some(branch) = &options.initial_branch {
+            eprintln!("warning: re-init: ignored --initial-branch={}", branch);
+        }
+    }
+    if !head.exists() {
+        fs::write(&head, format!("ref: refs/heads/{}\n", branch))?;
+    }
+
+    // Une configuration existante (dépôt ou modèle) est conservée : seules les clés
+    // de `core` sont écrites, à leur place si elles existent déjà
+    let mut config = ConfigFile::open(&git_dir.join("config"))?;
+    config.set("core.repositoryformatversion", "0")?;
+    config.set("core.filemode", "true")?;
+    config.set("core.bare", &options.bare.to_string())?;
+    if !options.bare && config.get("core.logallrefupdates")?.is_none() {
+        config.set("core.logallrefupdates", "true")?;
+    }
+    config.save()?;
+
+    Ok(reinitialized)
+}
+
+/// Répertoire des modèles : `--template`, puis `GIT_TEMPLATE_DIR`, puis `init.templateDir`.
+fn template_directory(options: &InitOptions, global_config: &ConfigSet) -> Result<Option<PathBuf>> {
+    let template = match &options.template {
+        Some(template) => template.clone(),
+        None => match env::var_os(GIT_TEMPLATE_DIR_ENVIRONMENT) {
+            Some(template) => PathBuf::from(template),
+            None => match global_config.get_path("init.templateDir")? {
+                Some(template) => template,
+                None => return Ok(None),
+            },
+        },
+    };
+    // Un chemin vide désactive explicitement les modèles
+    Ok((!template.as_os_str().is_empty()).then_some(template))
+}
+
+/// Copie récursivement les fichiers d'un modèle qui n'existent pas encore dans `destination`.
+///
+/// Comme dans git, un répertoire de modèles absent est ignoré.
+fn copy_template(template: &Path, destination: &Path) -> Result<()> {
+    let entries = match fs::read_dir(template) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            eprintln!("warning: templates not found in {}", template.display());
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    fs::create_dir_all(destination)?;
+    for entry in entries {
+        let entry = entry?;
+        let target = destination.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_template(&entry.path(), &target)?;
+        } else if !target.exists() {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
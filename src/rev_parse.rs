@@ -0,0 +1,223 @@
+use crate::dircache::index::Index;
+use crate::objects::object_kind::GitObjectKind;
+use crate::objects::object_manager;
+use crate::refs;
+use crate::repository;
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::Path;
+
+/// Longueur minimale d'un hash abrégé, comme dans git.
+const MIN_ABBREV_LENGTH: usize = 4;
+const CHECKOUT_REFLOG_PREFIX: &str = "checkout: moving from ";
+
+/// Résout une révision en hash d'objet.
+///
+/// Formes acceptées :
+/// - un hash complet ou un préfixe unique d'au moins 4 caractères ;
+/// - un nom de référence (`main`, `v1.0`, `origin/main`, `refs/heads/main`), `HEAD` ou `@` ;
+/// - `@{-N}`, la N-ième branche extraite précédemment ;
+/// - les suffixes `~N`, `^N`, `^{type}` et `^{}`, combinables (`HEAD~2^2^{tree}`) ;
+/// - `<rev>:<chemin>` pour une entrée de l'arbre d'un commit, `:<chemin>` pour l'index.
+pub fn resolve_revision(revision: &str) -> Result<String> {
+    if let Some(path) = revision.strip_prefix(':') {
+        return resolve_index_path(path);
+    }
+
+    if let Some((base, path)) = revision.split_once(':') {
+        let tree_hash = peel(&resolve_revision(base)?, "tree")
+            .map_err(|_| anyhow!("{}: not a tree-ish", base))?;
+        let path = revision_path(path)?;
+        return match object_manager::find_tree_entry(&tree_hash, &path)? {
+            Some(entry) => Ok(entry.hash),
+            None => Err(anyhow!("path '{}' does not exist in '{}'", path, base)),
+        };
+    }
+
+    // Le nom de base s'arrête au premier opérateur : `^` et `~` sont interdits dans les noms de références
+    let base_end = revision.find(['^', '~']).unwrap_or(revision.len());
+    let (base, mut operators) = revision.split_at(base_end);
+    let mut hash = resolve_base(base).map_err(|e| anyhow!("{}: {}", revision, e))?;
+
+    while let Some(operator) = operators.chars().next() {
+        operators = &operators[1..];
+
+        if operator == '^' && operators.starts_with('{') {
+            let end = operators.find('}')
+                .ok_or_else(|| anyhow!("{}: missing '}}'", revision))?;
+            let kind = &operators[1..end];
+            operators = &operators[end + 1..];
+            hash = match kind {
+                // `^{}` déréférence les tags jusqu'à un objet qui n'en est pas un
+                "" => peel_tags(&hash)?,
+                "object" => {
+                    object_manager::read_object_type(&hash)?;
+                    hash
+                }
+                "commit" | "tree" | "blob" | "tag" => peel(&hash, kind)
+                    .map_err(|_| anyhow!("{}: expected {} type", revision, kind))?,
+                _ => return Err(anyhow!("{}: unknown object type '{}'", revision, kind)),
+            };
+            continue;
+        }
+
+        let digits_end = operators.find(|c: char| !c.is_ascii_digit()).unwrap_or(operators.len());
+        let count = match &operators[..digits_end] {
+            "" => 1,
+            digits => digits.parse::<usize>().map_err(|_| anyhow!("{}: invalid number", revision))?,
+        };
+        operators = &operators[digits_end..];
+
+        hash = peel(&hash, "commit")?;
+        if operator == '^' {
+            // `^0` désigne le commit lui-même, `^N` son N-ième parent
+            if count > 0 {
+                hash = nth_parent(&hash, count)?
+                    .ok_or_else(|| anyhow!("{}: unknown revision", revision))?;
+            }
+        } else {
+            for _ in 0..count {
+                hash = nth_parent(&hash, 1)?
+                    .ok_or_else(|| anyhow!("{}: unknown revision", revision))?;
+            }
+        }
+    }
+
+    Ok(hash)
+}
+
+/// Résout une révision en hash d'objet, en vérifiant que l'objet existe : un hash complet
+/// est sinon accepté tel quel.
+///
+/// # Erreurs
+/// - Révision inconnue, ou objet absent du dépôt.
+pub fn resolve_existing_revision(revision: &str) -> Result<String> {
+    let hash = resolve_revision(revision)?;
+    if !object_manager::has_object(&hash)? {
+        return Err(anyhow!("{}: not a valid SHA1", revision));
+    }
+    Ok(hash)
+}
+
+/// Résout une révision en hash d'objet du type demandé, en déréférençant tags et commits.
+pub fn resolve_revision_as(revision: &str, kind: &str) -> Result<String> {
+    let hash = resolve_revision(revision)?;
+    peel(&hash, kind).map_err(|_| anyhow!("{}: expected {} type", revision, kind))
+}
+
+/// Abrège un hash au plus court préfixe unique d'au moins `min_length` caractères.
+pub fn abbreviate(hash: &str, min_length: usize) -> Result<String> {
+    let mut length = min_length.clamp(MIN_ABBREV_LENGTH, hash.len());
+    while length < hash.len() && object_manager::find_objects_by_prefix(&hash[..length])?.len() > 1 {
+        length += 1;
+    }
+    Ok(hash[..length].to_string())
+}
+
+fn resolve_base(base: &str) -> Result<String> {
+    let base = if base == "@" { refs::HEAD } else { base };
+
+    if let Some(count) = base.strip_prefix("@{-").and_then(|rest| rest.strip_suffix('}')) {
+        let count = count.parse::<usize>().ok().filter(|&count| count > 0)
+            .ok_or_else(|| anyhow!("invalid previous branch syntax"))?;
+        return resolve_previous_branch(count);
+    }
+
+    if base.len() == object_manager::OBJECT_HASH_SIZE && is_hex(base) {
+        return Ok(base.to_lowercase());
+    }
+
+    // Comme git, un nom de référence l'emporte sur un préfixe de hash
+    if let Some(reference) = refs::expand_ref_name(base)? {
+        if let Some(hash) = refs::resolve_ref(&reference)? {
+            return Ok(hash);
+        }
+    }
+
+    if base.len() >= MIN_ABBREV_LENGTH && is_hex(base) {
+        let candidates = object_manager::find_objects_by_prefix(&base.to_lowercase())?;
+        return match candidates.as_slice() {
+            [hash] => Ok(hash.clone()),
+            [] => Err(anyhow!("unknown revision")),
+            _ => Err(anyhow!("short object ID {} is ambiguous (candidates: {})", base, candidates.join(", "))),
+        };
+    }
+
+    Err(anyhow!("unknown revision or path not in the working tree"))
+}
+
+/// Retrouve la N-ième branche quittée, d'après les `checkout` du journal de HEAD.
+fn resolve_previous_branch(count: usize) -> Result<String> {
+    let reflog_path = repository::current()?.path("logs").join(refs::HEAD);
+    let reflog = fs::read_to_string(reflog_path).unwrap_or_default();
+
+    let previous = reflog.lines()
+        .rev()
+        .filter_map(|line| line.split_once('\t'))
+        .filter_map(|(_, message)| message.strip_prefix(CHECKOUT_REFLOG_PREFIX))
+        .filter_map(|moved| moved.split_once(" to "))
+        .map(|(from, _)| from)
+        .nth(count - 1)
+        .ok_or_else(|| anyhow!("only {} checkouts found", reflog.matches(CHECKOUT_REFLOG_PREFIX).count()))?;
+
+    match refs::resolve_ref(&format!("refs/heads/{}", previous))? {
+        Some(hash) => Ok(hash),
+        None => resolve_base(previous),
+    }
+}
+
+/// Chemin d'une révision `<rev>:<chemin>` ou `:<chemin>`, relatif à la racine du dépôt.
+///
+/// Comme dans git, un chemin commençant par `./` ou `../` est relatif au répertoire courant
+/// de l'utilisateur ; tout autre chemin est relatif à la racine.
+fn revision_path(path: &str) -> Result<String> {
+    if !path.starts_with("./") && !path.starts_with("../") {
+        return Ok(path.to_string());
+    }
+
+    let path = repository::current()?.work_tree_path(Path::new(path))?;
+    Ok(if path == Path::new(".") { String::new() } else { path.to_string_lossy().into_owned() })
+}
+
+fn resolve_index_path(path: &str) -> Result<String> {
+    // `:N:<chemin>` désigne une étape de conflit ; seule l'étape 0 est gérée
+    let path = revision_path(path.strip_prefix("0:").unwrap_or(path))?;
+    let index = Index::load()?;
+    index.get(path.as_bytes())
+        .map(|entry| entry.hash.clone())
+        .ok_or_else(|| anyhow!("path '{}' does not exist in the index", path))
+}
+
+/// Déréférence un objet jusqu'au type demandé : tag vers sa cible, commit vers son arbre.
+fn peel(hash: &str, kind: &str) -> Result<String> {
+    let mut hash = hash.to_string();
+    loop {
+        let object = object_manager::read_object(&hash)?;
+        hash = match object {
+            GitObjectKind::Tag(tag) if kind != "tag" => tag.object_hash,
+            GitObjectKind::Commit(commit) if kind == "tree" => commit.tree_hash().to_string(),
+            GitObjectKind::Blob(_) if kind == "blob" => return Ok(hash),
+            GitObjectKind::Tree(_) if kind == "tree" => return Ok(hash),
+            GitObjectKind::Commit(_) if kind == "commit" => return Ok(hash),
+            GitObjectKind::Tag(_) => return Ok(hash),
+            _ => return Err(anyhow!("object {} cannot be peeled to a {}", hash, kind)),
+        };
+    }
+}
+
+fn peel_tags(hash: &str) -> Result<String> {
+    let mut hash = hash.to_string();
+    while let GitObjectKind::Tag(tag) = object_manager::read_object(&hash)? {
+        hash = tag.object_hash;
+    }
+    Ok(hash)
+}
+
+fn nth_parent(hash: &str, n: usize) -> Result<Option<String>> {
+    let commit = object_manager::read_commit(hash)?;
+    Ok(commit.parents().get(n - 1).map(|parent| parent.to_string()))
+}
+
+fn is_hex(value: &str) -> bool {
+    value.chars().all(|c| c.is_ascii_hexdigit())
+}
@@ -0,0 +1,400 @@
+use crate::dircache::index::Index;
+use crate::dircache::index_entry::{self, IndexEntry, EXTENDED_FLAG_INTENT_TO_ADD, EXTENDED_FLAG_SKIP_WORKTREE, MODE_GITLINK, MODE_SYMLINK};
+use crate::fs_reader::{self, FSReader};
+use crate::ignore::ignore_rules::IgnoreRules;
+use crate::objects::blob::blob::Blob;
+use crate::objects::object_base::GitObject;
+use crate::objects::object_manager;
+use crate::refs;
+use crate::repository;
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+
+/// Mode d'un sous-arbre.
+const MODE_TREE: u32 = 0o40000;
+
+/// Une version d'un fichier : son mode et le hash de son contenu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileVersion {
+    pub mode: u32,
+    pub hash: String,
+}
+
+/// Modification d'un chemin entre deux états : arbre, index ou répertoire de travail.
+#[derive(Debug, Clone)]
+pub struct FileChange {
+    /// Chemin relatif à la racine du dépôt ; chemin d'arrivée d'un renommage.
+    pub path: Vec<u8>,
+    /// Chemin de départ d'un renommage.
+    pub old_path: Option<Vec<u8>>,
+    /// Version avant la modification, `None` pour un ajout.
+    pub old: Option<FileVersion>,
+    /// Version après la modification, `None` pour une suppression.
+    pub new: Option<FileVersion>,
+}
+
+impl FileChange {
+    /// Lettre de la modification, comme `git diff --name-status` : `A`, `D`, `R`, `T` ou `M`.
+    pub fn status(&self) -> char {
+        match (&self.old, &self.new) {
+            (None, _) => 'A',
+            (_, None) => 'D',
+            _ if self.old_path.is_some() => 'R',
+            (Some(old), Some(new)) if file_type(old.mode) != file_type(new.mode) => 'T',
+            _ => 'M',
+        }
+    }
+}
+
+/// Chemin en conflit de fusion dans l'index.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub path: Vec<u8>,
+    /// Versions de l'ancêtre commun, de notre côté et de l'autre côté (étapes 1 à 3).
+    pub stages: [Option<FileVersion>; 3],
+    /// Mode du fichier dans le répertoire de travail, `None` s'il n'existe pas.
+    pub worktree_mode: Option<u32>,
+}
+
+impl Conflict {
+    /// Étapes présentes, un bit par étape (1 pour l'ancêtre, 2 pour nous, 4 pour eux).
+    pub fn stage_mask(&self) -> u8 {
+        self.stages.iter().enumerate()
+            .filter(|(_, version)| version.is_some())
+            .fold(0, |mask, (i, _)| mask | (1 << i))
+    }
+}
+
+/// Fichiers non suivis à signaler, comme `status --untracked-files`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UntrackedMode {
+    /// Aucun.
+    No,
+    /// Un répertoire entièrement non suivi est signalé seul, avec un `/` final.
+    Normal,
+    /// Tous les fichiers, un par un.
+    All,
+}
+
+/// Compare un arbre (celui de HEAD en général) à l'index.
+///
+/// Les chemins en conflit sont laissés de côté, et un ajout dont le contenu est celui
+/// d'une suppression devient un renommage.
+///
+/// # Paramètres
+/// - `tree_hash` : arbre de référence, `None` avant le premier commit.
+pub fn diff_tree_to_index(tree_hash: Option<&str>, index: &Index) -> Result<Vec<FileChange>> {
+    let mut tree = match tree_hash {
+        Some(tree_hash) => read_tree_files(tree_hash)?,
+        None => BTreeMap::new(),
+    };
+    for entry in index.entries.iter().filter(|entry| entry.stage != 0) {
+        tree.remove(&entry.path);
+    }
+    // Un fichier ajouté avec `add -N` n'est pas encore dans l'index du point de vue d'un commit
+    let staged = index.entries.iter()
+        .filter(|entry| entry.stage == 0 && entry.extended_flags & EXTENDED_FLAG_INTENT_TO_ADD == 0)
+        .map(|entry| (entry.path.clone(), index_version(entry)))
+        .collect();
+
+    Ok(diff_versions(&tree, &staged))
+}
+
+/// Compare un arbre au répertoire de travail, comme `git diff <commit>` : seuls les fichiers
+/// de l'index sont pris en compte, et les chemins en conflit sont laissés de côté.
+///
+/// # Renvoie
+/// - Les modifications, et `true` si des entrées de l'index ont été rafraîchies
+///   (voir [`diff_index_to_worktree`]).
+pub fn diff_tree_to_worktree(tree_hash: Option<&str>, index: &mut Index) -> Result<(Vec<FileChange>, bool)> {
+    let (worktree_changes, refreshed) = diff_index_to_worktree(index)?;
+
+    let mut tree = match tree_hash {
+        Some(tree_hash) => read_tree_files(tree_hash)?,
+        None => BTreeMap::new(),
+    };
+    let mut worktree = BTreeMap::new();
+    for entry in &index.entries {
+        if entry.stage == 0 {
+            worktree.insert(entry.path.clone(), index_version(entry));
+        } else {
+            tree.remove(&entry.path);
+        }
+    }
+    for change in worktree_changes {
+        match change.new {
+            Some(version) => worktree.insert(change.path, version),
+            None => worktree.remove(&change.path),
+        };
+    }
+
+    Ok((diff_versions(&tree, &worktree), refreshed))
+}
+
+/// Compare deux arbres.
+pub fn diff_trees(old_tree: &str, new_tree: &str) -> Result<Vec<FileChange>> {
+    Ok(diff_versions(&read_tree_files(old_tree)?, &read_tree_files(new_tree)?))
+}
+
+/// Compare deux ensembles de fichiers, en appariant les suppressions et les ajouts de
+/// même contenu en renommages.
+fn diff_versions(old: &BTreeMap<Vec<u8>, FileVersion>, new: &BTreeMap<Vec<u8>, FileVersion>) -> Vec<FileChange> {
+    let mut changes = vec![];
+    for (path, version) in old {
+        match new.get(path) {
+            Some(new_version) if new_version == version => {}
+            new_version => changes.push(FileChange {
+                path: path.clone(),
+                old_path: None,
+                old: Some(version.clone()),
+                new: new_version.cloned(),
+            }),
+        }
+    }
+    for (path, version) in new {
+        if !old.contains_key(path) {
+            changes.push(FileChange { path: path.clone(), old_path: None, old: None, new: Some(version.clone()) });
+        }
+    }
+
+    detect_exact_renames(changes)
+}
+
+/// Compare l'index au répertoire de travail.
+///
+/// Un fichier dont les données `stat` n'ont pas changé n'est pas relu ; celui qui a été touché
+/// sans changer de contenu voit ses données `stat` mises à jour dans `index`.
+///
+/// # Renvoie
+/// - Les modifications, triées par chemin, et `true` si des entrées de l'index ont été rafraîchies.
+pub fn diff_index_to_worktree(index: &mut Index) -> Result<(Vec<FileChange>, bool)> {
+    let index_mtime = Index::modification_time()?;
+    let mut changes = vec![];
+    let mut refreshed = false;
+
+    for entry in index.entries.iter_mut() {
+        if entry.stage != 0 || entry.assume_valid || entry.extended_flags & EXTENDED_FLAG_SKIP_WORKTREE != 0 {
+            continue;
+        }
+        let intent_to_add = entry.extended_flags & EXTENDED_FLAG_INTENT_TO_ADD != 0;
+        let old = (!intent_to_add).then(|| index_version(entry));
+        let path = fs_reader::path_from_bytes(&entry.path);
+
+        let metadata = match fs::symlink_metadata(&path) {
+            // Un répertoire qui remplace un fichier compte comme sa suppression
+            Ok(metadata) if !metadata.is_dir() || entry.mode == MODE_GITLINK => metadata,
+            _ => {
+                changes.push(FileChange { path: entry.path.clone(), old_path: None, old, new: None });
+                continue;
+            }
+        };
+
+        let new = if entry.mode == MODE_GITLINK && metadata.is_dir() {
+            // Un sous-module sans commit extrait n'est pas considéré comme modifié
+            match refs::resolve_nested_head(&path)? {
+                Some(hash) if hash != entry.hash => FileVersion { mode: MODE_GITLINK, hash },
+                _ => continue,
+            }
+        } else {
+            let mode = index_entry::mode_from_metadata(&metadata);
+            if !intent_to_add && mode == entry.mode && entry.matches_stat(&metadata) && !entry.is_racy(index_mtime) {
+                continue;
+            }
+            let version = worktree_version(&path, mode)?;
+            if !intent_to_add && version.mode == entry.mode && version.hash == entry.hash {
+                entry.update_stat(&metadata);
+                refreshed = true;
+                continue;
+            }
+            version
+        };
+        changes.push(FileChange { path: entry.path.clone(), old_path: None, old, new: Some(new) });
+    }
+
+    Ok((changes, refreshed))
+}
+
+/// Chemins en conflit de l'index, avec leurs versions par étape.
+pub fn conflicts(index: &Index) -> Vec<Conflict> {
+    let mut conflicts: Vec<Conflict> = vec![];
+    for entry in index.entries.iter().filter(|entry| entry.stage != 0) {
+        if conflicts.last().map(|conflict| &conflict.path) != Some(&entry.path) {
+            let worktree_mode = fs::symlink_metadata(fs_reader::path_from_bytes(&entry.path)).ok()
+                .map(|metadata| index_entry::mode_from_metadata(&metadata));
+            conflicts.push(Conflict { path: entry.path.clone(), stages: [None, None, None], worktree_mode });
+        }
+        if let Some(conflict) = conflicts.last_mut() {
+            conflict.stages[(entry.stage as usize).clamp(1, 3) - 1] = Some(index_version(entry));
+        }
+    }
+    conflicts
+}
+
+/// Cherche les fichiers du répertoire de travail absents de l'index et non exclus.
+///
+/// # Renvoie
+/// - Les chemins triés, les répertoires avec un `/` final.
+pub fn untracked_files(index: &Index, ignore_rules: &mut IgnoreRules, mode: UntrackedMode) -> Result<Vec<Vec<u8>>> {
+    if mode == UntrackedMode::No {
+        return Ok(vec![]);
+    }
+
+    let mut tracked_directories = HashSet::new();
+    for entry in &index.entries {
+        for (separator, _) in entry.path.iter().enumerate().filter(|(_, &c)| c == b'/') {
+            tracked_directories.insert(&entry.path[..separator]);
+        }
+    }
+    let mut scan = UntrackedScan {
+        tracked: index.entries.iter().map(|entry| (entry.path.as_slice(), entry.mode)).collect(),
+        tracked_directories,
+        ignore_rules,
+        all: mode == UntrackedMode::All,
+    };
+
+    let mut untracked = vec![];
+    scan.scan_directory(b"", &mut untracked)?;
+    untracked.sort();
+    Ok(untracked)
+}
+
+/// Parcours du répertoire de travail à la recherche des fichiers non suivis.
+struct UntrackedScan<'a> {
+    /// Mode de chaque chemin de l'index.
+    tracked: HashMap<&'a [u8], u32>,
+    /// Répertoires qui contiennent au moins un chemin de l'index.
+    tracked_directories: HashSet<&'a [u8]>,
+    ignore_rules: &'a mut IgnoreRules,
+    /// Liste chaque fichier d'un répertoire non suivi au lieu du répertoire seul.
+    all: bool,
+}
+
+impl UntrackedScan<'_> {
+    fn scan_directory(&mut self, directory: &[u8], untracked: &mut Vec<Vec<u8>>) -> Result<()> {
+        let fs_path = if directory.is_empty() { Path::new(".").to_path_buf() } else { fs_reader::path_from_bytes(directory) };
+
+        for child in FSReader.read_directory(&fs_path)? {
+            let Some(name) = child.file_name() else { continue };
+            if name == repository::DOT_GIT {
+                continue;
+            }
+            let mut path = directory.to_vec();
+            if !path.is_empty() {
+                path.push(b'/');
+            }
+            path.extend_from_slice(&fs_reader::os_str_bytes(name));
+
+            let is_dir = fs::symlink_metadata(&child)?.is_dir();
+            // Comme dans git, un répertoire qui remplace un fichier suivi n'est parcouru
+            // que pour lister tous les fichiers, jamais signalé en bloc
+            let tracked = self.tracked.get(path.as_slice())
+                .is_some_and(|&mode| !is_dir || mode == MODE_GITLINK || !self.all);
+            if tracked {
+                continue;
+            }
+
+            if is_dir && self.tracked_directories.contains(path.as_slice()) {
+                // Les fichiers non suivis d'un répertoire exclu le sont aussi
+                if !self.ignore_rules.is_ignored(&path, true)? {
+                    self.scan_directory(&path, untracked)?;
+                }
+            } else if self.ignore_rules.is_ignored(&path, is_dir)? {
+                continue;
+            } else if !is_dir {
+                untracked.push(path);
+            } else if child.join(repository::DOT_GIT).exists() {
+                // Un dépôt imbriqué n'est pas parcouru
+                path.push(b'/');
+                untracked.push(path);
+            } else if self.all {
+                self.scan_directory(&path, untracked)?;
+            } else {
+                // Un répertoire n'est signalé que s'il contient au moins un fichier non exclu
+                let mut content = vec![];
+                self.scan_directory(&path, &mut content)?;
+                if !content.is_empty() {
+                    path.push(b'/');
+                    untracked.push(path);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fichiers d'un arbre, sous-arbres compris, par chemin.
+pub fn read_tree_files(tree_hash: &str) -> Result<BTreeMap<Vec<u8>, FileVersion>> {
+    let mut files = BTreeMap::new();
+    collect_tree_files(tree_hash, b"", &mut files)?;
+    Ok(files)
+}
+
+fn collect_tree_files(tree_hash: &str, prefix: &[u8], files: &mut BTreeMap<Vec<u8>, FileVersion>) -> Result<()> {
+    for entry in object_manager::read_tree(tree_hash)?.entries {
+        let path = [prefix, &entry.name].concat();
+        let mode = u32::from_str_radix(&entry.mode, 8)?;
+        if mode == MODE_TREE {
+            collect_tree_files(&entry.hash, &[&path[..], b"/"].concat(), files)?;
+        } else {
+            files.insert(path, FileVersion { mode, hash: entry.hash });
+        }
+    }
+    Ok(())
+}
+
+/// Version d'un fichier du répertoire de travail, hachée sans être écrite dans le dépôt.
+fn worktree_version(path: &Path, mode: u32) -> Result<FileVersion> {
+    let hash = if mode == MODE_SYMLINK {
+        Blob::new(FSReader.read_link(path)?)?.get_hash().to_string()
+    } else {
+        object_manager::hash_file(path, false)?
+    };
+    Ok(FileVersion { mode, hash })
+}
+
+fn index_version(entry: &IndexEntry) -> FileVersion {
+    FileVersion { mode: entry.mode, hash: entry.hash.clone() }
+}
+
+/// Apparie les suppressions et les ajouts de contenu identique en renommages.
+fn detect_exact_renames(mut changes: Vec<FileChange>) -> Vec<FileChange> {
+    let mut sources: HashMap<String, VecDeque<usize>> = HashMap::new();
+    for (i, change) in changes.iter().enumerate() {
+        if let (Some(old), None) = (&change.old, &change.new) {
+            if old.mode != MODE_GITLINK {
+                sources.entry(old.hash.clone()).or_default().push_back(i);
+            }
+        }
+    }
+
+    let mut renamed = HashSet::new();
+    for i in 0..changes.len() {
+        let Some(new) = changes[i].new.as_ref().filter(|_| changes[i].old.is_none()) else {
+            continue;
+        };
+        if new.mode == MODE_GITLINK {
+            continue;
+        }
+        if let Some(source) = sources.get_mut(&new.hash).and_then(VecDeque::pop_front) {
+            changes[i].old = changes[source].old.take();
+            changes[i].old_path = Some(changes[source].path.clone());
+            renamed.insert(source);
+        }
+    }
+
+    let mut position = 0;
+    changes.retain(|_| {
+        position += 1;
+        !renamed.contains(&(position - 1))
+    });
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+/// Type de fichier d'un mode : fichier (exécutable ou non), lien symbolique ou sous-module.
+fn file_type(mode: u32) -> u32 {
+    mode & 0o170000
+}
+
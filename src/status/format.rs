@@ -0,0 +1,338 @@
+use crate::dircache::index_entry::MODE_GITLINK;
+use crate::fs_reader;
+use crate::quote::quote_path;
+use crate::refs::NULL_HASH;
+use crate::rev_parse;
+use crate::status::changes::{Conflict, FileChange, FileVersion};
+use crate::status::status_manager::Status;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Largeur des libellés (`modified:`...) du format long, espace final compris.
+const CHANGE_LABEL_WIDTH: usize = 12;
+/// Largeur des libellés de conflit (`both modified:`...), espace final compris.
+const CONFLICT_LABEL_WIDTH: usize = 17;
+/// Longueur minimale des hashes abrégés.
+const ABBREV_LENGTH: usize = 7;
+
+/// Mise en forme des chemins affichés.
+#[derive(Debug)]
+pub struct PathStyle {
+    /// Répertoire de l'utilisateur relatif à la racine (`a/b`) : les chemins sont affichés
+    /// relativement à lui. `None` pour des chemins relatifs à la racine.
+    pub prefix: Option<Vec<u8>>,
+    /// Met entre guillemets les chemins qui contiennent des caractères spéciaux.
+    pub quote: bool,
+}
+
+impl PathStyle {
+    fn format(&self, path: &[u8]) -> Vec<u8> {
+        let path = match &self.prefix {
+            Some(prefix) => fs_reader::relative_path(path, prefix),
+            None => path.to_vec(),
+        };
+        if self.quote {
+            quote_path(&path).into_bytes()
+        } else {
+            path
+        }
+    }
+}
+
+/// Affiche l'état au format long, avec les sections et les conseils de `git status`.
+///
+/// # Paramètres
+/// - `hints` : ajoute les conseils entre parenthèses (`advice.statusHints`).
+/// - `untracked_listed` : faux avec `--untracked-files=no`.
+pub fn write_long<W: Write>(status: &Status, paths: &PathStyle, hints: bool, untracked_listed: bool, out: &mut W) -> Result<()> {
+    match &status.branch {
+        Some(branch) => writeln!(out, "On branch {}", branch)?,
+        None => {
+            let head = status.head.as_deref().unwrap_or(NULL_HASH);
+            writeln!(out, "HEAD detached at {}", rev_parse::abbreviate(head, ABBREV_LENGTH)?)?
+        }
+    }
+    let initial = status.head.is_none();
+    if initial {
+        writeln!(out, "\nNo commits yet\n")?;
+    }
+    let unstage_hint = if initial {
+        "  (use \"git rm --cached <file>...\" to unstage)"
+    } else {
+        "  (use \"git restore --staged <file>...\" to unstage)"
+    };
+
+    if !status.staged.is_empty() {
+        writeln!(out, "Changes to be committed:")?;
+        if hints {
+            writeln!(out, "{}", unstage_hint)?;
+        }
+        for change in &status.staged {
+            write_long_change(change, false, paths, out)?;
+        }
+        writeln!(out)?;
+    }
+
+    if !status.conflicts.is_empty() {
+        writeln!(out, "Unmerged paths:")?;
+        if hints {
+            writeln!(out, "{}", unstage_hint)?;
+            writeln!(out, "{}", resolution_hint(&status.conflicts))?;
+        }
+        for conflict in &status.conflicts {
+            let label = format!("{}:", conflict_label(conflict.stage_mask()));
+            write!(out, "\t{:<width$}", label, width = CONFLICT_LABEL_WIDTH)?;
+            out.write_all(&paths.format(&conflict.path))?;
+            writeln!(out)?;
+        }
+        writeln!(out)?;
+    }
+
+    if !status.unstaged.is_empty() {
+        writeln!(out, "Changes not staged for commit:")?;
+        if hints {
+            let add = if status.unstaged.iter().any(|change| change.new.is_none()) { "add/rm" } else { "add" };
+            writeln!(out, "  (use \"git {} <file>...\" to update what will be committed)", add)?;
+            writeln!(out, "  (use \"git restore <file>...\" to discard changes in working directory)")?;
+        }
+        for change in &status.unstaged {
+            write_long_change(change, true, paths, out)?;
+        }
+        writeln!(out)?;
+    }
+
+    if !status.untracked.is_empty() {
+        writeln!(out, "Untracked files:")?;
+        if hints {
+            writeln!(out, "  (use \"git add <file>...\" to include in what will be committed)")?;
+        }
+        for path in &status.untracked {
+            out.write_all(b"\t")?;
+            out.write_all(&paths.format(path))?;
+            writeln!(out)?;
+        }
+        writeln!(out)?;
+    } else if !untracked_listed && !status.staged.is_empty() {
+        writeln!(out, "Untracked files not listed{}", hint(hints, " (use -u option to show untracked files)"))?;
+    }
+
+    if !status.staged.is_empty() {
+        return Ok(());
+    }
+    if !status.unstaged.is_empty() || !status.conflicts.is_empty() {
+        writeln!(out, "no changes added to commit{}", hint(hints, " (use \"git add\" and/or \"git commit -a\")"))?;
+    } else if !status.untracked.is_empty() {
+        writeln!(out, "nothing added to commit but untracked files present{}", hint(hints, " (use \"git add\" to track)"))?;
+    } else if initial {
+        writeln!(out, "nothing to commit{}", hint(hints, " (create/copy files and use \"git add\" to track)"))?;
+    } else if !untracked_listed {
+        writeln!(out, "nothing to commit{}", hint(hints, " (use -u to show untracked files)"))?;
+    } else {
+        writeln!(out, "nothing to commit, working tree clean")?;
+    }
+    Ok(())
+}
+
+/// Affiche l'état au format court (`XY chemin`), qui est aussi celui de `--porcelain=v1`.
+///
+/// # Paramètres
+/// - `terminator` : fin de ligne ; avec `\0`, un renommage s'écrit `XY nouveau\0ancien\0`.
+pub fn write_short<W: Write>(status: &Status, paths: &PathStyle, terminator: u8, out: &mut W) -> Result<()> {
+    for (path, line) in merge_by_path(status) {
+        if let Some(conflict) = line.conflict {
+            write!(out, "{} ", conflict_code(conflict.stage_mask()))?;
+            out.write_all(&paths.format(path))?;
+            out.write_all(&[terminator])?;
+            continue;
+        }
+
+        let x = line.staged.map_or(' ', FileChange::status);
+        let y = line.unstaged.map_or(' ', FileChange::status);
+        write!(out, "{}{} ", x, y)?;
+        match line.staged.and_then(|change| change.old_path.as_deref()) {
+            Some(old_path) if terminator == b'\0' => {
+                out.write_all(&paths.format(path))?;
+                out.write_all(&[terminator])?;
+                out.write_all(&paths.format(old_path))?;
+            }
+            Some(old_path) => {
+                out.write_all(&paths.format(old_path))?;
+                out.write_all(b" -> ")?;
+                out.write_all(&paths.format(path))?;
+            }
+            None => out.write_all(&paths.format(path))?,
+        }
+        out.write_all(&[terminator])?;
+    }
+
+    for path in &status.untracked {
+        out.write_all(b"?? ")?;
+        out.write_all(&paths.format(path))?;
+        out.write_all(&[terminator])?;
+    }
+    Ok(())
+}
+
+/// Affiche l'état au format `--porcelain=v2`, avec modes et hashes de HEAD, de l'index
+/// et du répertoire de travail.
+pub fn write_porcelain_v2<W: Write>(status: &Status, paths: &PathStyle, terminator: u8, out: &mut W) -> Result<()> {
+    for (path, line) in merge_by_path(status) {
+        if let Some(conflict) = line.conflict {
+            let [base, ours, theirs] = &conflict.stages;
+            write!(out, "u {} N... {:06o} {:06o} {:06o} {:06o} {} {} {} ",
+                conflict_code(conflict.stage_mask()),
+                mode_of(base.as_ref()), mode_of(ours.as_ref()), mode_of(theirs.as_ref()), conflict.worktree_mode.unwrap_or(0),
+                hash_of(base.as_ref()), hash_of(ours.as_ref()), hash_of(theirs.as_ref()))?;
+            out.write_all(&paths.format(path))?;
+            out.write_all(&[terminator])?;
+            continue;
+        }
+
+        // Sans modification d'un côté, les deux versions qui l'encadrent sont identiques
+        let index = match (line.staged, line.unstaged) {
+            (_, Some(unstaged)) => unstaged.old.as_ref(),
+            (Some(staged), None) => staged.new.as_ref(),
+            (None, None) => None,
+        };
+        let head = line.staged.map_or(index, |staged| staged.old.as_ref());
+        let worktree_mode = line.unstaged.map_or(mode_of(index), |unstaged| mode_of(unstaged.new.as_ref()));
+
+        let x = line.staged.map_or('.', FileChange::status);
+        let y = line.unstaged.map_or('.', FileChange::status);
+        let old_path = line.staged.and_then(|change| change.old_path.as_deref());
+        write!(out, "{} {}{} {} {:06o} {:06o} {:06o} {} {} ",
+            if old_path.is_some() { '2' } else { '1' },
+            x, y, submodule_state(head, index, line.unstaged),
+            mode_of(head), mode_of(index), worktree_mode, hash_of(head), hash_of(index))?;
+        if old_path.is_some() {
+            // Seuls les renommages exacts sont détectés : la similarité est toujours de 100 %
+            write!(out, "R100 ")?;
+        }
+        out.write_all(&paths.format(path))?;
+        if let Some(old_path) = old_path {
+            out.write_all(if terminator == b'\0' { b"\0" } else { b"\t" })?;
+            out.write_all(&paths.format(old_path))?;
+        }
+        out.write_all(&[terminator])?;
+    }
+
+    for path in &status.untracked {
+        out.write_all(b"? ")?;
+        out.write_all(&paths.format(path))?;
+        out.write_all(&[terminator])?;
+    }
+    Ok(())
+}
+
+/// Ce que les formats courts affichent pour un chemin.
+#[derive(Default)]
+struct PathLine<'a> {
+    staged: Option<&'a FileChange>,
+    unstaged: Option<&'a FileChange>,
+    conflict: Option<&'a Conflict>,
+}
+
+/// Regroupe par chemin les modifications des deux comparaisons et les conflits.
+fn merge_by_path(status: &Status) -> BTreeMap<&[u8], PathLine<'_>> {
+    let mut lines: BTreeMap<&[u8], PathLine> = BTreeMap::new();
+    for change in &status.staged {
+        lines.entry(&change.path).or_default().staged = Some(change);
+    }
+    for change in &status.unstaged {
+        lines.entry(&change.path).or_default().unstaged = Some(change);
+    }
+    for conflict in &status.conflicts {
+        lines.entry(&conflict.path).or_default().conflict = Some(conflict);
+    }
+    lines
+}
+
+/// Écrit une ligne de modification du format long ; seul un sous-module modifié dans le
+/// répertoire de travail (`unstaged`) précise qu'il a de nouveaux commits.
+fn write_long_change<W: Write>(change: &FileChange, unstaged: bool, paths: &PathStyle, out: &mut W) -> Result<()> {
+    let label = match change.status() {
+        'A' => "new file:",
+        'D' => "deleted:",
+        'R' => "renamed:",
+        'T' => "typechange:",
+        _ => "modified:",
+    };
+    write!(out, "\t{:<width$}", label, width = CHANGE_LABEL_WIDTH)?;
+    if let Some(old_path) = &change.old_path {
+        out.write_all(&paths.format(old_path))?;
+        out.write_all(b" -> ")?;
+    }
+    out.write_all(&paths.format(&change.path))?;
+    let submodule_moved = unstaged && [&change.old, &change.new].iter()
+        .all(|version| version.as_ref().is_some_and(|version| version.mode == MODE_GITLINK));
+    if submodule_moved {
+        write!(out, " (new commits)")?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Code d'un conflit selon ses étapes présentes (1 : ancêtre, 2 : nous, 4 : eux).
+fn conflict_code(stage_mask: u8) -> &'static str {
+    match stage_mask {
+        1 => "DD",
+        2 => "AU",
+        3 => "UD",
+        4 => "UA",
+        5 => "DU",
+        6 => "AA",
+        _ => "UU",
+    }
+}
+
+fn conflict_label(stage_mask: u8) -> &'static str {
+    match stage_mask {
+        1 => "both deleted",
+        2 => "added by us",
+        3 => "deleted by them",
+        4 => "added by them",
+        5 => "deleted by us",
+        6 => "both added",
+        _ => "both modified",
+    }
+}
+
+/// Conseil de résolution des conflits, qui dépend des suppressions en jeu.
+fn resolution_hint(conflicts: &[Conflict]) -> &'static str {
+    let masks: Vec<u8> = conflicts.iter().map(Conflict::stage_mask).collect();
+    let both_deleted = masks.contains(&1);
+    let deleted_on_one_side = masks.iter().any(|&mask| mask == 3 || mask == 5);
+    let not_deleted = masks.iter().any(|&mask| !matches!(mask, 1 | 3 | 5));
+
+    if !both_deleted && !deleted_on_one_side {
+        "  (use \"git add <file>...\" to mark resolution)"
+    } else if both_deleted && !deleted_on_one_side && !not_deleted {
+        "  (use \"git rm <file>...\" to mark resolution)"
+    } else {
+        "  (use \"git add/rm <file>...\" as appropriate to mark resolution)"
+    }
+}
+
+/// État d'un sous-module pour `--porcelain=v2` : `N...` pour un fichier, `S` suivi de `C`
+/// quand son commit extrait a changé.
+fn submodule_state(head: Option<&FileVersion>, index: Option<&FileVersion>, unstaged: Option<&FileChange>) -> &'static str {
+    let is_submodule = [head, index].iter().any(|version| version.is_some_and(|version| version.mode == MODE_GITLINK));
+    match unstaged.and_then(|change| change.new.as_ref()) {
+        _ if !is_submodule => "N...",
+        Some(version) if version.mode == MODE_GITLINK => "SC..",
+        _ => "S...",
+    }
+}
+
+fn mode_of(version: Option<&FileVersion>) -> u32 {
+    version.map_or(0, |version| version.mode)
+}
+
+fn hash_of(version: Option<&FileVersion>) -> &str {
+    version.map_or(NULL_HASH, |version| &version.hash)
+}
+
+fn hint(enabled: bool, text: &'static str) -> &'static str {
+    if enabled { text } else { "" }
+}
@@ -0,0 +1,3 @@
+pub mod changes;
+pub mod format;
+pub mod status_manager;
@@ -0,0 +1,113 @@
+use crate::config::config_set::ConfigSet;
+use crate::dircache::index::{Index, IndexLock};
+use crate::fs_reader;
+use crate::ignore::ignore_rules::IgnoreRules;
+use crate::objects::object_manager;
+use crate::refs;
+use crate::repository;
+use crate::status::changes::{self, Conflict, FileChange, UntrackedMode};
+use crate::status::format::{self, PathStyle};
+use anyhow::Result;
+use std::io::Write;
+
+const BRANCH_PREFIX: &str = "refs/heads/";
+
+/// Format de sortie de la commande `status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFormat {
+    /// Sections commentées, chemins relatifs au répertoire courant.
+    Long,
+    /// Une ligne `XY chemin` par fichier, chemins relatifs au répertoire courant.
+    Short,
+    /// Format court stable pour les scripts, chemins relatifs à la racine.
+    PorcelainV1,
+    /// Format détaillé avec modes et hashes.
+    PorcelainV2,
+}
+
+/// Options de la commande `status`.
+#[derive(Debug)]
+pub struct StatusOptions {
+    pub format: StatusFormat,
+    /// Termine les entrées par un octet nul, sans guillemets ; implique `--porcelain`
+    /// pour le format long.
+    pub null_terminated: bool,
+    /// Fichiers non suivis à afficher ; par défaut `status.showUntrackedFiles`, sinon `normal`.
+    pub untracked: Option<UntrackedMode>,
+}
+
+/// État du dépôt : différences entre HEAD, l'index et le répertoire de travail.
+#[derive(Debug)]
+pub struct Status {
+    /// Branche courante, `None` si HEAD est détachée.
+    pub branch: Option<String>,
+    /// Commit de HEAD, `None` avant le premier commit.
+    pub head: Option<String>,
+    /// Différences entre HEAD et l'index.
+    pub staged: Vec<FileChange>,
+    /// Différences entre l'index et le répertoire de travail.
+    pub unstaged: Vec<FileChange>,
+    pub conflicts: Vec<Conflict>,
+    /// Fichiers non suivis, les répertoires avec un `/` final.
+    pub untracked: Vec<Vec<u8>>,
+}
+
+/// Affiche l'état du dépôt courant.
+///
+/// Comme git, l'index est au passage rafraîchi : les fichiers touchés sans être modifiés
+/// n'auront plus à être relus.
+pub fn status<W: Write>(options: &StatusOptions, out: &mut W) -> Result<()> {
+    let config = ConfigSet::load()?;
+    let untracked = match options.untracked {
+        Some(untracked) => untracked,
+        None => match config.get_string("status.showUntrackedFiles")?.as_deref() {
+            Some("no") => UntrackedMode::No,
+            Some("all") => UntrackedMode::All,
+            _ => UntrackedMode::Normal,
+        },
+    };
+    let status = collect_status(untracked)?;
+
+    let format = match options.format {
+        StatusFormat::Long if options.null_terminated => StatusFormat::PorcelainV1,
+        format => format,
+    };
+    // Les chemins ne sont relatifs au répertoire courant que dans les formats destinés à être lus
+    let relative = !options.null_terminated && format != StatusFormat::PorcelainV1;
+    let prefix = fs_reader::os_str_bytes(repository::current()?.prefix().as_os_str());
+    let paths = PathStyle { prefix: relative.then_some(prefix), quote: !options.null_terminated };
+    let terminator = if options.null_terminated { b'\0' } else { b'\n' };
+
+    match format {
+        StatusFormat::Long => {
+            let hints = config.get_bool("advice.statusHints")?.unwrap_or(true);
+            format::write_long(&status, &paths, hints, untracked != UntrackedMode::No, out)
+        }
+        StatusFormat::Short | StatusFormat::PorcelainV1 => format::write_short(&status, &paths, terminator, out),
+        StatusFormat::PorcelainV2 => format::write_porcelain_v2(&status, &paths, terminator, out),
+    }
+}
+
+/// Compare HEAD, l'index et le répertoire de travail.
+pub fn collect_status(untracked: UntrackedMode) -> Result<Status> {
+    let repository = repository::current()?;
+    // Rafraîchissement opportuniste, comme git : il n'a lieu que si le verrou est libre, et
+    // un dépôt en lecture seule n'empêche pas d'afficher l'état
+    let lock = IndexLock::acquire().ok();
+    let mut index = Index::load()?;
+
+    let head = refs::resolve_ref(refs::HEAD)?;
+    let branch = refs::read_symbolic_ref(refs::HEAD)?
+        .map(|target| target.strip_prefix(BRANCH_PREFIX).unwrap_or(&target).to_string());
+    let head_tree = head.as_deref().map(object_manager::read_commit_tree).transpose()?;
+
+    let staged = changes::diff_tree_to_index(head_tree.as_deref(), &index)?;
+    let (unstaged, refreshed) = changes::diff_index_to_worktree(&mut index)?;
+    if let Some(lock) = lock.filter(|_| refreshed) {
+        let _ = lock.commit(&index);
+    }
+    let conflicts = changes::conflicts(&index);
+    let untracked = changes::untracked_files(&index, &mut IgnoreRules::new(&repository)?, untracked)?;
+
+    Ok(Status { branch, head, staged, unstaged, conflicts, untracked })
+}
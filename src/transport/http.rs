@@ -0,0 +1,269 @@
+use crate::transport::pkt_line::{self, PktLine, DELIM_PKT, FLUSH_PKT};
+use anyhow::{anyhow, Result};
+use std::io::{self, Read, Write};
+
+const UPLOAD_PACK_SERVICE: &str = "git-upload-pack";
+const SIDEBAND_DATA: u8 = 1;
+const SIDEBAND_PROGRESS: u8 = 2;
+const SIDEBAND_ERROR: u8 = 3;
+/// Capacités demandées au serveur en protocole v1, lorsqu'il les annonce.
+const WANTED_V1_CAPABILITIES: [&str; 3] = ["side-band-64k", "ofs-delta", "no-progress"];
+
+/// Référence annoncée par le dépôt distant.
+#[derive(Debug, Clone)]
+pub struct RemoteRef {
+    pub name: String,
+    pub hash: String,
+    /// Cible de la référence lorsqu'elle est symbolique (typiquement `HEAD`).
+    pub symref_target: Option<String>,
+}
+
+/// Client du protocole git « smart HTTP » (versions 1 et 2) pour le service `git-upload-pack`.
+pub struct SmartHttpClient {
+    url: String,
+    protocol_version: u8,
+    capabilities: Vec<String>,
+    /// Références annoncées d'emblée par le serveur (protocole v1 uniquement).
+    advertised_refs: Vec<RemoteRef>,
+}
+
+impl SmartHttpClient {
+    /// Récupère l'annonce des références (`info/refs`) et détermine la version du protocole.
+    pub fn connect(url: &str) -> Result<SmartHttpClient> {
+        let url = url.trim_end_matches('/').to_string();
+        let response = ureq::get(&format!("{}/info/refs?service={}", url, UPLOAD_PACK_SERVICE))
+            .set("Git-Protocol", "version=2")
+            .call()?;
+
+        let content_type = response.content_type().to_string();
+        if content_type != format!("application/x-{}-advertisement", UPLOAD_PACK_SERVICE) {
+            return Err(anyhow!("{} does not support the smart HTTP protocol (content type {})", url, content_type));
+        }
+
+        let mut reader = response.into_reader();
+        let mut lines = vec![];
+        while let Some(line) = pkt_line::read_pkt_line(&mut reader)? {
+            lines.push(line);
+        }
+
+        // Le serveur peut précéder l'annonce d'une ligne "# service=..." suivie d'un flush
+        if lines.first().and_then(PktLine::as_text).is_some_and(|line| line.starts_with("# service=")) {
+            let flush = lines.iter().position(|line| *line == PktLine::Flush).unwrap_or(lines.len() - 1);
+            lines.drain(..=flush);
+        }
+
+        let mut client = SmartHttpClient {
+            url,
+            protocol_version: 1,
+            capabilities: vec![],
+            advertised_refs: vec![],
+        };
+
+        if lines.first().and_then(PktLine::as_text).as_deref() == Some("version 2") {
+            client.protocol_version = 2;
+            client.capabilities = lines.iter().skip(1).filter_map(PktLine::as_text).collect();
+        } else {
+            client.parse_v1_advertisement(&lines)?;
+        }
+
+        Ok(client)
+    }
+
+    pub fn protocol_version(&self) -> u8 {
+        self.protocol_version
+    }
+
+    /// Liste les branches, les tags et `HEAD` du dépôt distant.
+    pub fn list_refs(&self) -> Result<Vec<RemoteRef>> {
+        if self.protocol_version == 1 {
+            return Ok(self.advertised_refs.clone());
+        }
+
+        let mut request = self.v2_command_request("ls-refs");
+        for argument in ["peel", "symrefs", "ref-prefix HEAD", "ref-prefix refs/heads/", "ref-prefix refs/tags/"] {
+            request.extend_from_slice(&pkt_line::encode_text(argument));
+        }
+        request.extend_from_slice(FLUSH_PKT);
+
+        let mut reader = self.post_upload_pack(&request)?;
+        let mut refs = vec![];
+        while let Some(line) = pkt_line::read_pkt_line(&mut reader)? {
+            let Some(line) = line.as_text() else { break };
+            let mut parts = line.split(' ');
+            let (Some(hash), Some(name)) = (parts.next(), parts.next()) else {
+                return Err(anyhow!("Invalid ls-refs line: {}", line));
+            };
+            // Une branche "unborn" n'a pas encore de commit
+            if hash == "unborn" {
+                continue;
+            }
+            let symref_target = parts
+                .find_map(|attribute| attribute.strip_prefix("symref-target:"))
+                .map(str::to_string);
+            refs.push(RemoteRef { name: name.to_string(), hash: hash.to_string(), symref_target });
+        }
+
+        Ok(refs)
+    }
+
+    /// Demande au serveur un packfile contenant les objets voulus et l'écrit dans `out`.
+    pub fn fetch_pack<W: Write>(&self, wants: &[String], out: &mut W) -> Result<()> {
+        if wants.is_empty() {
+            return Err(anyhow!("Nothing to fetch"));
+        }
+
+        if self.protocol_version == 2 {
+            self.fetch_pack_v2(wants, out)
+        } else {
+            self.fetch_pack_v1(wants, out)
+        }
+    }
+
+    fn fetch_pack_v1<W: Write>(&self, wants: &[String], out: &mut W) -> Result<()> {
+        let capabilities: Vec<&str> = WANTED_V1_CAPABILITIES.iter()
+            .copied()
+            .filter(|capability| self.capabilities.iter().any(|c| c == capability))
+            .collect();
+
+        let mut request = vec![];
+        for (i, want) in wants.iter().enumerate() {
+            if i == 0 {
+                request.extend_from_slice(&pkt_line::encode_text(&format!("want {} {} {}", want, capabilities.join(" "), agent())));
+            } else {
+                request.extend_from_slice(&pkt_line::encode_text(&format!("want {}", want)));
+            }
+        }
+        request.extend_from_slice(FLUSH_PKT);
+        request.extend_from_slice(&pkt_line::encode_text("done"));
+
+        let mut reader = self.post_upload_pack(&request)?;
+
+        // Sans négociation, le serveur répond "NAK" avant d'envoyer le pack
+        match pkt_line::read_pkt_line(&mut reader)?.and_then(|line| line.as_text()) {
+            Some(line) if line == "NAK" || line.starts_with("ACK") => {}
+            Some(line) if line.starts_with("ERR ") => return Err(anyhow!("Remote error: {}", &line[4..])),
+            line => return Err(anyhow!("Unexpected upload-pack response: {:?}", line)),
+        }
+
+        if capabilities.contains(&"side-band-64k") {
+            read_sideband(&mut reader, out)
+        } else {
+            io::copy(&mut reader, out)?;
+            Ok(())
+        }
+    }
+
+    fn fetch_pack_v2<W: Write>(&self, wants: &[String], out: &mut W) -> Result<()> {
+        let mut request = self.v2_command_request("fetch");
+        request.extend_from_slice(&pkt_line::encode_text("ofs-delta"));
+        request.extend_from_slice(&pkt_line::encode_text("no-progress"));
+        for want in wants {
+            request.extend_from_slice(&pkt_line::encode_text(&format!("want {}", want)));
+        }
+        request.extend_from_slice(&pkt_line::encode_text("done"));
+        request.extend_from_slice(FLUSH_PKT);
+
+        let mut reader = self.post_upload_pack(&request)?;
+
+        // La réponse est découpée en sections ; seule "packfile" nous intéresse
+        while let Some(line) = pkt_line::read_pkt_line(&mut reader)? {
+            match line.as_text().as_deref() {
+                Some("packfile") => return read_sideband(&mut reader, out),
+                Some(section) if section.starts_with("ERR ") => return Err(anyhow!("Remote error: {}", &section[4..])),
+                Some(_) => {
+                    // Ignore le contenu des autres sections jusqu'à la suivante
+                    while let Some(PktLine::Data(_)) = pkt_line::read_pkt_line(&mut reader)? {}
+                }
+                None => break,
+            }
+        }
+
+        Err(anyhow!("Remote did not send a packfile"))
+    }
+
+    /// Construit l'en-tête d'une commande du protocole v2, jusqu'au séparateur des arguments.
+    fn v2_command_request(&self, command: &str) -> Vec<u8> {
+        let mut request = pkt_line::encode_text(&format!("command={}", command));
+        request.extend_from_slice(&pkt_line::encode_text(&agent()));
+        if self.capabilities.iter().any(|capability| capability == "object-format=sha1") {
+            request.extend_from_slice(&pkt_line::encode_text("object-format=sha1"));
+        }
+        request.extend_from_slice(DELIM_PKT);
+        request
+    }
+
+    fn post_upload_pack(&self, request: &[u8]) -> Result<Box<dyn Read + Send + Sync>> {
+        let mut http_request = ureq::post(&format!("{}/{}", self.url, UPLOAD_PACK_SERVICE))
+            .set("Content-Type", &format!("application/x-{}-request", UPLOAD_PACK_SERVICE))
+            .set("Accept", &format!("application/x-{}-result", UPLOAD_PACK_SERVICE));
+        if self.protocol_version == 2 {
+            http_request = http_request.set("Git-Protocol", "version=2");
+        }
+
+        Ok(http_request.send_bytes(request)?.into_reader())
+    }
+
+    /// Analyse l'annonce v1 : "<hash> <ref>\0<capacités>" puis une référence par ligne.
+    fn parse_v1_advertisement(&mut self, lines: &[PktLine]) -> Result<()> {
+        for line in lines {
+            let PktLine::Data(data) = line else { break };
+
+            let (reference, capabilities) = match data.iter().position(|&b| b == 0) {
+                Some(position) => (&data[..position], Some(&data[position + 1..])),
+                None => (&data[..], None),
+            };
+
+            if let Some(capabilities) = capabilities {
+                let capabilities = String::from_utf8_lossy(capabilities);
+                self.capabilities = capabilities.split_whitespace().map(str::to_string).collect();
+            }
+
+            let reference = String::from_utf8_lossy(reference);
+            let reference = reference.trim_end();
+            let (hash, name) = reference.split_once(' ')
+                .ok_or_else(|| anyhow!("Invalid ref advertisement: {}", reference))?;
+
+            // Dépôt vide ou référence pelée d'un tag annoté
+            if name == "capabilities^{}" || name.ends_with("^{}") {
+                continue;
+            }
+            self.advertised_refs.push(RemoteRef {
+                name: name.to_string(),
+                hash: hash.to_string(),
+                symref_target: None,
+            });
+        }
+
+        // Les références symboliques sont annoncées sous la forme "symref=HEAD:refs/heads/main"
+        for capability in &self.capabilities {
+            if let Some((name, target)) = capability.strip_prefix("symref=").and_then(|symref| symref.split_once(':')) {
+                if let Some(reference) = self.advertised_refs.iter_mut().find(|reference| reference.name == name) {
+                    reference.symref_target = Some(target.to_string());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn agent() -> String {
+    format!("agent=codecrafters-git/{}", env!("CARGO_PKG_VERSION"))
+}
+
+/// Démultiplexe un flux side-band : données du pack, progression et erreurs du serveur.
+fn read_sideband<R: Read, W: Write>(reader: &mut R, out: &mut W) -> Result<()> {
+    while let Some(line) = pkt_line::read_pkt_line(reader)? {
+        let PktLine::Data(data) = line else { break };
+        let Some((&band, payload)) = data.split_first() else { continue };
+
+        match band {
+            SIDEBAND_DATA => out.write_all(payload)?,
+            SIDEBAND_PROGRESS => io::stderr().write_all(payload)?,
+            SIDEBAND_ERROR => return Err(anyhow!("Remote error: {}", String::from_utf8_lossy(payload).trim_end())),
+            _ => return Err(anyhow!("Invalid side-band channel: {}", band)),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use anyhow::{anyhow, Result};
+use std::io::Read;
+
+/// Taille maximale d'une pkt-line, préfixe de longueur inclus.
+pub(crate) const MAX_PKT_LINE_SIZE: usize = 65520;
+
+pub(crate) const FLUSH_PKT: &[u8] = b"0000";
+pub(crate) const DELIM_PKT: &[u8] = b"0001";
+
+/// Une ligne du protocole git, préfixée par sa longueur en hexadécimal sur 4 caractères.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PktLine {
+    Data(Vec<u8>),
+    /// `0000` : fin d'un message.
+    Flush,
+    /// `0001` : séparateur de sections (protocole v2).
+    Delimiter,
+    /// `0002` : fin de réponse (protocole v2 sans état).
+    ResponseEnd,
+}
+
+impl PktLine {
+    /// Renvoie le contenu textuel de la ligne, sans le saut de ligne final.
+    pub fn as_text(&self) -> Option<String> {
+        match self {
+            PktLine::Data(data) => {
+                let data = data.strip_suffix(b"\n").unwrap_or(data);
+                Some(String::from_utf8_lossy(data).into_owned())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Encode des données en une pkt-line.
+pub fn encode(data: &[u8]) -> Vec<u8> {
+    let mut line = format!("{:04x}", data.len() + 4).into_bytes();
+    line.extend_from_slice(data);
+    line
+}
+
+/// Encode une ligne de texte en pkt-line, en ajoutant le saut de ligne final.
+pub fn encode_text(text: &str) -> Vec<u8> {
+    encode(format!("{}\n", text).as_bytes())
+}
+
+/// Lit la prochaine pkt-line du flux.
+///
+/// # Renvoie
+/// - `None` si le flux est terminé.
+pub fn read_pkt_line<R: Read>(reader: &mut R) -> Result<Option<PktLine>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    let length = std::str::from_utf8(&length)
+        .ok()
+        .and_then(|length| usize::from_str_radix(length, 16).ok())
+        .ok_or_else(|| anyhow!("Invalid pkt-line length: {:?}", String::from_utf8_lossy(&length)))?;
+
+    match length {
+        0 => Ok(Some(PktLine::Flush)),
+        1 => Ok(Some(PktLine::Delimiter)),
+        2 => Ok(Some(PktLine::ResponseEnd)),
+        3 => Err(anyhow!("Invalid pkt-line length: 3")),
+        _ if length > MAX_PKT_LINE_SIZE => Err(anyhow!("Invalid pkt-line length: {}", length)),
+        _ => {
+            let mut data = vec![0u8; length - 4];
+            reader.read_exact(&mut data)?;
+            Ok(Some(PktLine::Data(data)))
+        }
+    }
+}